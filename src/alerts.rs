@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::http;
+use crate::message::MessageType;
+use crate::session::Session;
+use crate::terminal::{self, OscProtocol};
+use crate::ui::format_duration;
+
+/// What a session's quiet spell means
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// Claude ended its turn with a reply
+    Finished,
+    /// Claude stopped on a tool call, most likely waiting for permission
+    NeedsInput,
+    /// Requests keep failing (see `ErrorBurstConfig`)
+    Errors,
+}
+
+impl AlertKind {
+    /// Notification title
+    pub fn title(self) -> &'static str {
+        match self {
+            AlertKind::Finished => "Claude finished",
+            AlertKind::NeedsInput => "Claude needs input",
+            AlertKind::Errors => "Claude keeps failing",
+        }
+    }
+}
+
+/// A session that just went quiet waiting for the user
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub session_id: String,
+    pub project: String,
+    pub name: String,
+    /// Time since the prompt that started the turn, when it is loaded
+    pub turn: Option<TimeDelta>,
+    /// The failures behind an `Errors` alert
+    pub burst: Option<ErrorBurst>,
+}
+
+impl Alert {
+    /// One-line summary used by every notifier
+    pub fn text(&self) -> String {
+        let after = self
+            .turn
+            .map_or(String::new(), |d| format!(" after {}", format_duration(d)));
+        match self.kind {
+            AlertKind::Finished => format!("[{}] {} finished{}", self.project, self.name, after),
+            AlertKind::NeedsInput => {
+                format!("[{}] {} needs input{}", self.project, self.name, after)
+            }
+            AlertKind::Errors => {
+                let (count, label) = self
+                    .burst
+                    .as_ref()
+                    .map_or((0, ""), |b| (b.count, b.label.as_str()));
+                format!(
+                    "[{}] {} hit {} API errors ({})",
+                    self.project, self.name, count, label
+                )
+            }
+        }
+    }
+}
+
+/// When API errors count as a burst that stalls a session
+/// (`[error_burst]` in config.toml)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ErrorBurstConfig {
+    /// Errors needed within the window
+    pub count: usize,
+    pub window_secs: u64,
+}
+
+impl Default for ErrorBurstConfig {
+    fn default() -> Self {
+        Self {
+            count: 3,
+            window_secs: 300,
+        }
+    }
+}
+
+/// API errors piling up in a session with no successful reply since
+#[derive(Debug, Clone)]
+pub struct ErrorBurst {
+    /// Errors in the window ending at the last one
+    pub count: usize,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+    /// Name of the last error, e.g. `overloaded_529`
+    pub label: String,
+}
+
+impl ErrorBurstConfig {
+    /// The burst `session` is stuck in, if any: at least `count` errors
+    /// within `window_secs` of the last one, and no reply with output
+    /// since
+    pub fn burst(&self, session: &Session) -> Option<ErrorBurst> {
+        let last = session.api_errors.last()?;
+        let recovered = session
+            .usage
+            .last()
+            .is_some_and(|p| p.timestamp > last.timestamp && p.usage.output > 0);
+        if recovered || self.count == 0 {
+            return None;
+        }
+        let window_start = last.timestamp - TimeDelta::seconds(self.window_secs as i64);
+        let in_window = session
+            .api_errors
+            .iter()
+            .rev()
+            .take_while(|e| e.timestamp >= window_start);
+        let (count, first) = in_window.fold((0, last.timestamp), |(n, _), e| (n + 1, e.timestamp));
+        (count >= self.count).then(|| ErrorBurst {
+            count,
+            first,
+            last: last.timestamp,
+            label: last.label.clone(),
+        })
+    }
+}
+
+/// Spots sessions whose turn just ended, once per turn, and sessions
+/// stuck on API errors, once per burst
+pub struct AlertDetector {
+    /// Turns ending before this happened before launch and are not reported
+    started: DateTime<Utc>,
+    /// Last message key already reported per session
+    reported: HashMap<String, String>,
+    /// Time of the last error already reported per session
+    reported_errors: HashMap<String, DateTime<Utc>>,
+    pub error_burst: ErrorBurstConfig,
+}
+
+impl AlertDetector {
+    pub fn new(error_burst: ErrorBurstConfig) -> Self {
+        Self {
+            started: Utc::now(),
+            reported: HashMap::new(),
+            reported_errors: HashMap::new(),
+            error_burst,
+        }
+    }
+
+    pub fn detect(&mut self, sessions: &HashMap<String, Session>) -> Vec<Alert> {
+        let recent = Utc::now() - TimeDelta::hours(1);
+        let mut alerts = Vec::new();
+        for session in sessions.values() {
+            if session.last_activity < recent || session.last_activity < self.started {
+                continue;
+            }
+            if let Some(burst) = self.error_burst.burst(session)
+                && burst.last >= self.started
+                && self
+                    .reported_errors
+                    .get(&session.id)
+                    .is_none_or(|&reported| burst.first > reported)
+            {
+                self.reported_errors.insert(session.id.clone(), burst.last);
+                alerts.push(Alert {
+                    kind: AlertKind::Errors,
+                    session_id: session.id.clone(),
+                    project: session.project_name().to_string(),
+                    name: session.display_name(),
+                    turn: None,
+                    burst: Some(burst),
+                });
+            }
+            let Some(last) = session
+                .messages
+                .iter()
+                .rev()
+                .find(|m| !matches!(m.msg_type, MessageType::Progress | MessageType::Other))
+            else {
+                continue;
+            };
+            let key = last.key();
+            if self.reported.get(&session.id) == Some(&key) || !session.awaiting_user() {
+                continue;
+            }
+            self.reported.insert(session.id.clone(), key);
+            let prompt = session.messages.iter().rev().find(|m| m.is_prompt());
+            alerts.push(Alert {
+                kind: if last.msg_type == MessageType::ToolUse {
+                    AlertKind::NeedsInput
+                } else {
+                    AlertKind::Finished
+                },
+                session_id: session.id.clone(),
+                project: session.project_name().to_string(),
+                name: session.display_name(),
+                turn: prompt.map(|p| last.timestamp - p.timestamp),
+                burst: None,
+            });
+        }
+        alerts
+    }
+}
+
+/// Which alerts a notifier passes on; flattened into each notifier's table
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AlertRules {
+    /// Project names to notify for (a trailing `*` matches a prefix);
+    /// empty means all
+    pub projects: Vec<String>,
+    pub exclude_projects: Vec<String>,
+    pub events: Vec<AlertKind>,
+    /// Skip `finished` alerts for turns shorter than this
+    pub min_turn_secs: u64,
+    /// Minimum time between notifications about the same session
+    pub rate_limit_secs: u64,
+}
+
+impl Default for AlertRules {
+    fn default() -> Self {
+        Self {
+            projects: Vec::new(),
+            exclude_projects: Vec::new(),
+            events: vec![AlertKind::Finished, AlertKind::NeedsInput],
+            min_turn_secs: 60,
+            rate_limit_secs: 300,
+        }
+    }
+}
+
+impl AlertRules {
+    fn allows(&self, alert: &Alert) -> bool {
+        let matches = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => alert.project.starts_with(prefix),
+            None => alert.project == *pattern,
+        };
+        let long_enough = alert.kind != AlertKind::Finished
+            || alert
+                .turn
+                .is_some_and(|t| t.num_seconds() >= self.min_turn_secs as i64);
+        self.events.contains(&alert.kind)
+            && (self.projects.is_empty() || self.projects.iter().any(matches))
+            && !self.exclude_projects.iter().any(matches)
+            && long_enough
+    }
+}
+
+/// Chat service a webhook URL belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookService {
+    Slack,
+    Discord,
+}
+
+/// A Slack or Discord incoming webhook (`[[webhooks]]` in config.toml)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WebhookConfig {
+    pub service: WebhookService,
+    pub url: String,
+    #[serde(flatten)]
+    pub rules: AlertRules,
+}
+
+/// Per-session rate limiting shared by notifiers: alerts inside the limit
+/// are counted and mentioned with the next one that goes out
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    last_sent: HashMap<String, Instant>,
+    suppressed: HashMap<String, usize>,
+}
+
+impl RateLimiter {
+    /// `Some(suppressed count)` if an alert for `session_id` may go out now
+    pub fn admit(&mut self, session_id: &str, limit: Duration) -> Option<usize> {
+        if self
+            .last_sent
+            .get(session_id)
+            .is_some_and(|t| t.elapsed() < limit)
+        {
+            *self.suppressed.entry(session_id.to_string()).or_default() += 1;
+            return None;
+        }
+        self.last_sent
+            .insert(session_id.to_string(), Instant::now());
+        Some(self.suppressed.remove(session_id).unwrap_or(0))
+    }
+}
+
+/// An ntfy topic for phone/desktop push (`[[ntfy]]` in config.toml)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NtfyConfig {
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+    pub topic: String,
+    /// 1 (min) to 5 (urgent); 3 is ntfy's default
+    #[serde(default = "default_ntfy_priority")]
+    pub priority: u8,
+    /// Access token for protected topics
+    pub token: Option<String>,
+    #[serde(flatten)]
+    pub rules: AlertRules,
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+fn default_ntfy_priority() -> u8 {
+    3
+}
+
+/// Desktop notifications through the terminal itself
+/// (`[terminal_notify]` in config.toml)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TerminalNotifyConfig {
+    #[serde(default)]
+    pub protocol: OscProtocol,
+    #[serde(flatten)]
+    pub rules: AlertRules,
+}
+
+/// A JSON POST announcing an alert
+struct Request {
+    url: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+/// Where alerts can be sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotifierConfig {
+    Webhook(WebhookConfig),
+    Ntfy(NtfyConfig),
+    Terminal(TerminalNotifyConfig),
+}
+
+impl NotifierConfig {
+    fn rules(&self) -> &AlertRules {
+        match self {
+            NotifierConfig::Webhook(c) => &c.rules,
+            NotifierConfig::Ntfy(c) => &c.rules,
+            NotifierConfig::Terminal(c) => &c.rules,
+        }
+    }
+
+    /// The request announcing `text`, for notifiers that post over HTTP
+    fn request(&self, alert: &Alert, text: String) -> Option<Request> {
+        match self {
+            NotifierConfig::Webhook(c) => {
+                let body = match c.service {
+                    WebhookService::Slack => json!({ "text": text }),
+                    WebhookService::Discord => json!({ "content": text }),
+                };
+                Some(Request {
+                    url: c.url.clone(),
+                    headers: Vec::new(),
+                    body: body.to_string(),
+                })
+            }
+            NotifierConfig::Ntfy(c) => {
+                // JSON publishing goes to the server root, topic in the body
+                let tag = match alert.kind {
+                    AlertKind::Finished => "white_check_mark",
+                    AlertKind::NeedsInput => "raising_hand",
+                    AlertKind::Errors => "warning",
+                };
+                let body = json!({
+                    "topic": c.topic,
+                    "title": alert.kind.title(),
+                    "message": text,
+                    "priority": c.priority.clamp(1, 5),
+                    "tags": [tag],
+                });
+                let headers = c
+                    .token
+                    .iter()
+                    .map(|t| ("Authorization".to_string(), format!("Bearer {}", t)))
+                    .collect();
+                Some(Request {
+                    url: c.server.clone(),
+                    headers,
+                    body: body.to_string(),
+                })
+            }
+            NotifierConfig::Terminal(_) => None,
+        }
+    }
+}
+
+pub struct Notifier {
+    pub config: NotifierConfig,
+    limiter: RateLimiter,
+}
+
+impl Notifier {
+    pub fn new(config: NotifierConfig) -> Self {
+        Self {
+            config,
+            limiter: RateLimiter::default(),
+        }
+    }
+
+    /// Send `alert` in the background if the rules and rate limit allow it
+    pub fn deliver(&mut self, alert: &Alert) {
+        let rules = self.config.rules();
+        if !rules.allows(alert) {
+            return;
+        }
+        let limit = Duration::from_secs(rules.rate_limit_secs);
+        let Some(suppressed) = self.limiter.admit(&alert.session_id, limit) else {
+            return;
+        };
+        let mut text = alert.text();
+        if suppressed > 0 {
+            text.push_str(&format!(" (+{} more since the last message)", suppressed));
+        }
+        if let NotifierConfig::Terminal(c) = &self.config {
+            if let Err(e) = terminal::notify(c.protocol, alert.kind.title(), &text) {
+                tracing::warn!(error = %e, "terminal notification failed");
+            }
+        } else if let Some(request) = self.config.request(alert, text) {
+            http::post_json_detached(request.url, request.headers, request.body);
+        }
+    }
+}
@@ -1,19 +1,259 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyModifiers, MouseEventKind};
-use ratatui::layout::Rect;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Margin, Rect};
 use ratatui::widgets::ListState;
 
-use crate::session::{self, Session};
-use crate::watcher::{SessionWatcher, WatchEvent};
+use crate::alerts::{AlertDetector, Notifier};
+use crate::classes::MessageClass;
+use crate::compare::Comparison;
+use crate::config::{Config, RuntimeOverrides, Workspace};
+use crate::filter::SessionFilter;
+use crate::git::{self, Commit, GitCache};
+use crate::heatmap::HeatMetric;
+use crate::hooks::Hooks;
+use crate::hyperlink::{self, Hyperlink};
+use crate::latency::ChartWindow;
+use crate::message::{self, MessageType, SessionMessage};
+use crate::otlp::Exporter;
+use crate::pricing::PriceTable;
+use crate::redact::{self, Redactor};
+use crate::refs::{self, FileRef, Link, Reference};
+use crate::render::RendererRegistry;
+use crate::replay::Replay;
+use crate::report::{self, Breakdown, Period, RankWindow, SizeMetric};
+use crate::script::Scripts;
+use crate::session::{self, IgnoreList, Session, SlugNames};
+use crate::snapshot::{self, SnapshotScope};
+use crate::state::{ClaudyState, UiState};
+use crate::statsd;
+use crate::terminal;
+use crate::timezone::{self, DisplayZone, TimeFormat};
+use crate::ui::{ChatStyle, SessionColumn};
+use crate::usage::UsageSnapshot;
+use crate::watcher::{self, SessionWatcher, WatchEvent, WatcherBackend};
+
+/// Number of sessions listed in the largest-sessions popup
+pub const LARGEST_SESSIONS_TOP: usize = 20;
+
+/// Lines scrolled in the chat pane per j/k or mouse wheel step
+const SCROLL_STEP: usize = 3;
+
+/// Upper bound for vim-style count prefixes
+const MAX_COUNT: usize = 9_999;
+
+/// Maximum gap between two clicks on the same row to count as a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Quiet period after the last input or file event before the idle tick
+/// rate may kick in
+const IDLE_AFTER: Duration = Duration::from_secs(5);
+
+/// Lines short of the end at which the chat view still counts as there
+const NEAR_BOTTOM_LINES: usize = 2;
+
+/// How long a status bar message stays visible
+const STATUS_MESSAGE_TTL: Duration = Duration::from_secs(5);
+
+/// Ticks a session's list entry stays in inverse video after an alert
+const FLASH_TICKS: u32 = 8;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FocusPanel {
     Sessions,
     Chat,
+    /// The side panel, while one is open
+    Side,
+}
+
+/// Full-screen view, switched with F1–F5, or 1–5 outside the monitor
+/// (where digits are counts); each has its own keys
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum View {
+    /// Sessions, chat and session info
+    Monitor,
+    /// Today's usage and sessions, and the activity heatmap
+    Dashboard,
+    /// Usage and estimated cost per month or week
+    Stats,
+    /// Every project with its sessions and spend
+    Projects,
+    /// The keys of each view
+    Help,
+}
+
+impl View {
+    pub const ALL: [View; 5] = [
+        View::Monitor,
+        View::Dashboard,
+        View::Stats,
+        View::Projects,
+        View::Help,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            View::Monitor => "Monitor",
+            View::Dashboard => "Dashboard",
+            View::Stats => "Stats",
+            View::Projects => "Projects",
+            View::Help => "Help",
+        }
+    }
+
+    /// The view `key` switches to from `current`, if it is a view key
+    fn for_key(key: KeyCode, current: View) -> Option<View> {
+        let n = match key {
+            KeyCode::F(n) => n as usize,
+            KeyCode::Char(c @ '1'..='9') if current != View::Monitor => c as usize - '0' as usize,
+            _ => return None,
+        };
+        View::ALL.get(n.checked_sub(1)?).copied()
+    }
+}
+
+/// Where the views other than the monitor were left, kept across switches
+pub struct ViewState {
+    pub dashboard_selected: usize,
+    pub heat_metric: HeatMetric,
+    pub stats_period: Period,
+    pub stats_breakdown: Breakdown,
+    pub stats_selected: usize,
+    pub projects_window: RankWindow,
+    pub projects_selected: usize,
+    /// Project whose sessions the leaderboard is drilled down into
+    pub project_open: Option<String>,
+    pub project_session_selected: usize,
+    pub help_scroll: usize,
+    /// Furthest the key list scrolls, as of the last draw
+    pub help_max_scroll: usize,
+}
+
+/// A row of the session list
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListRow {
+    /// Heading over sessions last active in one period (`date_separators`)
+    Heading(String),
+    /// Index into `sorted_session_ids`
+    Session(usize),
+}
+
+/// Panel shown beside the chat pane
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SidePanel {
+    /// Every URL and file reference cited in the session (`W`)
+    References,
+    /// The user's prompts, as a table of contents (`O`)
+    Outline,
+}
+
+/// What a status bar text prompt is collecting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptKind {
+    /// Claudy-managed title for a session
+    Rename,
+    /// Space-separated tag list for a session
+    Tags,
+    /// Text to find in the selected session's chat
+    Search,
+}
+
+impl PromptKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            PromptKind::Rename => "RENAME",
+            PromptKind::Tags => "TAGS",
+            PromptKind::Search => "FIND",
+        }
+    }
+}
+
+/// Single-line text input shown in the status bar
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    /// Session the input applies to
+    pub session_id: String,
+    pub text: String,
+}
+
+/// Work that needs the terminal handed over to another program; queued by
+/// key handlers and run from the event loop, which owns the terminal
+#[derive(Debug, Clone)]
+pub enum ExternalAction {
+    /// Edit a session's note in $VISUAL / $EDITOR
+    EditNote(String),
+    /// Open a file in the editor at a line
+    OpenFile(PathBuf, u32),
+}
+
+/// Action bound to a clickable line in the Session Info panel
+#[derive(Debug, Clone)]
+pub enum InfoAction {
+    CopyId(String),
+    OpenCwd(String),
+    FilterBranch(String),
+    FilterRepo(String),
+}
+
+/// How a file event affects the session list
+enum ListUpdate {
+    Unchanged,
+    /// Only this session's `last_activity` moved
+    Reorder(String),
+    /// Membership, dedup or filter results may differ: run `update_sort`
+    Rebuild,
+}
+
+/// Modal overlay drawn above the main layout; captures all key input
+#[derive(Debug, Clone, PartialEq)]
+pub enum Popup {
+    /// Largest sessions ranked by file size or tokens, with cleanup actions
+    LargestSessions {
+        metric: SizeMetric,
+        selected: usize,
+        /// Session id awaiting delete confirmation
+        confirm_delete: Option<String>,
+    },
+    /// Starred messages of the selected session
+    Starred { selected: usize },
+    /// Quick-pick menu of saved filters from the config file
+    SavedFilters { selected: usize },
+    /// Session list columns to show, hide and reorder
+    Columns { selected: usize },
+    /// The default projects directory and the configured workspaces
+    Workspaces { selected: usize },
+    /// Lines of the selected session's transcript that failed to parse
+    ParseErrors { selected: usize },
+    /// Configured message classes, to show or hide in the chat
+    MessageClasses { selected: usize },
+    /// Turn durations over time across sessions, per project or model
+    ResponseTimes {
+        window: ChartWindow,
+        breakdown: Breakdown,
+    },
+    /// Activity per day over the past months
+    Heatmap { metric: HeatMetric },
+    /// `App::comparison`, scrolled to turn `row`
+    Compare { row: usize },
+    /// Git commits made while the selected session was active
+    Commits { selected: usize },
+    /// `git show` of a commit the transcript reports, scrolled by `scroll`
+    /// lines
+    CommitDiff { hash: String, scroll: usize },
+    /// `file:line` references in the selected message
+    FileRefs { selected: usize },
+    /// URLs in the selected message, to open in the browser
+    Urls { selected: usize },
 }
 
 pub struct App {
@@ -24,23 +264,224 @@ pub struct App {
     pub chat_scroll: usize,
     pub chat_total_lines: usize,
     pub chat_scroll_locked_to_bottom: bool,
+    /// Messages arrived below a chat view scrolled up from the end;
+    /// cleared once the view is back at the end
+    pub new_messages_below: bool,
+    /// (message index, first visual line) for each rendered message,
+    /// rebuilt by `ui::draw_chat_stream` on every frame
+    pub chat_message_lines: Vec<(usize, usize)>,
+    /// Message cursor within the selected session's chat
+    pub selected_message: Option<usize>,
+    /// Message index the chat view should scroll into view on the next draw
+    pub chat_jump_to: Option<usize>,
+    /// Message index and line offset to put back at the top of the chat
+    /// view on the next draw, after earlier messages were backfilled
+    pub chat_anchor: Option<(usize, usize)>,
+    /// Where each session's chat was left when another was selected: the
+    /// top message's key and line offset, or None if it followed the end
+    chat_positions: HashMap<String, Option<(String, usize)>>,
+    /// Frozen chat view: (session id, number of messages shown). New messages
+    /// keep being parsed but aren't rendered until resumed.
+    pub paused: Option<(String, usize)>,
+    /// Playback of a session's history, if one is running (`v`)
+    pub replay: Option<Replay>,
+    /// Session marked with `D`, to be compared with the next one `D` is
+    /// pressed on
+    pub compare_base: Option<String>,
+    /// Turn-by-turn comparison shown by `Popup::Compare`
+    pub comparison: Option<Comparison>,
+    /// Active chat search; matches are highlighted in the chat pane
+    pub chat_search: Option<String>,
+    /// Cut messages off at `config.max_message_lines`; `T` toggles
+    pub truncate_messages: bool,
+    /// Transcript map beside the chat text; `M` toggles
+    pub show_minimap: bool,
+    /// Layout of the chat pane; `V` cycles
+    pub chat_style: ChatStyle,
+    /// Extra session list columns, in order; `l` opens the chooser
+    pub session_columns: Vec<SessionColumn>,
+    /// Blur prompts and working directories for screen sharing; `P` toggles
+    pub privacy: bool,
+    /// Long assistant messages expanded with `o`, by session id and
+    /// message index; everything else over `config.fold_lines` stays folded
+    pub unfolded_messages: HashMap<String, HashSet<usize>>,
+    /// Numeric prefix typed before a motion (vim-style `10j`)
+    pub pending_count: Option<usize>,
+    /// `]` or `[` typed with its count, waiting for the motion's target
+    pub pending_bracket: Option<(char, usize)>,
+    /// `g` typed, waiting for the second `g`: the item `gg` jumps to
+    pub pending_g: Option<usize>,
     pub filter_mode: bool,
     pub filter_text: Option<String>,
+    /// Position in `state.filter_history` while cycling with Up/Down
+    filter_history_pos: Option<usize>,
     pub show_active_only: bool,
+    /// Oldest activity first instead of newest; `I` toggles
+    pub sort_ascending: bool,
+    /// Message classes currently hidden from the chat view
+    pub hidden_classes: HashSet<String>,
     pub focus: FocusPanel,
+    /// Panel open beside the chat, if any
+    pub side_panel: Option<SidePanel>,
+    /// Selected row of the side panel
+    pub side_selected: usize,
+    /// First side panel row in view, as last drawn
+    pub side_offset: usize,
+    /// Links cited in the selected session, by session id and the message
+    /// count they were collected at
+    references: Option<(String, usize, Vec<Reference>)>,
+    pub view: View,
+    pub views: ViewState,
+    pub popup: Option<Popup>,
+    pub prompt: Option<Prompt>,
+    pending_external: Option<ExternalAction>,
+    /// Snapshot to write once the next frame is drawn
+    pending_snapshot: Option<SnapshotScope>,
     pub should_quit: bool,
+    /// Whether claudy captures the mouse; off allows native text selection
+    pub mouse_capture: bool,
+    /// Projects directory from `--path` or the config
     pub base_path: PathBuf,
+    /// Workspace being watched instead of `base_path`, if any
+    pub workspace: Option<String>,
+    /// Directories sessions are discovered and watched in
+    pub base_paths: Vec<PathBuf>,
     pub watcher: Option<SessionWatcher>,
+    pub state: ClaudyState,
+    pub config: Config,
+    /// Command-line and terminal settings laid over `config`, again after
+    /// each reload
+    overrides: RuntimeOverrides,
+    /// Model prices for cost estimates
+    pub prices: PriceTable,
     /// Stored layout rects for mouse hit testing
     pub session_list_area: Rect,
     pub chat_area: Rect,
+    pub side_area: Rect,
+    /// Area of the view other than the monitor, for paging
+    pub view_area: Rect,
+    /// OSC 8 links last written over the chat pane, rewritten when they
+    /// change or the screen is redrawn from scratch
+    hyperlinks: Vec<Hyperlink>,
+    /// Clickable Session Info lines, recorded by `ui::draw_session_info`
+    pub info_regions: Vec<(Rect, InfoAction)>,
+    status_message: Option<(String, Instant)>,
+    /// Time and list row of the last left click, for double-click detection
+    last_click: Option<(Instant, usize)>,
+    /// Last keyboard/mouse input or watcher event, for adaptive tick rate
+    last_event: Instant,
     last_index_refresh: Instant,
+    /// Whether any session file event arrived since the last missed-update check
+    file_events_seen: bool,
+    /// Watcher backend asked for, to start again on a workspace switch
+    backend: WatcherBackend,
+    /// Scan interval when polling, requested or as a fallback
+    poll_interval: Duration,
+    /// Native watching ran out of OS watches and fell back to polling
+    pub watch_limit_reached: bool,
+    /// Usage totals across all sessions, refreshed every tick
+    pub usage: UsageSnapshot,
+    /// Active and waiting session counts, refreshed every tick
+    pub counters: SessionCounters,
+    /// Zone times are shown in
+    pub zone: DisplayZone,
+    pub time_format: TimeFormat,
+    /// Sends session activity to an OpenTelemetry collector, if configured
+    otlp: Option<Exporter>,
+    /// Sends counters and gauges to a StatsD daemon, if configured
+    statsd: Option<statsd::Emitter>,
+    /// Notices sessions whose turn just ended
+    alert_detector: AlertDetector,
+    /// Webhooks and push services told about alerts
+    notifiers: Vec<Notifier>,
+    /// Git status of session working directories, queried in the background
+    pub git: GitCache,
+    /// Directory names of project slugs, decoded in the background
+    slug_names: SlugNames,
+    /// User commands run on new messages
+    hooks: Hooks,
+    /// Custom message renderers consulted by the chat view
+    pub renderers: RendererRegistry,
+    /// Engine for `expr:` filters and script columns
+    pub scripts: Scripts,
+    /// Masks secrets in displayed and copied content
+    pub redactor: Redactor,
+    /// Sessions flashing in the list after an alert, with ticks left
+    pub flashing: HashMap<String, u32>,
+}
+
+/// Live session counts shown in the status bar
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionCounters {
+    pub active: usize,
+    /// Active sessions where Claude looks done and the user is up
+    pub awaiting_user: usize,
+}
+
+impl SessionCounters {
+    fn compute(sessions: &HashMap<String, Session>) -> Self {
+        // Only recently touched sessions can be active; skip the stat for the rest
+        let recent = Utc::now() - chrono::TimeDelta::hours(1);
+        let mut counters = Self::default();
+        for session in sessions.values() {
+            if session.last_activity < recent || !session.is_active() {
+                continue;
+            }
+            counters.active += 1;
+            if session.awaiting_user() {
+                counters.awaiting_user += 1;
+            }
+        }
+        counters
+    }
 }
 
 impl App {
-    pub fn new(base_path: PathBuf) -> Result<Self> {
-        let sessions = session::discover_sessions(&base_path)?;
+    /// The app over `base_path` (or `workspace`), picking up where `state`
+    /// was left
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        base_path: PathBuf,
+        workspace: Option<String>,
+        config: Config,
+        overrides: RuntimeOverrides,
+        state: ClaudyState,
+        prices: PriceTable,
+        backend: WatcherBackend,
+        poll_interval: Duration,
+    ) -> Result<Self> {
+        let base_paths = match workspace {
+            Some(ref name) => config
+                .workspaces
+                .iter()
+                .find(|w| w.name == *name)
+                .map(Workspace::dirs)
+                .with_context(|| format!("No workspace {:?} in config", name))?,
+            None => vec![base_path.clone()],
+        };
+        let mut sessions = session::discover_all(&base_paths, &config.ignore_projects)?;
+        let mut slug_names = SlugNames::new();
+        slug_names.request(sessions.values());
+        sessions.values_mut().for_each(|s| {
+            state.apply(s);
+            s.apply_aliases(&config.project_aliases, &slug_names);
+        });
         let sorted_ids = sort_session_ids(&sessions);
+        let (zone, zone_warning) = DisplayZone::parse_or_local(&config.timezone);
+        let usage = UsageSnapshot::compute(&sessions, &prices, &zone, Utc::now());
+        let counters = SessionCounters::compute(&sessions);
+        let time_format = config.time_format;
+        let otlp = config.otlp.clone().map(Exporter::start);
+        let statsd = config.statsd.clone().map(statsd::Emitter::start);
+        let notifiers = config.notifiers().into_iter().map(Notifier::new).collect();
+        let hooks = Hooks::new(config.hooks.clone());
+        let renderers = RendererRegistry::from_rules(&config.renderers);
+        let scripts = Scripts::new(config.script_columns.clone());
+        let hidden_classes = initially_hidden(&config.message_classes);
+        let redactor = Redactor::new(config.redaction.clone());
+        let privacy = config.privacy_mode;
+        let minimap = config.minimap;
+        let alert_detector = AlertDetector::new(config.error_burst.clone());
 
         let selected = sorted_ids.first().cloned();
         let mut list_state = ListState::default();
@@ -49,59 +490,500 @@ impl App {
         }
 
         // Start file watcher
-        let watcher = SessionWatcher::new(base_path.clone()).ok();
+        let (watcher, watcher_warning, watch_limit_reached) =
+            start_watcher(&base_paths, &config.ignore_projects, backend, poll_interval);
 
-        Ok(Self {
+        let mut app = Self {
             sessions,
             sorted_session_ids: sorted_ids,
             selected_session: selected,
             list_state,
             chat_scroll: 0,
             chat_total_lines: 0,
+            new_messages_below: false,
             chat_scroll_locked_to_bottom: true,
+            chat_message_lines: Vec::new(),
+            selected_message: None,
+            chat_jump_to: None,
+            chat_anchor: None,
+            chat_positions: HashMap::new(),
+            paused: None,
+            replay: None,
+            compare_base: None,
+            comparison: None,
+            truncate_messages: true,
+            show_minimap: minimap,
+            chat_style: config.chat_style,
+            session_columns: config.session_columns.clone(),
+            privacy,
+            unfolded_messages: HashMap::new(),
+            chat_search: None,
+            pending_count: None,
+            pending_bracket: None,
+            pending_g: None,
             filter_mode: false,
             filter_text: None,
+            filter_history_pos: None,
             show_active_only: false,
+            sort_ascending: false,
+            hidden_classes,
             focus: FocusPanel::Sessions,
+            side_panel: None,
+            side_selected: 0,
+            side_offset: 0,
+            references: None,
+            view: View::Monitor,
+            views: ViewState {
+                dashboard_selected: 0,
+                heat_metric: HeatMetric::Tokens,
+                stats_period: Period::Month,
+                stats_breakdown: Breakdown::Total,
+                stats_selected: 0,
+                projects_window: RankWindow::Month,
+                projects_selected: 0,
+                project_open: None,
+                project_session_selected: 0,
+                help_scroll: 0,
+                help_max_scroll: 0,
+            },
+            popup: None,
+            prompt: None,
+            pending_external: None,
+            pending_snapshot: None,
             should_quit: false,
+            mouse_capture: true,
             base_path,
+            workspace,
+            base_paths,
             watcher,
+            state,
+            config,
+            overrides,
+            prices,
             session_list_area: Rect::default(),
             chat_area: Rect::default(),
+            side_area: Rect::default(),
+            view_area: Rect::default(),
+            hyperlinks: Vec::new(),
+            info_regions: Vec::new(),
+            status_message: watcher_warning
+                .or(zone_warning)
+                .map(|msg| (msg, Instant::now())),
+            last_click: None,
+            last_event: Instant::now(),
             last_index_refresh: Instant::now(),
+            file_events_seen: false,
+            backend,
+            poll_interval,
+            watch_limit_reached,
+            usage,
+            counters,
+            zone,
+            time_format,
+            otlp,
+            statsd,
+            alert_detector,
+            notifiers,
+            git: GitCache::new(),
+            slug_names,
+            hooks,
+            renderers,
+            scripts,
+            redactor,
+            flashing: HashMap::new(),
+        };
+        app.restore_ui_state();
+        Ok(app)
+    }
+
+    /// Bring back the selection, filters and chat position saved on exit
+    fn restore_ui_state(&mut self) {
+        let ui = std::mem::take(&mut self.state.ui);
+        self.filter_text = ui.filter.filter(|f| !f.is_empty());
+        self.show_active_only = ui.active_only;
+        self.sort_ascending = ui.sort_ascending;
+        if let Some(columns) = ui.session_columns {
+            self.session_columns = columns;
+        }
+        if let Some(id) = ui.selected_session
+            && self.sessions.contains_key(&id)
+        {
+            self.selected_session = Some(id);
+        }
+        self.update_sort();
+        self.restore_chat_position(ui.chat_top);
+    }
+
+    /// The selected session's chat position: the key of the message at the
+    /// top of the view and how far into it, or None while it follows the end
+    fn chat_position(&self) -> Option<(String, usize)> {
+        if self.chat_scroll_locked_to_bottom {
+            return None;
+        }
+        self.chat_top_message().and_then(|(idx, offset)| {
+            let msg = self.selected()?.messages.get(idx)?;
+            Some((msg.key(), offset))
         })
     }
 
+    /// Scroll the selected session's chat back to `position`. Messages
+    /// before the loaded tail can't be anchored to; those stay at the bottom.
+    fn restore_chat_position(&mut self, position: Option<(String, usize)>) {
+        self.chat_scroll_locked_to_bottom = true;
+        self.chat_anchor = None;
+        if let Some((key, offset)) = position
+            && let Some(idx) = self
+                .selected()
+                .and_then(|s| s.messages.iter().position(|m| m.key() == key))
+        {
+            self.chat_scroll_locked_to_bottom = false;
+            self.chat_anchor = Some((idx, offset));
+        }
+    }
+
+    /// Show session `id` in the chat, remembering where the current one was
+    /// scrolled to and returning to where `id` was left
+    fn switch_session(&mut self, id: Option<String>) {
+        if id == self.selected_session {
+            return;
+        }
+        if let Some(current) = self.selected_session.clone() {
+            let position = self.chat_position();
+            self.chat_positions.insert(current, position);
+        }
+        self.selected_session = id;
+        self.selected_message = None;
+        self.chat_jump_to = None;
+        self.new_messages_below = false;
+        let position = self
+            .selected_session
+            .as_ref()
+            .and_then(|id| self.chat_positions.get(id))
+            .cloned()
+            .flatten();
+        self.restore_chat_position(position);
+    }
+
+    /// Record the current view in claudy state and write it out
+    fn save_ui_state(&mut self) {
+        self.state.ui = UiState {
+            selected_session: self.selected_session.clone(),
+            chat_top: self.chat_position(),
+            filter: self.filter_text.clone(),
+            active_only: self.show_active_only,
+            sort_ascending: self.sort_ascending,
+            session_columns: (self.session_columns != self.config.session_columns)
+                .then(|| self.session_columns.clone()),
+        };
+        if let Err(e) = self.state.save() {
+            tracing::warn!(error = %e, "failed to save UI state");
+        }
+    }
+
+    /// Message at the top of the chat viewport and how many of its lines
+    /// are scrolled past, as of the last draw
+    fn chat_top_message(&self) -> Option<(usize, usize)> {
+        self.chat_message_lines
+            .iter()
+            .rev()
+            .find(|(_, start)| *start <= self.chat_scroll)
+            .map(|&(idx, start)| (idx, self.chat_scroll - start))
+    }
+
+    /// Show a transient message in the status bar
+    pub fn set_status(&mut self, msg: impl Into<String>) {
+        self.status_message = Some((msg.into(), Instant::now()));
+    }
+
+    /// Current status bar message, if it hasn't expired yet
+    pub fn status_message(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, at)| at.elapsed() < STATUS_MESSAGE_TTL)
+            .map(|(msg, _)| msg.as_str())
+    }
+
+    /// Ask for the selected session's git status, and its commits while
+    /// they are shown
+    fn refresh_git(&mut self) {
+        let Some(session) = self
+            .selected_session
+            .as_ref()
+            .and_then(|id| self.sessions.get(id))
+        else {
+            return;
+        };
+        let Some(cwd) = session.cwd.as_deref() else {
+            return;
+        };
+        self.git.refresh(cwd);
+        if matches!(self.popup, Some(Popup::Commits { .. }))
+            && let Some(window) = git::activity_window(session)
+        {
+            self.git.refresh_log(cwd, window);
+        }
+    }
+
     pub fn tick(&mut self) {
         // Process file watcher events
-        if let Some(ref watcher) = self.watcher {
+        if let Some(ref mut watcher) = self.watcher {
             let events = watcher.poll();
+            if !events.is_empty() {
+                self.last_event = Instant::now();
+            }
+            // A busy session emits many events per tick: handle each path once,
+            // in arrival order, re-reading it fully if it was created or removed
+            let mut changed: Vec<(PathBuf, bool)> = Vec::new();
+            let mut config_changed = false;
+            let mut watch_limit = false;
             for evt in events {
-                match evt {
-                    WatchEvent::FileModified(path) => {
-                        self.handle_file_modified(&path);
+                let (path, created) = match evt {
+                    WatchEvent::FileModified(path) => (path, false),
+                    WatchEvent::FileCreated(path) | WatchEvent::FileRemoved(path) => (path, true),
+                    WatchEvent::ConfigChanged => {
+                        config_changed = true;
+                        continue;
                     }
-                    WatchEvent::FileCreated(path) => {
-                        self.handle_file_created(&path);
+                    WatchEvent::WatchLimitReached => {
+                        watch_limit = true;
+                        continue;
                     }
+                };
+                match changed.iter_mut().find(|(p, _)| *p == path) {
+                    Some(entry) => entry.1 |= created,
+                    None => changed.push((path, created)),
+                }
+            }
+            self.file_events_seen |= !changed.is_empty();
+            let mut rebuild = false;
+            let mut moved = Vec::new();
+            for (path, created) in changed {
+                // What matters is whether the path is still there at the end
+                let update = if !path.exists() {
+                    self.handle_file_removed(&path)
+                } else if created {
+                    self.handle_file_created(&path)
+                } else {
+                    self.handle_file_modified(&path)
+                };
+                match update {
+                    ListUpdate::Unchanged => {}
+                    ListUpdate::Reorder(id) => moved.push(id),
+                    ListUpdate::Rebuild => rebuild = true,
                 }
             }
+            // Expressions can look at anything (tokens, cost), so any change
+            // may flip them
+            if self
+                .filter_text
+                .as_deref()
+                .is_some_and(|f| SessionFilter::parse(f).script().is_some())
+            {
+                rebuild |= !moved.is_empty();
+            }
+            // Sessions that only got newer are moved in place; anything that
+            // may change dedup or filter results needs the full pass
+            if !rebuild {
+                rebuild = !moved.iter().all(|id| self.reposition_session(id));
+            }
+            if rebuild {
+                self.update_sort();
+            }
+            // One save often produces several events; reload once
+            if config_changed {
+                self.reload_config();
+            }
+            if watch_limit && !self.watch_limit_reached {
+                self.watch_limit_reached = true;
+                self.switch_to_polling(&format!(
+                    "Watch limit reached ({})",
+                    watcher::WATCH_LIMIT_FIX
+                ));
+            }
+        }
+
+        self.usage = UsageSnapshot::compute(&self.sessions, &self.prices, &self.zone, Utc::now());
+        self.counters = SessionCounters::compute(&self.sessions);
+        if let Some(ref mut otlp) = self.otlp {
+            otlp.tick(&self.sessions);
+        }
+        if let Some(ref mut statsd) = self.statsd {
+            statsd.tick(self.counters);
+        }
+        if let Some(ref mut replay) = self.replay
+            && let Some(session) = self.sessions.get(&replay.session_id)
+        {
+            replay.advance(&session.messages);
+        }
+        self.flashing.retain(|_, ticks| {
+            *ticks -= 1;
+            *ticks > 0
+        });
+        let alerts = self.alert_detector.detect(&self.sessions);
+        if !alerts.is_empty()
+            && self.config.attention_bell
+            && let Err(e) = terminal::bell()
+        {
+            tracing::warn!(error = %e, "failed to ring the bell");
+        }
+        for alert in alerts {
+            tracing::info!(session = %alert.session_id, kind = ?alert.kind, "session alert");
+            if self.config.attention_flash {
+                self.flashing.insert(alert.session_id.clone(), FLASH_TICKS);
+            }
+            for notifier in &mut self.notifiers {
+                notifier.deliver(&alert);
+            }
+        }
+
+        self.git.poll();
+        self.refresh_git();
+
+        if self.slug_names.poll() {
+            for session in self.sessions.values_mut() {
+                session.apply_aliases(&self.config.project_aliases, &self.slug_names);
+            }
+            self.update_sort();
         }
 
         // Periodically refresh sessions-index.json metadata (every 10s)
         if self.last_index_refresh.elapsed() >= Duration::from_secs(10) {
-            session::refresh_index_metadata(&self.base_path, &mut self.sessions);
+            for base_path in &self.base_paths {
+                session::refresh_index_metadata(base_path, &mut self.sessions);
+            }
+            self.check_missed_updates();
             self.last_index_refresh = Instant::now();
             self.update_sort();
         }
     }
 
-    fn handle_file_modified(&mut self, path: &Path) {
+    /// Catch up on session files that grew without a watcher event. If that
+    /// happens while native events are silent altogether, the filesystem
+    /// (e.g. NFS) doesn't deliver them: switch to the polling backend.
+    fn check_missed_updates(&mut self) {
+        let grown: Vec<PathBuf> = self
+            .sessions
+            .values()
+            .filter(|s| fs::metadata(&s.file_path).is_ok_and(|m| m.len() > s.file_offset))
+            .map(|s| s.file_path.clone())
+            .collect();
+        let events_seen = std::mem::take(&mut self.file_events_seen);
+        if grown.is_empty() {
+            return;
+        }
+        for path in &grown {
+            self.handle_file_modified(path);
+        }
+        let native = self
+            .watcher
+            .as_ref()
+            .is_some_and(|w| w.poll_interval.is_none());
+        if native && !events_seen {
+            tracing::warn!(
+                missed = grown.len(),
+                "no watcher events for changed files; switching to polling"
+            );
+            self.switch_to_polling("No file events received");
+        }
+    }
+
+    /// Replace the native watcher with the polling backend, explaining why
+    fn switch_to_polling(&mut self, reason: &str) {
+        let interval = self.poll_interval;
+        match SessionWatcher::new(
+            &self.base_paths,
+            &self.config.ignore_projects,
+            Config::path(),
+            Some(interval),
+        ) {
+            Ok(w) => {
+                self.watcher = Some(w);
+                self.set_status(format!("{}; polling every {}s", reason, interval.as_secs()));
+            }
+            Err(e) => tracing::warn!(error = %e, "polling watcher failed"),
+        }
+    }
+
+    /// Re-read the config file, keeping the current config if it's invalid
+    fn reload_config(&mut self) {
+        match Config::load() {
+            Ok(mut config) => {
+                self.overrides.apply(&mut config);
+                tracing::info!("config reloaded");
+                if self.otlp.as_ref().map(|e| &e.config) != config.otlp.as_ref() {
+                    self.otlp = config.otlp.clone().map(Exporter::start);
+                }
+                if self.statsd.as_ref().map(|e| &e.config) != config.statsd.as_ref() {
+                    self.statsd = config.statsd.clone().map(statsd::Emitter::start);
+                }
+                if self.hooks.config != config.hooks {
+                    self.hooks = Hooks::new(config.hooks.clone());
+                }
+                if self.config.renderers != config.renderers {
+                    self.renderers = RendererRegistry::from_rules(&config.renderers);
+                }
+                if self.scripts.columns != config.script_columns {
+                    self.scripts = Scripts::new(config.script_columns.clone());
+                }
+                if self.redactor.config != config.redaction {
+                    self.redactor = Redactor::new(config.redaction.clone());
+                }
+                if self.config.message_classes != config.message_classes {
+                    self.hidden_classes = initially_hidden(&config.message_classes);
+                }
+                let aliases_changed = self.config.project_aliases != config.project_aliases;
+                let ignore_changed = self.config.ignore_projects != config.ignore_projects;
+                // Editing the columns in the config overrides the chooser
+                if self.config.session_columns != config.session_columns {
+                    self.session_columns = config.session_columns.clone();
+                }
+                self.alert_detector.error_burst = config.error_burst.clone();
+                let notifiers = config.notifiers();
+                if !self.notifiers.iter().map(|n| &n.config).eq(&notifiers) {
+                    self.notifiers = notifiers.into_iter().map(Notifier::new).collect();
+                }
+                let (zone, zone_warning) = DisplayZone::parse_or_local(&config.timezone);
+                self.zone = zone;
+                self.config = config;
+                if aliases_changed {
+                    for session in self.sessions.values_mut() {
+                        session.apply_aliases(&self.config.project_aliases, &self.slug_names);
+                    }
+                    // Project filters match the new names
+                    self.update_sort();
+                }
+                self.set_status(zone_warning.unwrap_or_else(|| "Config reloaded".to_string()));
+                if ignore_changed {
+                    self.watcher = None;
+                    let (watcher, warning, watch_limit) = start_watcher(
+                        &self.base_paths,
+                        &self.config.ignore_projects,
+                        self.backend,
+                        self.poll_interval,
+                    );
+                    self.watcher = watcher;
+                    self.watch_limit_reached = watch_limit;
+                    self.refresh_all();
+                    if let Some(warning) = warning {
+                        self.set_status(warning);
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::warn!(error = format!("{:#}", e), "config reload failed");
+                self.set_status(format!("Config not reloaded: {:#}", e));
+            }
+        }
+    }
+
+    /// Read appended lines and report how the session list is affected
+    fn handle_file_modified(&mut self, path: &Path) -> ListUpdate {
         // Check if sessions-index.json changed
         if path.file_name().and_then(|n| n.to_str()) == Some("sessions-index.json") {
-            session::refresh_index_metadata(&self.base_path, &mut self.sessions);
-            self.update_sort();
-            return;
+            for base_path in &self.base_paths {
+                session::refresh_index_metadata(base_path, &mut self.sessions);
+            }
+            return ListUpdate::Rebuild;
         }
 
         let session_id = path
@@ -111,25 +993,157 @@ impl App {
             .to_string();
 
         if let Some(session) = self.sessions.get_mut(&session_id) {
-            let _ = session::read_new_lines(session);
-            // Auto-scroll to bottom when chat pane is focused and viewing this session
-            if self.focus == FocusPanel::Chat
-                && self.selected_session.as_deref() == Some(&session_id)
+            let activity = session.last_activity;
+            let fields = filter_fields(session);
+            let usage_len = session.usage.len();
+            let error_count = session.parse_diagnostics.count;
+            let had_cwd = session.cwd.is_some();
+            match session::read_new_lines(session) {
+                Ok(messages) => {
+                    // An alias may be keyed by the name the working directory gives
+                    if !had_cwd && session.cwd.is_some() {
+                        session.apply_aliases(&self.config.project_aliases, &self.slug_names);
+                    }
+                    let points = &session.usage[usage_len..];
+                    self.hooks.run(session, &messages);
+                    if let Some(ref mut otlp) = self.otlp {
+                        otlp.record(session, &messages, points);
+                    }
+                    if let Some(ref mut statsd) = self.statsd {
+                        let new_errors = session.parse_diagnostics.count - error_count;
+                        statsd.record(session, &messages, points, new_errors);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(session = %session_id, error = %e, "failed to read new lines")
+                }
+            }
+            // Follow new messages only when the view was at the end anyway;
+            // otherwise leave the reader where they are and say so
+            if self.selected_session.as_deref() == Some(&session_id)
+                && self.paused.is_none()
+                && !self.chat_scroll_locked_to_bottom
             {
-                self.chat_scroll_locked_to_bottom = true;
+                if self.chat_at_bottom() {
+                    self.chat_scroll_locked_to_bottom = true;
+                } else {
+                    self.new_messages_below = true;
+                }
+            }
+            let session = &self.sessions[&session_id];
+            if filter_fields(session) != fields {
+                ListUpdate::Rebuild
+            } else if session.last_activity != activity {
+                ListUpdate::Reorder(session_id)
+            } else {
+                ListUpdate::Unchanged
             }
-            self.update_sort();
         } else {
-            self.handle_file_created(path);
+            self.handle_file_created(path)
         }
     }
 
-    fn handle_file_created(&mut self, path: &Path) {
-        if let Ok(Some(session)) = session::discover_single_session(path) {
-            let id = session.id.clone();
-            self.sessions.insert(id, session);
-            self.update_sort();
+    /// Parse a new (or replaced) session file, or every transcript in a
+    /// project directory moved into the tree
+    fn handle_file_created(&mut self, path: &Path) -> ListUpdate {
+        if path.is_dir() {
+            let Ok(entries) = fs::read_dir(path) else {
+                return ListUpdate::Unchanged;
+            };
+            let mut update = ListUpdate::Unchanged;
+            for entry in entries.flatten() {
+                let file = entry.path();
+                let is_agent = file
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("agent-"));
+                if !is_agent && let ListUpdate::Rebuild = self.handle_file_created(&file) {
+                    update = ListUpdate::Rebuild;
+                }
+            }
+            return update;
+        }
+        let Ok(Some(mut session)) = session::discover_single_session(path) else {
+            return ListUpdate::Unchanged;
+        };
+        tracing::info!(session = %session.id, "new session discovered");
+        self.state.apply(&mut session);
+        self.slug_names.request([&session]);
+        session.apply_aliases(&self.config.project_aliases, &self.slug_names);
+        if let Some(ref mut otlp) = self.otlp {
+            otlp.record(&session, &session.messages, &session.usage);
+        }
+        if let Some(ref mut statsd) = self.statsd {
+            statsd.record(&session, &session.messages, &session.usage, 0);
+        }
+        self.hooks.run(&session, &session.messages);
+        let id = session.id.clone();
+        self.sessions.insert(id, session);
+        ListUpdate::Rebuild
+    }
+
+    /// Drop sessions whose transcript was deleted, directly or with its directory
+    fn handle_file_removed(&mut self, path: &Path) -> ListUpdate {
+        let removed: Vec<String> = self
+            .sessions
+            .values()
+            .filter(|s| s.file_path.starts_with(path))
+            .map(|s| s.id.clone())
+            .collect();
+        if removed.is_empty() {
+            return ListUpdate::Unchanged;
+        }
+        for id in &removed {
+            tracing::info!(session = %id, "session file removed");
+            self.forget_session(id);
+        }
+        ListUpdate::Rebuild
+    }
+
+    /// Drop session `id` and everything kept about it while it was listed
+    fn forget_session(&mut self, id: &str) {
+        self.sessions.remove(id);
+        self.unfolded_messages.remove(id);
+        self.chat_positions.remove(id);
+        if self.paused.as_ref().is_some_and(|(p, _)| p == id) {
+            self.paused = None;
+        }
+        if self.replay.as_ref().is_some_and(|r| r.session_id == id) {
+            self.replay = None;
         }
+        if self.compare_base.as_deref() == Some(id) {
+            self.compare_base = None;
+        }
+    }
+
+    /// Order of two sessions in the list: `newest_first`, or its reverse
+    fn list_order(&self, a: &Session, b: &Session) -> Ordering {
+        let order = newest_first(a, b);
+        if self.sort_ascending {
+            order.reverse()
+        } else {
+            order
+        }
+    }
+
+    /// Move a listed session to its place by `list_order`, keeping the
+    /// selection on the same session. Returns false if it isn't listed.
+    fn reposition_session(&mut self, id: &str) -> bool {
+        let Some(from) = self.sorted_session_ids.iter().position(|s| s == id) else {
+            return false;
+        };
+        let id = self.sorted_session_ids.remove(from);
+        let session = &self.sessions[&id];
+        let to = self.sorted_session_ids.partition_point(|other| {
+            self.list_order(&self.sessions[other], session) == Ordering::Less
+        });
+        self.sorted_session_ids.insert(to, id);
+        if let Some(ref sel) = self.selected_session
+            && let Some(idx) = self.sorted_session_ids.iter().position(|s| s == sel)
+        {
+            self.list_state.select(Some(idx));
+        }
+        true
     }
 
     fn update_sort(&mut self) {
@@ -173,24 +1187,29 @@ impl App {
             });
         }
 
-        // Apply text filter
-        if let Some(ref filter) = self.filter_text {
-            let filter_lower = filter.to_lowercase();
+        // Apply text/tag/script filter
+        if let Some(filter) = self.filter_text.as_deref().map(SessionFilter::parse) {
+            let script = filter.script().map(|expr| self.scripts.compile(expr));
+            if let Some(Err(ref e)) = script {
+                self.set_status(format!("Filter expression: {:#}", e));
+            }
             self.sorted_session_ids.retain(|id| {
-                if let Some(s) = self.sessions.get(id) {
-                    s.display_name().to_lowercase().contains(&filter_lower)
-                        || s.id.contains(&filter_lower)
-                        || s.summary
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&filter_lower)
-                } else {
-                    false
-                }
+                self.sessions.get(id).is_some_and(|s| {
+                    filter.matches(s)
+                        && match script {
+                            None => true,
+                            Some(Ok(ref ast)) => self.scripts.matches(ast, s, &self.prices),
+                            Some(Err(_)) => false,
+                        }
+                })
             });
         }
 
+        // Deduplicated newest first above, so the latest copy is the one kept
+        if self.sort_ascending {
+            self.sorted_session_ids.reverse();
+        }
+
         // Restore selection
         if let Some(ref sel) = old_selected {
             if let Some(idx) = self.sorted_session_ids.iter().position(|id| id == sel) {
@@ -202,6 +1221,13 @@ impl App {
                 self.list_state.select(None);
                 self.selected_session = None;
             }
+        } else if !self.sorted_session_ids.is_empty() {
+            // Selection was lost (e.g. a filter briefly matched nothing)
+            self.list_state.select(Some(0));
+            self.selected_session = self.sorted_session_ids.first().cloned();
+        }
+        if self.selected_session != old_selected {
+            self.selected_message = None;
         }
     }
 
@@ -210,80 +1236,1823 @@ impl App {
             self.handle_filter_key(key);
             return;
         }
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key);
+            return;
+        }
+        if self.popup.is_some() {
+            self.handle_popup_key(key);
+            return;
+        }
+        // Second key of a `]u`/`[u` motion; anything else cancels it
+        if let Some((bracket, n)) = self.pending_bracket.take() {
+            if key.code == KeyCode::Char('u') {
+                self.jump_to_prompt(bracket == ']', n);
+            }
+            return;
+        }
+        // Second key of `gg`; anything else cancels it
+        if let Some(target) = self.pending_g.take() {
+            if key.code == KeyCode::Char('g') {
+                self.goto_item(target);
+            }
+            return;
+        }
+        if let Some(view) = View::for_key(key.code, self.view) {
+            self.view = view;
+            self.pending_count = None;
+            return;
+        }
+        if self.view != View::Monitor {
+            self.handle_view_key(key);
+            return;
+        }
+
+        if let KeyCode::Char(c @ '0'..='9') = key.code
+            && !(c == '0' && self.pending_count.is_none())
+        {
+            let digit = c as usize - '0' as usize;
+            let count = self.pending_count.unwrap_or(0);
+            self.pending_count = Some((count * 10 + digit).min(MAX_COUNT));
+            return;
+        }
+        let count = self.pending_count.take();
+        let n = count.unwrap_or(1);
+        if self.handle_replay_key(key) {
+            return;
+        }
+        if self.focus == FocusPanel::Side && self.handle_side_panel_key(key) {
+            return;
+        }
 
         match key.code {
             KeyCode::Char('q') => self.should_quit = true,
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.should_quit = true;
             }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_chat_down(self.chat_page_height() / 2 * n);
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_chat_up(self.chat_page_height() / 2 * n);
+            }
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_chat_down(self.chat_page_height() * n);
+            }
+            KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_chat_up(self.chat_page_height() * n);
+            }
             KeyCode::Tab => {
                 self.focus = match self.focus {
                     FocusPanel::Sessions => FocusPanel::Chat,
-                    FocusPanel::Chat => FocusPanel::Sessions,
+                    FocusPanel::Chat if self.side_panel.is_some() => FocusPanel::Side,
+                    FocusPanel::Chat | FocusPanel::Side => FocusPanel::Sessions,
                 };
                 Self::drain_events();
             }
             KeyCode::Char('j') | KeyCode::Down => match self.focus {
-                FocusPanel::Sessions => self.move_selection(1),
-                FocusPanel::Chat => self.scroll_chat_down(3),
+                FocusPanel::Sessions => self.move_selection(n as i32),
+                FocusPanel::Chat | FocusPanel::Side => self.scroll_chat_down(SCROLL_STEP * n),
             },
             KeyCode::Char('k') | KeyCode::Up => match self.focus {
-                FocusPanel::Sessions => self.move_selection(-1),
-                FocusPanel::Chat => self.scroll_chat_up(3),
+                FocusPanel::Sessions => self.move_selection(-(n as i32)),
+                FocusPanel::Chat | FocusPanel::Side => self.scroll_chat_up(SCROLL_STEP * n),
             },
+            KeyCode::Enter
+                if self.focus == FocusPanel::Chat && self.chat_style == ChatStyle::Oneline =>
+            {
+                self.expand_oneline();
+            }
             KeyCode::Enter => self.select_current(),
             KeyCode::Char('r') => self.refresh_all(),
             KeyCode::Char('a') => {
                 self.show_active_only = !self.show_active_only;
                 self.update_sort();
             }
+            KeyCode::Char('I') => {
+                self.sort_ascending = !self.sort_ascending;
+                self.update_sort();
+                self.set_status(if self.sort_ascending {
+                    "Oldest first"
+                } else {
+                    "Newest first"
+                });
+            }
             KeyCode::Char('/') => {
                 self.filter_mode = true;
                 self.filter_text = Some(String::new());
+                self.filter_history_pos = None;
             }
-            KeyCode::Char('G') => {
-                self.chat_scroll_locked_to_bottom = true;
+            KeyCode::Char('F') => self.popup = Some(Popup::SavedFilters { selected: 0 }),
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let current = self
+                    .workspace
+                    .as_ref()
+                    .and_then(|name| self.config.workspaces.iter().position(|w| w.name == *name))
+                    .map_or(0, |i| i + 1);
+                self.popup = Some(Popup::Workspaces { selected: current });
             }
-            KeyCode::Char('g') => {
-                self.chat_scroll = 0;
-                self.chat_scroll_locked_to_bottom = false;
+            // gg/G (Home/End) jump to the first/last item of the focused pane; with
+            // a count (`5G`, `5gg`) they jump to the nth session or message
+            KeyCode::Char('G') | KeyCode::End => match count {
+                Some(c) => self.goto_item(c - 1),
+                None => self.goto_last(),
+            },
+            KeyCode::Char('g') => self.pending_g = Some(count.map_or(0, |c| c - 1)),
+            KeyCode::Home => self.goto_item(count.map_or(0, |c| c - 1)),
+            KeyCode::PageDown => self.scroll_chat_down(self.chat_page_height() * n),
+            KeyCode::PageUp => self.scroll_chat_up(self.chat_page_height() * n),
+            KeyCode::Char(c @ (']' | '[')) => self.pending_bracket = Some((c, n)),
+            KeyCode::Char('J') => self.move_message_cursor(n as i32),
+            KeyCode::Char('K') => self.move_message_cursor(-(n as i32)),
+            KeyCode::Char('s') => self.toggle_star(),
+            KeyCode::Char('S') => self.popup = Some(Popup::Starred { selected: 0 }),
+            KeyCode::Char('H') => {
+                self.popup = Some(Popup::Commits { selected: 0 });
+                self.refresh_git();
             }
-            KeyCode::PageDown => self.scroll_chat_down(20),
-            KeyCode::PageUp => self.scroll_chat_up(20),
-            _ => {}
-        }
-    }
-
-    fn handle_filter_key(&mut self, key: event::KeyEvent) {
-        match key.code {
-            KeyCode::Esc => {
-                self.filter_mode = false;
-                self.filter_text = None;
-                self.update_sort();
+            KeyCode::Char('E') => self.popup = Some(Popup::ParseErrors { selected: 0 }),
+            KeyCode::Char('C') => self.popup = Some(Popup::MessageClasses { selected: 0 }),
+            KeyCode::Char('l') => self.popup = Some(Popup::Columns { selected: 0 }),
+            KeyCode::Char('h') => {
+                self.popup = Some(Popup::Heatmap {
+                    metric: HeatMetric::Tokens,
+                })
             }
-            KeyCode::Enter => {
-                self.filter_mode = false;
-                self.update_sort();
+            KeyCode::Char('B') => self.view = View::Stats,
+            KeyCode::Char('R') => {
+                if let Some(session) = self.selected() {
+                    let text = session
+                        .user_title
+                        .clone()
+                        .or_else(|| session.custom_title.clone())
+                        .unwrap_or_default();
+                    self.prompt = Some(Prompt {
+                        kind: PromptKind::Rename,
+                        session_id: session.id.clone(),
+                        text,
+                    });
+                }
             }
-            KeyCode::Backspace => {
-                if let Some(ref mut text) = self.filter_text {
-                    text.pop();
-                    if text.is_empty() {
-                        self.filter_text = None;
-                    }
+            KeyCode::Char('N') => {
+                if let Some(id) = self.selected_session.clone() {
+                    self.pending_external = Some(ExternalAction::EditNote(id));
                 }
-                self.update_sort();
             }
-            KeyCode::Char(c) => {
-                if let Some(ref mut text) = self.filter_text {
-                    text.push(c);
-                } else {
-                    self.filter_text = Some(c.to_string());
+            KeyCode::Char('t') => {
+                if let Some(session) = self.selected() {
+                    let text = session
+                        .tags
+                        .iter()
+                        .map(|t| format!("#{}", t))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    self.prompt = Some(Prompt {
+                        kind: PromptKind::Tags,
+                        session_id: session.id.clone(),
+                        text,
+                    });
                 }
-                self.update_sort();
             }
-            _ => {}
-        }
+            KeyCode::Char('f') => {
+                if let Some(id) = self.selected_session.clone() {
+                    self.prompt = Some(Prompt {
+                        kind: PromptKind::Search,
+                        session_id: id,
+                        text: self.chat_search.clone().unwrap_or_default(),
+                    });
+                }
+            }
+            KeyCode::Char('p') | KeyCode::Char(' ') => self.toggle_pause(),
+            KeyCode::Char('T') => self.toggle_truncation(),
+            KeyCode::Char('M') => self.show_minimap = !self.show_minimap,
+            KeyCode::Char('Z') => {
+                self.time_format = self.time_format.toggle();
+                self.set_status(format!("Times: {}", self.time_format.label()));
+            }
+            KeyCode::Char('V') => {
+                self.chat_style = self.chat_style.next();
+                self.set_status(format!("Chat style: {}", self.chat_style.label()));
+            }
+            KeyCode::Char('v') => self.toggle_replay(),
+            KeyCode::Char('D') => self.compare_selected(),
+            KeyCode::Char('L') => {
+                self.popup = Some(Popup::ResponseTimes {
+                    window: ChartWindow::Week,
+                    breakdown: Breakdown::Project,
+                });
+            }
+            KeyCode::Char('P') => self.toggle_privacy(),
+            KeyCode::Char('x') => self.pending_snapshot = Some(SnapshotScope::Chat),
+            KeyCode::Char('X') => self.pending_snapshot = Some(SnapshotScope::Frame),
+            KeyCode::Char('o') => self.toggle_fold(),
+            KeyCode::Char('i') => self.show_selected_commit(),
+            KeyCode::Char('e') => {
+                if self.selected_message.is_some() {
+                    self.popup = Some(Popup::FileRefs { selected: 0 });
+                } else {
+                    self.set_status("No message selected (J/K to select)");
+                }
+            }
+            KeyCode::Char('w') => {
+                if self.selected_message.is_some() {
+                    self.popup = Some(Popup::Urls { selected: 0 });
+                } else {
+                    self.set_status("No message selected (J/K to select)");
+                }
+            }
+            KeyCode::Char('W') => self.toggle_side_panel(SidePanel::References),
+            KeyCode::Char('O') => self.toggle_side_panel(SidePanel::Outline),
+            KeyCode::Char('y') => self.copy_selected_message(),
+            KeyCode::Char('m') => self.toggle_mouse_capture(),
+            KeyCode::Char('U') => {
+                self.popup = Some(Popup::LargestSessions {
+                    metric: SizeMetric::FileSize,
+                    selected: 0,
+                    confirm_delete: None,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_prompt_key(&mut self, key: event::KeyEvent) {
+        let Some(ref mut prompt) = self.prompt else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                if let Some(prompt) = self.prompt.take() {
+                    self.submit_prompt(prompt);
+                }
+            }
+            KeyCode::Backspace => {
+                prompt.text.pop();
+            }
+            KeyCode::Char(c) => prompt.text.push(c),
+            _ => {}
+        }
+    }
+
+    fn submit_prompt(&mut self, prompt: Prompt) {
+        let text = prompt.text.trim();
+        let value = (!text.is_empty()).then(|| text.to_string());
+        match prompt.kind {
+            PromptKind::Rename => {
+                self.state.set_title(&prompt.session_id, value.clone());
+                if let Some(session) = self.sessions.get_mut(&prompt.session_id) {
+                    session.user_title = value.clone();
+                }
+                self.save_state(if value.is_some() {
+                    "Title saved"
+                } else {
+                    "Title cleared"
+                });
+            }
+            PromptKind::Tags => {
+                let mut tags: Vec<String> = Vec::new();
+                for word in text.split_whitespace() {
+                    let tag = word.trim_start_matches('#');
+                    if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+                        tags.push(tag.to_string());
+                    }
+                }
+                self.state.set_tags(&prompt.session_id, tags.clone());
+                if let Some(session) = self.sessions.get_mut(&prompt.session_id) {
+                    session.tags = tags;
+                }
+                self.save_state("Tags saved");
+                self.update_sort();
+            }
+            PromptKind::Search => {
+                self.chat_search = value;
+                if self.chat_search.is_some() {
+                    self.jump_to_next_match();
+                }
+            }
+        }
+    }
+
+    /// Persist claudy state, reporting the outcome in the status bar
+    fn save_state(&mut self, success_msg: &str) {
+        match self.state.save() {
+            Ok(()) => self.set_status(success_msg),
+            Err(e) => self.set_status(format!("Failed to save state: {}", e)),
+        }
+    }
+
+    /// Suspend the TUI, run the queued external program, then restore
+    fn run_external(&mut self, terminal: &mut ratatui::DefaultTerminal, action: ExternalAction) {
+        crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture).ok();
+        ratatui::restore();
+
+        match action {
+            ExternalAction::EditNote(id) => {
+                let current = self.state.notes.get(&id).cloned().unwrap_or_default();
+                match edit_in_editor(&id, &current) {
+                    Ok(text) => {
+                        let note = text.trim();
+                        self.state
+                            .set_note(&id, (!note.is_empty()).then(|| note.to_string()));
+                        self.save_state("Note saved");
+                    }
+                    Err(e) => self.set_status(format!("Editor failed: {}", e)),
+                }
+            }
+            ExternalAction::OpenFile(path, line) => {
+                if let Err(e) = open_in_editor(&path, line) {
+                    self.set_status(format!("Editor failed: {}", e));
+                }
+            }
+        }
+
+        *terminal = ratatui::init();
+        self.hyperlinks.clear();
+        if self.mouse_capture {
+            crossterm::execute!(std::io::stdout(), crossterm::event::EnableMouseCapture).ok();
+        }
+    }
+
+    /// The currently selected session, if any
+    pub fn selected(&self) -> Option<&Session> {
+        self.selected_session
+            .as_ref()
+            .and_then(|id| self.sessions.get(id))
+    }
+
+    fn handle_popup_key(&mut self, key: event::KeyEvent) {
+        match self.popup.clone() {
+            Some(Popup::LargestSessions {
+                metric,
+                selected,
+                confirm_delete,
+            }) => self.handle_largest_sessions_key(key, metric, selected, confirm_delete),
+            Some(Popup::Starred { selected }) => self.handle_starred_key(key, selected),
+            Some(Popup::SavedFilters { selected }) => self.handle_saved_filters_key(key, selected),
+            Some(Popup::Columns { selected }) => self.handle_columns_key(key, selected),
+            Some(Popup::Workspaces { selected }) => self.handle_workspaces_key(key, selected),
+            Some(Popup::ParseErrors { selected }) => self.handle_parse_errors_key(key, selected),
+            Some(Popup::MessageClasses { selected }) => {
+                self.handle_message_classes_key(key, selected)
+            }
+            Some(Popup::ResponseTimes { window, breakdown }) => {
+                self.handle_response_times_key(key, window, breakdown)
+            }
+            Some(Popup::Heatmap { metric }) => self.handle_heatmap_key(key, metric),
+            Some(Popup::Compare { row }) => self.handle_compare_key(key, row),
+            Some(Popup::Commits { selected }) => self.handle_commits_key(key, selected),
+            Some(Popup::CommitDiff { hash, scroll }) => {
+                self.handle_commit_diff_key(key, hash, scroll)
+            }
+            Some(Popup::FileRefs { selected }) => self.handle_file_refs_key(key, selected),
+            Some(Popup::Urls { selected }) => self.handle_urls_key(key, selected),
+            None => {}
+        }
+    }
+
+    fn handle_starred_key(&mut self, key: event::KeyEvent, selected: usize) {
+        let starred = self.starred_message_indices();
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Enter => {
+                if let Some(&idx) = starred.get(selected) {
+                    self.popup = None;
+                    self.selected_message = Some(idx);
+                    self.chat_jump_to = Some(idx);
+                    self.focus = FocusPanel::Chat;
+                }
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                (selected + 1).min(starred.len().saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::Starred { selected });
+    }
+
+    /// Commits listed by `Popup::Commits`, if the answer is in
+    pub fn session_commits(&self) -> Option<&[Commit]> {
+        let session = self.selected()?;
+        let (since, _) = git::activity_window(session)?;
+        self.git.log(session.cwd.as_deref()?, since)?
+    }
+
+    fn handle_commits_key(&mut self, key: event::KeyEvent, selected: usize) {
+        let len = self.session_commits().map_or(0, |c| c.len());
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('H') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Enter => {
+                // Jump to the last message before the commit landed
+                let time = self
+                    .session_commits()
+                    .and_then(|c| c.get(selected))
+                    .map(|c| c.time);
+                let idx = time.zip(self.selected()).and_then(|(time, session)| {
+                    session
+                        .messages
+                        .partition_point(|m| m.timestamp <= time)
+                        .checked_sub(1)
+                });
+                if let Some(idx) = idx {
+                    self.popup = None;
+                    self.selected_message = Some(idx);
+                    self.chat_jump_to = Some(idx);
+                    self.focus = FocusPanel::Chat;
+                }
+                return;
+            }
+            KeyCode::Char('y') => {
+                if let Some(hash) = self
+                    .session_commits()
+                    .and_then(|c| c.get(selected))
+                    .map(|c| c.hash.clone())
+                {
+                    match crate::clipboard::copy(&hash) {
+                        Ok(()) => self.set_status(format!("Copied {}", &hash[..7])),
+                        Err(e) => self.set_status(format!("Copy failed: {}", e)),
+                    }
+                }
+                selected
+            }
+            KeyCode::Char('j') | KeyCode::Down => (selected + 1).min(len.saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::Commits { selected });
+    }
+
+    /// `file:line` references in the selected message
+    pub fn selected_file_refs(&self) -> Vec<FileRef> {
+        self.selected_message
+            .and_then(|idx| self.selected()?.messages.get(idx))
+            .map_or_else(Vec::new, |m| refs::file_refs(&m.content))
+    }
+
+    fn handle_file_refs_key(&mut self, key: event::KeyEvent, selected: usize) {
+        let file_refs = self.selected_file_refs();
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('e') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Enter => {
+                if let Some(file_ref) = file_refs.get(selected) {
+                    let cwd = self.selected().and_then(|s| s.cwd.as_deref());
+                    let path = file_ref.resolve(cwd);
+                    self.popup = None;
+                    self.pending_external = Some(ExternalAction::OpenFile(path, file_ref.line));
+                }
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                (selected + 1).min(file_refs.len().saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::FileRefs { selected });
+    }
+
+    /// http(s) URLs in the selected message
+    pub fn selected_urls(&self) -> Vec<String> {
+        self.selected_message
+            .and_then(|idx| self.selected()?.messages.get(idx))
+            .map_or_else(Vec::new, |m| refs::urls(&m.content))
+    }
+
+    fn handle_urls_key(&mut self, key: event::KeyEvent, selected: usize) {
+        let urls = self.selected_urls();
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('w') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Enter => {
+                if let Some(url) = urls.get(selected) {
+                    self.popup = None;
+                    match crate::opener::open(url) {
+                        Ok(()) => self.set_status(format!("Opened {}", url)),
+                        Err(e) => self.set_status(format!("Open failed: {}", e)),
+                    }
+                }
+                return;
+            }
+            KeyCode::Char('y') => {
+                if let Some(url) = urls.get(selected) {
+                    match crate::clipboard::copy(url) {
+                        Ok(()) => self.set_status(format!("Copied {}", url)),
+                        Err(e) => self.set_status(format!("Copy failed: {}", e)),
+                    }
+                }
+                selected
+            }
+            KeyCode::Char('j') | KeyCode::Down => (selected + 1).min(urls.len().saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::Urls { selected });
+    }
+
+    /// Open `panel` beside the chat and focus it, or close it if it's open
+    fn toggle_side_panel(&mut self, panel: SidePanel) {
+        if self.side_panel == Some(panel) {
+            self.side_panel = None;
+            if self.focus == FocusPanel::Side {
+                self.focus = FocusPanel::Chat;
+            }
+        } else {
+            self.side_panel = Some(panel);
+            self.side_selected = 0;
+            self.side_offset = 0;
+            self.focus = FocusPanel::Side;
+        }
+    }
+
+    /// Rows in the open side panel
+    fn side_panel_len(&mut self) -> usize {
+        match self.side_panel {
+            Some(SidePanel::References) => self.references().len(),
+            Some(SidePanel::Outline) => self.outline().len(),
+            None => 0,
+        }
+    }
+
+    /// Keys the focused side panel handles itself; false leaves the key
+    /// to the global bindings (`g`/`G` included, which go by focus)
+    fn handle_side_panel_key(&mut self, key: event::KeyEvent) -> bool {
+        let last = self.side_panel_len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.side_selected = (self.side_selected + 1).min(last);
+                self.follow_side_selection();
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.side_selected = self.side_selected.saturating_sub(1);
+                self.follow_side_selection();
+            }
+            KeyCode::Enter => self.activate_side_row(),
+            KeyCode::Char('o') if self.side_panel == Some(SidePanel::References) => {
+                self.open_reference()
+            }
+            KeyCode::Esc => {
+                self.side_panel = None;
+                self.focus = FocusPanel::Chat;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// The outline scrolls the chat along as its selection moves
+    fn follow_side_selection(&mut self) {
+        if self.side_panel == Some(SidePanel::Outline) {
+            self.activate_side_row();
+        }
+    }
+
+    fn activate_side_row(&mut self) {
+        match self.side_panel {
+            Some(SidePanel::References) => self.jump_to_reference(),
+            Some(SidePanel::Outline) => {
+                if let Some(&idx) = self.outline().get(self.side_selected) {
+                    self.selected_message = Some(idx);
+                    self.chat_jump_to = Some(idx);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Links cited in the selected session, collected again when it has
+    /// new messages
+    pub fn references(&mut self) -> &[Reference] {
+        let Some(session) = self.selected() else {
+            return &[];
+        };
+        let stale = self
+            .references
+            .as_ref()
+            .is_none_or(|(id, count, _)| *id != session.id || *count != session.messages.len());
+        if stale {
+            let references = refs::references(&session.messages);
+            self.references = Some((session.id.clone(), session.messages.len(), references));
+        }
+        self.references.as_ref().map_or(&[], |(_, _, r)| r)
+    }
+
+    /// Indices of the selected session's prompts, for the outline
+    pub fn outline(&self) -> Vec<usize> {
+        self.selected().map_or_else(Vec::new, |session| {
+            session
+                .messages
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.is_prompt())
+                .map(|(idx, _)| idx)
+                .collect()
+        })
+    }
+
+    /// Move the message cursor to the next mention of the selected
+    /// reference, wrapping around to the first
+    fn jump_to_reference(&mut self) {
+        let selected = self.side_selected;
+        let Some(mentions) = self.references().get(selected).map(|r| r.messages.clone()) else {
+            return;
+        };
+        let next = mentions
+            .iter()
+            .position(|&idx| Some(idx) > self.selected_message)
+            .unwrap_or(0);
+        let idx = mentions[next];
+        self.selected_message = Some(idx);
+        self.chat_jump_to = Some(idx);
+        if mentions.len() > 1 {
+            self.set_status(format!("Mention {} of {}", next + 1, mentions.len()));
+        }
+    }
+
+    /// Open the selected reference: URLs in the browser, files in the editor
+    fn open_reference(&mut self) {
+        let selected = self.side_selected;
+        let Some(link) = self.references().get(selected).map(|r| r.link.clone()) else {
+            return;
+        };
+        match link {
+            Link::Url(url) => match crate::opener::open(&url) {
+                Ok(()) => self.set_status(format!("Opened {}", url)),
+                Err(e) => self.set_status(format!("Open failed: {}", e)),
+            },
+            Link::File(file_ref) => {
+                let cwd = self.selected().and_then(|s| s.cwd.as_deref());
+                let path = file_ref.resolve(cwd);
+                self.pending_external = Some(ExternalAction::OpenFile(path, file_ref.line));
+            }
+        }
+    }
+
+    /// Open the commit the selected message reports creating
+    fn show_selected_commit(&mut self) {
+        let Some(session) = self.selected() else {
+            return;
+        };
+        let Some(hash) = self
+            .selected_message
+            .and_then(|idx| message::created_commit_at(&session.messages, idx))
+            .map(str::to_string)
+        else {
+            self.set_status("No commit in the selected message (J/K to select)");
+            return;
+        };
+        let Some(cwd) = session.cwd.clone() else {
+            self.set_status("Session has no working directory recorded");
+            return;
+        };
+        self.git.request_show(&cwd, &hash);
+        self.popup = Some(Popup::CommitDiff { hash, scroll: 0 });
+    }
+
+    /// `git show` output for `Popup::CommitDiff`, once it came back
+    pub fn commit_diff(&self, hash: &str) -> Option<&str> {
+        self.git.shown(self.selected()?.cwd.as_deref()?, hash)?
+    }
+
+    fn handle_commit_diff_key(&mut self, key: event::KeyEvent, hash: String, scroll: usize) {
+        let last = self
+            .commit_diff(&hash)
+            .map_or(0, |d| d.lines().count().saturating_sub(1));
+        let page = self.chat_page_height();
+        let scroll = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Char('y') => {
+                match crate::clipboard::copy(&hash) {
+                    Ok(()) => self.set_status(format!("Copied {}", hash)),
+                    Err(e) => self.set_status(format!("Copy failed: {}", e)),
+                }
+                scroll
+            }
+            KeyCode::Char('j') | KeyCode::Down => scroll + 1,
+            KeyCode::Char('k') | KeyCode::Up => scroll.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') => scroll + page,
+            KeyCode::PageUp => scroll.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => 0,
+            KeyCode::Char('G') | KeyCode::End => last,
+            _ => scroll,
+        };
+        self.popup = Some(Popup::CommitDiff {
+            hash,
+            scroll: scroll.min(last),
+        });
+    }
+
+    fn handle_saved_filters_key(&mut self, key: event::KeyEvent, selected: usize) {
+        let len = self.config.saved_filters.len();
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('F') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Enter => {
+                self.popup = None;
+                if let Some(saved) = self.config.saved_filters.get(selected) {
+                    let filter = saved.filter.clone();
+                    let status = format!("Filter: {}", saved.name);
+                    if self.state.push_filter_history(&filter) {
+                        self.save_state(&status);
+                    } else {
+                        self.set_status(status);
+                    }
+                    self.filter_text = Some(filter);
+                    self.update_sort();
+                }
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => (selected + 1).min(len.saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::SavedFilters { selected });
+    }
+
+    fn handle_workspaces_key(&mut self, key: event::KeyEvent, selected: usize) {
+        // The default directory comes first
+        let len = self.config.workspaces.len() + 1;
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Enter => {
+                self.popup = None;
+                let name = selected
+                    .checked_sub(1)
+                    .and_then(|i| self.config.workspaces.get(i))
+                    .map(|w| w.name.clone());
+                self.switch_workspace(name);
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => (selected + 1).min(len - 1),
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::Workspaces { selected });
+    }
+
+    /// Watch workspace `name` (or the default directory for None) instead:
+    /// discover its sessions and restart the watcher on its directories
+    fn switch_workspace(&mut self, name: Option<String>) {
+        let base_paths = match name {
+            Some(ref name) => match self.config.workspaces.iter().find(|w| w.name == *name) {
+                Some(workspace) => workspace.dirs(),
+                None => return,
+            },
+            None => vec![self.base_path.clone()],
+        };
+        let mut sessions = match session::discover_all(&base_paths, &self.config.ignore_projects) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                self.set_status(format!("Workspace not loaded: {:#}", e));
+                return;
+            }
+        };
+        self.slug_names.request(sessions.values());
+        sessions.values_mut().for_each(|s| {
+            self.state.apply(s);
+            s.apply_aliases(&self.config.project_aliases, &self.slug_names);
+        });
+        // Drop the watcher first so a native one's watches are released
+        self.watcher = None;
+        let (watcher, warning, watch_limit) = start_watcher(
+            &base_paths,
+            &self.config.ignore_projects,
+            self.backend,
+            self.poll_interval,
+        );
+        self.watcher = watcher;
+        self.watch_limit_reached = watch_limit;
+        self.sessions = sessions;
+        self.base_paths = base_paths;
+        self.workspace = name;
+        self.paused = None;
+        self.replay = None;
+        self.comparison = None;
+        // Starts over at the top of the new list
+        self.switch_session(None);
+        self.update_sort();
+        let label = self.workspace.as_deref().unwrap_or("default").to_string();
+        self.set_status(warning.unwrap_or(format!(
+            "Workspace: {} ({} sessions)",
+            label,
+            self.sessions.len()
+        )));
+    }
+
+    /// Shown columns in their order, then the rest of the built-in and
+    /// script columns, each with whether it is shown
+    pub fn column_choices(&self) -> Vec<(SessionColumn, bool)> {
+        let scripts = self
+            .config
+            .script_columns
+            .iter()
+            .map(|c| SessionColumn::Script(c.name.clone()));
+        let hidden = SessionColumn::BUILT_IN
+            .into_iter()
+            .chain(scripts)
+            .filter(|c| !self.session_columns.contains(c))
+            .map(|c| (c, false));
+        self.session_columns
+            .iter()
+            .map(|c| (c.clone(), true))
+            .chain(hidden)
+            .collect()
+    }
+
+    fn handle_columns_key(&mut self, key: event::KeyEvent, selected: usize) {
+        let choices = self.column_choices();
+        let Some((column, shown)) = choices.get(selected).cloned() else {
+            self.popup = None;
+            return;
+        };
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('l') => {
+                self.popup = None;
+                self.save_ui_state();
+                return;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if shown {
+                    self.session_columns.retain(|c| *c != column);
+                } else {
+                    self.session_columns.push(column.clone());
+                }
+                // Keep the cursor on the column wherever it moved to
+                self.column_choices()
+                    .iter()
+                    .position(|(c, _)| *c == column)
+                    .unwrap_or(selected)
+            }
+            // Shown columns move within the shown ones
+            KeyCode::Char('K') if shown && selected > 0 => {
+                self.session_columns.swap(selected, selected - 1);
+                selected - 1
+            }
+            KeyCode::Char('J') if shown && selected + 1 < self.session_columns.len() => {
+                self.session_columns.swap(selected, selected + 1);
+                selected + 1
+            }
+            KeyCode::Char('j') | KeyCode::Down => (selected + 1).min(choices.len() - 1),
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::Columns { selected });
+    }
+
+    fn handle_heatmap_key(&mut self, key: event::KeyEvent, metric: HeatMetric) {
+        let metric = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Char('m') | KeyCode::Tab => metric.toggle(),
+            _ => metric,
+        };
+        self.popup = Some(Popup::Heatmap { metric });
+    }
+
+    fn handle_message_classes_key(&mut self, key: event::KeyEvent, selected: usize) {
+        let len = self.config.message_classes.len();
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('C') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(class) = self.config.message_classes.get(selected)
+                    && !self.hidden_classes.remove(&class.name)
+                {
+                    self.hidden_classes.insert(class.name.clone());
+                }
+                selected
+            }
+            KeyCode::Char('j') | KeyCode::Down => (selected + 1).min(len.saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::MessageClasses { selected });
+    }
+
+    fn handle_response_times_key(
+        &mut self,
+        key: event::KeyEvent,
+        window: ChartWindow,
+        breakdown: Breakdown,
+    ) {
+        let (window, breakdown) = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('L') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Tab => (window.next(), breakdown),
+            KeyCode::Char('b') => (window, breakdown.next()),
+            _ => (window, breakdown),
+        };
+        self.popup = Some(Popup::ResponseTimes { window, breakdown });
+    }
+
+    fn handle_compare_key(&mut self, key: event::KeyEvent, row: usize) {
+        let Some(ref comparison) = self.comparison else {
+            self.popup = None;
+            return;
+        };
+        let last = comparison.rows.len().saturating_sub(1);
+        let row = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('D') => {
+                self.popup = None;
+                self.comparison = None;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => (row + 1).min(last),
+            KeyCode::Char('k') | KeyCode::Up => row.saturating_sub(1),
+            KeyCode::PageDown => (row + 10).min(last),
+            KeyCode::PageUp => row.saturating_sub(10),
+            KeyCode::Char('g') | KeyCode::Home => 0,
+            KeyCode::Char('G') | KeyCode::End => last,
+            // Next/previous turn where the sessions went different ways
+            KeyCode::Char('n') => comparison.rows[(row + 1).min(comparison.rows.len())..]
+                .iter()
+                .position(|r| r.differs())
+                .map_or(row, |i| row + 1 + i),
+            KeyCode::Char('N') => comparison.rows[..row]
+                .iter()
+                .rposition(|r| r.differs())
+                .unwrap_or(row),
+            _ => row,
+        };
+        self.popup = Some(Popup::Compare { row });
+    }
+
+    fn handle_parse_errors_key(&mut self, key: event::KeyEvent, selected: usize) {
+        let len = self
+            .selected()
+            .map_or(0, |s| s.parse_diagnostics.recent.len());
+        let selected = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('E') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => (selected + 1).min(len.saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => selected,
+        };
+        self.popup = Some(Popup::ParseErrors { selected });
+    }
+
+    /// Keys of the views other than the monitor: each view's own, then
+    /// quitting and Esc back to the monitor
+    fn handle_view_key(&mut self, key: event::KeyEvent) {
+        let handled = match self.view {
+            View::Monitor => false,
+            View::Dashboard => self.handle_dashboard_key(key),
+            View::Stats => self.handle_stats_key(key),
+            View::Projects => self.handle_projects_key(key),
+            View::Help => self.handle_help_key(key),
+        };
+        if handled {
+            return;
+        }
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.should_quit = true;
+            }
+            KeyCode::Esc => self.view = View::Monitor,
+            _ => {}
+        }
+    }
+
+    /// Sessions active today in the display zone, most recent first, as
+    /// the dashboard lists them
+    pub fn todays_sessions(&self) -> Vec<&Session> {
+        let today = self.zone.convert(Utc::now()).date_naive();
+        let mut sessions: Vec<&Session> = self
+            .sessions
+            .values()
+            .filter(|s| self.zone.convert(s.last_activity).date_naive() == today)
+            .collect();
+        sessions.sort_by(|a, b| newest_first(a, b));
+        sessions
+    }
+
+    fn handle_dashboard_key(&mut self, key: event::KeyEvent) -> bool {
+        let len = self.todays_sessions().len();
+        let selected = &mut self.views.dashboard_selected;
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                *selected = (*selected + 1).min(len.saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => *selected = selected.saturating_sub(1),
+            KeyCode::Char('m') => self.views.heat_metric = self.views.heat_metric.toggle(),
+            KeyCode::Enter => {
+                let id = self
+                    .todays_sessions()
+                    .get(self.views.dashboard_selected)
+                    .map(|s| s.id.clone());
+                if let Some(id) = id {
+                    self.show_in_monitor(&id);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Back to the monitor with session `id` selected and open, clearing
+    /// the filters if they hide it
+    fn show_in_monitor(&mut self, id: &str) {
+        if !self.sorted_session_ids.iter().any(|s| s == id) {
+            self.filter_text = None;
+            self.show_active_only = false;
+            self.update_sort();
+        }
+        if let Some(idx) = self.sorted_session_ids.iter().position(|s| s == id) {
+            self.select_index(idx);
+            self.select_current();
+        }
+        self.view = View::Monitor;
+    }
+
+    fn handle_stats_key(&mut self, key: event::KeyEvent) -> bool {
+        let views = &mut self.views;
+        match key.code {
+            KeyCode::Tab => {
+                views.stats_period = views.stats_period.toggle();
+                views.stats_selected = 0;
+            }
+            KeyCode::Char('b') => {
+                views.stats_breakdown = views.stats_breakdown.next();
+                views.stats_selected = 0;
+            }
+            KeyCode::Char('j') | KeyCode::Down => views.stats_selected += 1,
+            KeyCode::Char('k') | KeyCode::Up => {
+                views.stats_selected = views.stats_selected.saturating_sub(1)
+            }
+            _ => return false,
+        }
+        let len = report::billing_rows(
+            &self.sessions,
+            &self.prices,
+            &self.zone,
+            self.views.stats_period,
+            self.views.stats_breakdown,
+        )
+        .len();
+        self.views.stats_selected = self.views.stats_selected.min(len.saturating_sub(1));
+        true
+    }
+
+    fn handle_projects_key(&mut self, key: event::KeyEvent) -> bool {
+        let since = self.views.projects_window.since(Utc::now());
+        if let Some(project) = self.views.project_open.clone() {
+            return self.handle_project_sessions_key(key, &project, since);
+        }
+        let projects = report::project_summaries(&self.sessions, &self.prices, since);
+        let selected = projects.get(self.views.projects_selected);
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.views.projects_selected =
+                    (self.views.projects_selected + 1).min(projects.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.views.projects_selected = self.views.projects_selected.saturating_sub(1)
+            }
+            KeyCode::Tab => {
+                self.views.projects_window = self.views.projects_window.next();
+                self.views.projects_selected = 0;
+            }
+            KeyCode::Enter => {
+                if let Some(project) = selected {
+                    self.views.project_open = Some(project.name.clone());
+                    self.views.project_session_selected = 0;
+                }
+            }
+            // The monitor, filtered to the project's sessions
+            KeyCode::Char('f') => {
+                if let Some(project) = selected {
+                    self.filter_text = Some(format!("repo:{}", project.name));
+                    self.update_sort();
+                    self.view = View::Monitor;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Keys of the leaderboard drilled down into `project`'s sessions
+    fn handle_project_sessions_key(
+        &mut self,
+        key: event::KeyEvent,
+        project: &str,
+        since: Option<DateTime<Utc>>,
+    ) -> bool {
+        let sessions = report::project_sessions(&self.sessions, &self.prices, project, since);
+        let selected = &mut self.views.project_session_selected;
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                *selected = (*selected + 1).min(sessions.len().saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => *selected = selected.saturating_sub(1),
+            KeyCode::Tab => {
+                self.views.projects_window = self.views.projects_window.next();
+                *selected = 0;
+            }
+            KeyCode::Enter => {
+                if let Some((id, _)) = sessions.get(*selected) {
+                    self.show_in_monitor(id);
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') => {
+                self.views.project_open = None;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_help_key(&mut self, key: event::KeyEvent) -> bool {
+        let scroll = self.views.help_scroll;
+        // Inside the borders
+        let page = self.view_area.height.saturating_sub(2).max(1) as usize;
+        let scroll = match key.code {
+            KeyCode::Char('j') | KeyCode::Down => scroll + 1,
+            KeyCode::Char('k') | KeyCode::Up => scroll.saturating_sub(1),
+            KeyCode::PageDown => scroll + page,
+            KeyCode::PageUp => scroll.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => 0,
+            _ => return false,
+        };
+        self.views.help_scroll = scroll.min(self.views.help_max_scroll);
+        true
+    }
+
+    /// Indices of starred messages in the selected session, in transcript order
+    pub fn starred_message_indices(&self) -> Vec<usize> {
+        let Some(session) = self.selected() else {
+            return Vec::new();
+        };
+        session
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| self.state.is_starred(&session.id, &m.key()))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Move the message cursor by `delta` rendered messages. Without a cursor,
+    /// start from the first message visible at the top of the chat viewport.
+    fn move_message_cursor(&mut self, delta: i32) {
+        let lines = &self.chat_message_lines;
+        if lines.is_empty() {
+            return;
+        }
+        let pos = match self
+            .selected_message
+            .and_then(|m| lines.iter().position(|(idx, _)| *idx == m))
+        {
+            Some(pos) => (pos as i64 + delta as i64).clamp(0, lines.len() as i64 - 1) as usize,
+            None => lines
+                .iter()
+                .position(|(_, line)| *line >= self.chat_scroll)
+                .unwrap_or(lines.len() - 1),
+        };
+        let idx = lines[pos].0;
+        self.selected_message = Some(idx);
+        self.chat_jump_to = Some(idx);
+    }
+
+    /// Leave the one-line chat style for the full stream, starting at the
+    /// selected message, or else the one at the top of the view
+    fn expand_oneline(&mut self) {
+        let idx = self.selected_message.or_else(|| {
+            self.chat_message_lines
+                .iter()
+                .find(|(_, line)| *line >= self.chat_scroll)
+                .map(|(idx, _)| *idx)
+        });
+        self.chat_style = ChatStyle::Stream;
+        if let Some(idx) = idx {
+            self.chat_anchor = Some((idx, 0));
+            self.chat_scroll_locked_to_bottom = false;
+        }
+    }
+
+    /// `time` as the TUI shows it: in the configured zone, on the chosen
+    /// clock, dated unless it is today
+    pub fn clock(&self, time: DateTime<Utc>) -> String {
+        self.zone.clock(time, self.time_format)
+    }
+
+    /// The session list as drawn: the sessions in order, with a heading
+    /// wherever the period of their last activity changes
+    pub fn list_rows(&self) -> Vec<ListRow> {
+        let mut rows = Vec::with_capacity(self.sorted_session_ids.len());
+        let today = self.zone.convert(Utc::now()).date_naive();
+        let mut heading: Option<String> = None;
+        for (idx, id) in self.sorted_session_ids.iter().enumerate() {
+            if self.config.date_separators
+                && let Some(session) = self.sessions.get(id)
+            {
+                let day = self.zone.convert(session.last_activity).date_naive();
+                let current = timezone::day_heading(day, today);
+                if heading.as_ref() != Some(&current) {
+                    rows.push(ListRow::Heading(current.clone()));
+                    heading = Some(current);
+                }
+            }
+            rows.push(ListRow::Session(idx));
+        }
+        rows
+    }
+
+    /// Scroll the chat so the `n`th prompt below (or above) the top of the
+    /// view starts it
+    fn jump_to_prompt(&mut self, forward: bool, n: usize) {
+        let Some(session) = self.selected() else {
+            return;
+        };
+        let top = self.chat_scroll;
+        let prompts = self
+            .chat_message_lines
+            .iter()
+            .filter(|(idx, _)| session.messages.get(*idx).is_some_and(|m| m.is_prompt()))
+            .map(|&(_, line)| line);
+        // With fewer than `n` left, the farthest one
+        let target = if forward {
+            let below: Vec<usize> = prompts.filter(|&line| line > top).collect();
+            below.get(n - 1).or(below.last()).copied()
+        } else {
+            let above: Vec<usize> = prompts.filter(|&line| line < top).collect();
+            let i = above.len().saturating_sub(n);
+            above.get(i).copied()
+        };
+        match target {
+            Some(line) => {
+                self.chat_scroll = line;
+                self.chat_scroll_locked_to_bottom = false;
+            }
+            None if forward => self.set_status("No later prompt"),
+            None => self.set_status("No earlier prompt"),
+        }
+    }
+
+    /// Move the message cursor to the next message containing the chat
+    /// search text, wrapping around to the top
+    fn jump_to_next_match(&mut self) {
+        let Some(needle) = self.chat_search.clone() else {
+            return;
+        };
+        let Some(session) = self.selected() else {
+            return;
+        };
+        let matching: Vec<usize> =
+            self.chat_message_lines
+                .iter()
+                .map(|(idx, _)| *idx)
+                .filter(|&idx| {
+                    session.messages.get(idx).is_some_and(|m| {
+                        message::find_ignore_case(&m.content, &needle, 0).is_some()
+                    })
+                })
+                .collect();
+        let next = matching
+            .iter()
+            .find(|&&idx| self.selected_message.is_none_or(|cur| idx > cur))
+            .or(matching.first())
+            .copied();
+        match next {
+            Some(idx) => {
+                self.selected_message = Some(idx);
+                self.chat_jump_to = Some(idx);
+                self.focus = FocusPanel::Chat;
+            }
+            None => self.set_status(format!("No matches for \"{}\"", needle)),
+        }
+    }
+
+    /// Freeze the selected session's chat at its current length, or resume
+    fn toggle_pause(&mut self) {
+        if self.paused.take().is_some() {
+            self.chat_scroll_locked_to_bottom = true;
+            self.set_status("Live updates resumed");
+        } else if let Some(session) = self.selected() {
+            self.paused = Some((session.id.clone(), session.messages.len()));
+            self.set_status("Paused: new messages are held back (p to resume)");
+        }
+    }
+
+    fn toggle_truncation(&mut self) {
+        self.truncate_messages = !self.truncate_messages;
+        if self.truncate_messages {
+            self.set_status(format!(
+                "Long messages cut at {} lines",
+                self.config.max_message_lines
+            ));
+        } else {
+            self.set_status("Long messages shown in full");
+        }
+    }
+
+    /// Save what was just drawn for pasting into issues and chats
+    fn write_snapshot(&mut self, buffer: &Buffer, scope: SnapshotScope) {
+        let (area, name) = match scope {
+            SnapshotScope::Chat => {
+                let area = self.chat_area.inner(Margin::new(1, 1));
+                let name = self.selected().map_or("chat", |s| s.short_id()).to_string();
+                (area, name)
+            }
+            SnapshotScope::Frame => (buffer.area, "frame".to_string()),
+        };
+        match snapshot::write(buffer, area, &name, &self.config.snapshot) {
+            Ok(path) if self.config.snapshot.png_command.is_some() => self.set_status(format!(
+                "Snapshot saved to {} (+ .txt, rendering .png)",
+                path.display()
+            )),
+            Ok(path) => self.set_status(format!("Snapshot saved to {} (+ .txt)", path.display())),
+            Err(e) => self.set_status(format!("Snapshot failed: {:#}", e)),
+        }
+    }
+
+    /// Write the chat pane's links as OSC 8 hyperlinks if they differ from
+    /// the last ones. ratatui only redraws changed cells, so links whose
+    /// cells didn't change stay linked on screen.
+    fn write_hyperlinks(&mut self, buffer: &Buffer) {
+        let area = self.chat_area.inner(Margin::new(1, 1));
+        let cwd = self.selected().and_then(|s| s.cwd.clone());
+        let links = hyperlink::find(buffer, area, cwd.as_deref());
+        if links != self.hyperlinks {
+            // Unwritten links just stay plain text
+            hyperlink::write(&links).ok();
+            self.hyperlinks = links;
+        }
+    }
+
+    /// A session name, project or path as shown: blurred in privacy mode
+    pub fn private<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.privacy {
+            Cow::Owned(redact::blur(text))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    fn toggle_privacy(&mut self) {
+        self.privacy = !self.privacy;
+        if self.privacy {
+            self.set_status("Privacy mode on: prompts and paths hidden");
+        } else {
+            self.set_status("Privacy mode off");
+        }
+    }
+
+    /// Whether a message is an assistant reply long enough to fold
+    fn is_foldable(&self, msg: &SessionMessage) -> bool {
+        let max = self.config.fold_lines;
+        matches!(msg.msg_type, MessageType::Assistant | MessageType::ToolUse)
+            && max > 0
+            && msg.content.lines().count() > max
+    }
+
+    /// Whether message `idx` of the selected session renders folded
+    pub fn is_folded(&self, idx: usize, msg: &SessionMessage) -> bool {
+        self.is_foldable(msg)
+            && !self
+                .selected_session
+                .as_ref()
+                .and_then(|id| self.unfolded_messages.get(id))
+                .is_some_and(|set| set.contains(&idx))
+    }
+
+    /// Fold or unfold the message under the cursor
+    fn toggle_fold(&mut self) {
+        let Some(idx) = self.selected_message else {
+            self.set_status("Pick a message with J/K first");
+            return;
+        };
+        let Some(session) = self.selected() else {
+            return;
+        };
+        if !session
+            .messages
+            .get(idx)
+            .is_some_and(|m| self.is_foldable(m))
+        {
+            self.set_status("Message is too short to fold");
+            return;
+        }
+        let id = session.id.clone();
+        let unfolded = self.unfolded_messages.entry(id).or_default();
+        if !unfolded.remove(&idx) {
+            unfolded.insert(idx);
+        }
+        self.chat_jump_to = Some(idx);
+    }
+
+    /// Line limit for a single rendered message, if truncation is on
+    pub fn message_line_limit(&self) -> Option<usize> {
+        let max = self.config.max_message_lines;
+        (self.truncate_messages && max > 0).then_some(max)
+    }
+
+    /// Number of messages to render for a session, honoring a pause
+    pub fn visible_message_count(&self, session: &Session) -> usize {
+        if let Some(ref replay) = self.replay
+            && replay.session_id == session.id
+        {
+            return replay.position.min(session.messages.len());
+        }
+        match self.paused {
+            Some((ref id, count)) if *id == session.id => count.min(session.messages.len()),
+            _ => session.messages.len(),
+        }
+    }
+
+    /// Backfill every message of session `id` not loaded yet
+    fn load_full_transcript(&mut self, id: &str) -> Result<()> {
+        let Some(session) = self.sessions.get_mut(id) else {
+            return Ok(());
+        };
+        let added = match session::backfill(session, usize::MAX) {
+            Ok(added) => added,
+            Err(e) => {
+                self.reload_session(id);
+                return Err(e);
+            }
+        };
+        self.shift_message_indices(id, added);
+        Ok(())
+    }
+
+    /// Start replaying the selected session from its first message, or
+    /// stop the running replay
+    fn toggle_replay(&mut self) {
+        if self.replay.take().is_some() {
+            self.chat_scroll_locked_to_bottom = true;
+            self.set_status("Replay stopped");
+            return;
+        }
+        let Some(id) = self.selected_session.clone() else {
+            return;
+        };
+        // Replay needs the whole transcript, not just the loaded tail
+        if let Err(e) = self.load_full_transcript(&id) {
+            self.set_status(format!("Replay failed: {}", e));
+            return;
+        }
+        self.paused = None;
+        self.selected_message = None;
+        self.chat_jump_to = None;
+        self.chat_anchor = None;
+        self.chat_scroll_locked_to_bottom = true;
+        self.replay = Some(Replay::new(id));
+        self.set_status("Replay: Space pause, +/- speed, ←/→ turns, ,/. seek, v stop");
+    }
+
+    /// Mark the selected session for comparison, or compare it with the
+    /// marked one. Both transcripts are loaded in full first.
+    fn compare_selected(&mut self) {
+        let Some(id) = self.selected_session.clone() else {
+            return;
+        };
+        let base = match self.compare_base.take() {
+            Some(base) if base != id && self.sessions.contains_key(&base) => base,
+            Some(base) if base == id => {
+                self.set_status("Compare cancelled");
+                return;
+            }
+            _ => {
+                self.compare_base = Some(id);
+                self.set_status("Compare: select another session and press D");
+                return;
+            }
+        };
+        for session_id in [&base, &id] {
+            if let Err(e) = self.load_full_transcript(session_id) {
+                self.set_status(format!("Compare failed: {}", e));
+                return;
+            }
+        }
+        let (Some(left), Some(right)) = (self.sessions.get(&base), self.sessions.get(&id)) else {
+            return;
+        };
+        let comparison = Comparison::new(left, right, &self.prices);
+        self.set_status(format!(
+            "Comparing {} with {}",
+            self.private(&left.display_name()),
+            self.private(&right.display_name())
+        ));
+        self.comparison = Some(comparison);
+        self.popup = Some(Popup::Compare { row: 0 });
+    }
+
+    /// Playback controls, while the selected session is being replayed
+    fn handle_replay_key(&mut self, key: event::KeyEvent) -> bool {
+        let Some(ref mut replay) = self.replay else {
+            return false;
+        };
+        if self.selected_session.as_deref() != Some(replay.session_id.as_str()) {
+            return false;
+        }
+        let Some(session) = self.sessions.get(&replay.session_id) else {
+            return false;
+        };
+        let messages = &session.messages;
+        match key.code {
+            KeyCode::Char(' ') | KeyCode::Char('p') => replay.toggle_playing(messages.len()),
+            KeyCode::Char('+') | KeyCode::Char('=') => replay.speed = replay.speed.faster(),
+            KeyCode::Char('-') => replay.speed = replay.speed.slower(),
+            KeyCode::Right => replay.next_turn(messages),
+            KeyCode::Left => replay.previous_turn(messages),
+            KeyCode::Char('.') => replay.step(true, messages.len()),
+            KeyCode::Char(',') => replay.step(false, messages.len()),
+            KeyCode::Esc => {
+                self.toggle_replay();
+                return true;
+            }
+            _ => return false,
+        }
+        self.chat_scroll_locked_to_bottom = true;
+        true
+    }
+
+    fn copy_selected_message(&mut self) {
+        let Some(content) = self
+            .selected_message
+            .and_then(|idx| self.selected()?.messages.get(idx))
+            .map(|m| self.redactor.redact(&m.content).into_owned())
+        else {
+            self.set_status("No message selected (J/K or click to select)");
+            return;
+        };
+        match crate::clipboard::copy(&content) {
+            Ok(()) => self.set_status(format!("Copied {} chars", content.chars().count())),
+            Err(e) => self.set_status(format!("Copy failed: {}", e)),
+        }
+    }
+
+    /// Release the mouse so the terminal's own selection works, or take it back
+    fn toggle_mouse_capture(&mut self) {
+        self.mouse_capture = !self.mouse_capture;
+        let result = if self.mouse_capture {
+            crossterm::execute!(std::io::stdout(), crossterm::event::EnableMouseCapture)
+        } else {
+            crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture)
+        };
+        match result {
+            Ok(()) if self.mouse_capture => self.set_status("Mouse capture on"),
+            Ok(()) => self.set_status("Mouse capture off: select text natively, m to restore"),
+            Err(e) => self.set_status(format!("Failed to toggle mouse capture: {}", e)),
+        }
+    }
+
+    fn toggle_star(&mut self) {
+        let Some(idx) = self.selected_message else {
+            self.set_status("No message selected (J/K to select)");
+            return;
+        };
+        let Some(session) = self.selected() else {
+            return;
+        };
+        let Some(msg) = session.messages.get(idx) else {
+            return;
+        };
+        let (session_id, key) = (session.id.clone(), msg.key());
+        let starred = self.state.toggle_star(&session_id, &key);
+        self.save_state(if starred { "Starred" } else { "Unstarred" });
+    }
+
+    fn handle_largest_sessions_key(
+        &mut self,
+        key: event::KeyEvent,
+        metric: SizeMetric,
+        selected: usize,
+        confirm_delete: Option<String>,
+    ) {
+        let ids = report::largest_session_ids(&self.sessions, metric, LARGEST_SESSIONS_TOP);
+
+        // A pending delete consumes the next key: 'y' confirms, anything else cancels
+        if let Some(id) = confirm_delete {
+            if key.code == KeyCode::Char('y') {
+                self.delete_session(&id);
+            }
+            self.popup = Some(Popup::LargestSessions {
+                metric,
+                selected,
+                confirm_delete: None,
+            });
+            return;
+        }
+
+        let current = ids.get(selected).cloned();
+        let mut selected = selected;
+        let mut metric = metric;
+        let mut confirm_delete = None;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('U') => {
+                self.popup = None;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                selected = (selected + 1).min(ids.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Tab => {
+                metric = metric.toggle();
+                selected = 0;
+            }
+            KeyCode::Enter => {
+                if let Some(id) = current {
+                    self.popup = None;
+                    self.jump_to_session(&id);
+                }
+                return;
+            }
+            KeyCode::Char('A') => {
+                if let Some(id) = current {
+                    self.archive_session(&id);
+                }
+            }
+            KeyCode::Char('X') => confirm_delete = current,
+            _ => {}
+        }
+        let len = report::largest_session_ids(&self.sessions, metric, LARGEST_SESSIONS_TOP).len();
+        self.popup = Some(Popup::LargestSessions {
+            metric,
+            selected: selected.min(len.saturating_sub(1)),
+            confirm_delete,
+        });
+    }
+
+    /// Select a session in the list, clearing filters that would hide it
+    fn jump_to_session(&mut self, id: &str) {
+        self.switch_session(Some(id.to_string()));
+        if !self.sorted_session_ids.iter().any(|s| s == id) {
+            self.filter_text = None;
+            self.show_active_only = false;
+        }
+        self.update_sort();
+    }
+
+    fn archive_session(&mut self, id: &str) {
+        let Some(session) = self.sessions.get(id) else {
+            return;
+        };
+        let Some(archive_root) = session::archive_dir() else {
+            self.set_status("Archive failed: no data directory");
+            return;
+        };
+        match session::archive_session(session, &archive_root) {
+            Ok(dest) => {
+                let name = self.private(&session.display_name()).into_owned();
+                let dest = self.private(&dest.display().to_string()).into_owned();
+                self.forget_session(id);
+                self.update_sort();
+                self.set_status(format!("Archived {} to {}", name, dest));
+            }
+            Err(e) => self.set_status(format!("Archive failed: {}", e)),
+        }
+    }
+
+    fn delete_session(&mut self, id: &str) {
+        let Some(session) = self.sessions.get(id) else {
+            return;
+        };
+        match session::delete_session(session) {
+            Ok(()) => {
+                let name = self.private(&session.display_name()).into_owned();
+                self.forget_session(id);
+                self.update_sort();
+                self.set_status(format!("Deleted {}", name));
+            }
+            Err(e) => self.set_status(format!("Delete failed: {}", e)),
+        }
+    }
+
+    fn handle_filter_key(&mut self, key: event::KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.filter_mode = false;
+                self.filter_text = None;
+                self.update_sort();
+            }
+            KeyCode::Enter => {
+                self.filter_mode = false;
+                if let Some(filter) = self.filter_text.clone()
+                    && self.state.push_filter_history(&filter)
+                    && let Err(e) = self.state.save()
+                {
+                    self.set_status(format!("Failed to save state: {}", e));
+                }
+                self.update_sort();
+            }
+            KeyCode::Up | KeyCode::Down => {
+                let len = self.state.filter_history.len();
+                if len == 0 {
+                    return;
+                }
+                // Up walks back through older entries; Down returns toward the
+                // newest and finally to an empty filter
+                let pos = match (key.code, self.filter_history_pos) {
+                    (KeyCode::Up, None) => Some(0),
+                    (KeyCode::Up, Some(p)) => Some((p + 1).min(len - 1)),
+                    (_, Some(0)) | (_, None) => None,
+                    (_, Some(p)) => Some(p - 1),
+                };
+                self.filter_history_pos = pos;
+                self.filter_text = pos.map(|p| self.state.filter_history[p].clone());
+                self.update_sort();
+            }
+            KeyCode::Backspace => {
+                if let Some(ref mut text) = self.filter_text {
+                    text.pop();
+                    if text.is_empty() {
+                        self.filter_text = None;
+                    }
+                }
+                self.update_sort();
+            }
+            KeyCode::Char(c) => {
+                if let Some(ref mut text) = self.filter_text {
+                    text.push(c);
+                } else {
+                    self.filter_text = Some(c.to_string());
+                }
+                self.update_sort();
+            }
+            _ => {}
+        }
+    }
+
+    /// Jump to the item at `idx` (0-based) in the focused pane
+    fn goto_item(&mut self, idx: usize) {
+        match self.focus {
+            FocusPanel::Sessions => self.select_index(idx),
+            FocusPanel::Chat => {
+                if idx == 0 && self.selected_message.is_none() {
+                    self.chat_scroll = 0;
+                    self.chat_scroll_locked_to_bottom = false;
+                } else if let Some(&(msg, _)) = self
+                    .chat_message_lines
+                    .get(idx.min(self.chat_message_lines.len().saturating_sub(1)))
+                {
+                    self.selected_message = Some(msg);
+                    self.chat_jump_to = Some(msg);
+                }
+            }
+            FocusPanel::Side => {
+                self.side_selected = idx.min(self.side_panel_len().saturating_sub(1));
+                self.follow_side_selection();
+            }
+        }
+    }
+
+    /// Jump to the last item of the focused pane
+    fn goto_last(&mut self) {
+        match self.focus {
+            FocusPanel::Sessions => {
+                self.goto_item(self.sorted_session_ids.len().saturating_sub(1));
+            }
+            FocusPanel::Chat => {
+                self.selected_message = None;
+                self.chat_scroll_locked_to_bottom = true;
+            }
+            FocusPanel::Side => {
+                self.side_selected = self.side_panel_len().saturating_sub(1);
+                self.follow_side_selection();
+            }
+        }
+    }
+
+    /// Select the session at list position `idx` (clamped to the list)
+    fn select_index(&mut self, idx: usize) {
+        let current = self.list_state.selected().unwrap_or(0) as i32;
+        self.move_selection(idx as i32 - current);
     }
 
     fn move_selection(&mut self, delta: i32) {
@@ -298,18 +3067,124 @@ impl App {
             current.saturating_sub((-delta) as usize)
         };
         self.list_state.select(Some(new_idx));
-        self.selected_session = self.sorted_session_ids.get(new_idx).cloned();
-        self.chat_scroll_locked_to_bottom = true;
+        self.switch_session(self.sorted_session_ids.get(new_idx).cloned());
     }
 
     fn select_current(&mut self) {
         if let Some(idx) = self.list_state.selected() {
-            self.selected_session = self.sorted_session_ids.get(idx).cloned();
-            self.chat_scroll_locked_to_bottom = true;
+            self.switch_session(self.sorted_session_ids.get(idx).cloned());
             self.focus = FocusPanel::Chat;
         }
     }
 
+    /// Load earlier messages of the selected session once the chat view
+    /// nears the top of what is loaded (or doesn't fill the pane)
+    fn backfill_selected(&mut self) {
+        let page = self.chat_page_height();
+        let near_top = self.chat_total_lines <= page
+            || (!self.chat_scroll_locked_to_bottom && self.chat_scroll < page);
+        let Some(id) = self.selected_session.clone() else {
+            return;
+        };
+        let Some(session) = self.sessions.get_mut(&id) else {
+            return;
+        };
+        if !near_top || !session.is_partial() {
+            return;
+        }
+        let added = match session::backfill(session, session::TAIL_MESSAGES) {
+            Ok(added) => added,
+            Err(e) => {
+                tracing::warn!(session = %id, error = format!("{:#}", e), "failed to backfill messages");
+                self.reload_session(&id);
+                return;
+            }
+        };
+        if added == 0 {
+            return;
+        }
+        if !self.chat_scroll_locked_to_bottom {
+            self.chat_anchor = self.chat_top_message();
+        }
+        self.shift_message_indices(&id, added);
+    }
+
+    /// Move everything that points at a message of session `id` by `n`,
+    /// after `n` earlier messages were loaded in front
+    fn shift_message_indices(&mut self, id: &str, n: usize) {
+        if self.selected_session.as_deref() == Some(id) {
+            self.selected_message = self.selected_message.map(|m| m + n);
+            self.chat_jump_to = self.chat_jump_to.map(|m| m + n);
+            self.chat_anchor = self.chat_anchor.map(|(idx, offset)| (idx + n, offset));
+        }
+        if let Some((ref paused_id, ref mut count)) = self.paused
+            && paused_id == id
+        {
+            *count += n;
+        }
+        if let Some(unfolded) = self.unfolded_messages.get_mut(id) {
+            *unfolded = unfolded.iter().map(|idx| idx + n).collect();
+        }
+        if let Some(ref mut replay) = self.replay
+            && replay.session_id == id
+        {
+            replay.position += n;
+        }
+    }
+
+    /// Read session `id` again from scratch, once what is loaded no longer
+    /// matches its file; positions in its messages are dropped
+    fn reload_session(&mut self, id: &str) {
+        let Some(path) = self.sessions.get(id).map(|s| s.file_path.clone()) else {
+            return;
+        };
+        match session::discover_single_session(&path) {
+            Ok(Some(mut session)) => {
+                self.state.apply(&mut session);
+                self.slug_names.request([&session]);
+                session.apply_aliases(&self.config.project_aliases, &self.slug_names);
+                self.sessions.insert(id.to_string(), session);
+            }
+            result => {
+                if let Err(e) = result {
+                    tracing::warn!(session = %id, error = %e, "failed to reload session");
+                }
+                // Keep what is loaded, and stop trying for the rest
+                if let Some(session) = self.sessions.get_mut(id) {
+                    session.backfill_offset = 0;
+                    session.unloaded_messages = 0;
+                }
+            }
+        }
+        if self.selected_session.as_deref() == Some(id) {
+            self.selected_message = None;
+            self.chat_jump_to = None;
+            self.chat_anchor = None;
+        }
+        if self
+            .paused
+            .as_ref()
+            .is_some_and(|(paused_id, _)| paused_id == id)
+        {
+            self.paused = None;
+        }
+        self.unfolded_messages.remove(id);
+        if self.replay.as_ref().is_some_and(|r| r.session_id == id) {
+            self.replay = None;
+        }
+    }
+
+    /// Rows visible inside the chat pane borders (at least 2, so a half
+    /// page is never zero)
+    fn chat_page_height(&self) -> usize {
+        self.chat_area.height.saturating_sub(2).max(2) as usize
+    }
+
+    /// Whether the chat view, as last drawn, reaches (almost) to the end
+    fn chat_at_bottom(&self) -> bool {
+        self.chat_scroll + self.chat_page_height() + NEAR_BOTTOM_LINES >= self.chat_total_lines
+    }
+
     fn scroll_chat_down(&mut self, amount: usize) {
         self.chat_scroll = self.chat_scroll.saturating_add(amount);
         self.chat_scroll_locked_to_bottom = false;
@@ -323,14 +3198,45 @@ impl App {
     pub fn handle_mouse_event(&mut self, mouse: event::MouseEvent) {
         let x = mouse.column;
         let y = mouse.row;
+        // A popup covers the panels and takes keys only, so clicks and
+        // scrolls must not reach the hit areas drawn beneath it
+        if self.popup.is_some() {
+            return;
+        }
+        // The hit areas are the monitor's
+        if self.view != View::Monitor {
+            return;
+        }
+
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left)
+            && let Some(action) = self
+                .info_regions
+                .iter()
+                .find(|(rect, _)| self.rect_contains(*rect, x, y))
+                .map(|(_, action)| action.clone())
+        {
+            self.run_info_action(action);
+            return;
+        }
 
         match mouse.kind {
             MouseEventKind::Down(_) => {
                 let old_focus = self.focus;
                 if self.rect_contains(self.session_list_area, x, y) {
                     self.focus = FocusPanel::Sessions;
+                    if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                        self.click_session_row(y);
+                    }
                 } else if self.rect_contains(self.chat_area, x, y) {
                     self.focus = FocusPanel::Chat;
+                    if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                        self.click_chat_row(y);
+                    }
+                } else if self.side_panel.is_some() && self.rect_contains(self.side_area, x, y) {
+                    self.focus = FocusPanel::Side;
+                    if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                        self.click_side_row(y);
+                    }
                 }
                 if self.focus != old_focus {
                     Self::drain_events();
@@ -339,7 +3245,7 @@ impl App {
             MouseEventKind::ScrollDown => {
                 // Only scroll if this panel is focused (prevents scroll leaking)
                 if self.rect_contains(self.chat_area, x, y) && self.focus == FocusPanel::Chat {
-                    self.scroll_chat_down(3);
+                    self.scroll_chat_down(SCROLL_STEP);
                 } else if self.rect_contains(self.session_list_area, x, y)
                     && self.focus == FocusPanel::Sessions
                 {
@@ -348,7 +3254,7 @@ impl App {
             }
             MouseEventKind::ScrollUp => {
                 if self.rect_contains(self.chat_area, x, y) && self.focus == FocusPanel::Chat {
-                    self.scroll_chat_up(3);
+                    self.scroll_chat_up(SCROLL_STEP);
                 } else if self.rect_contains(self.session_list_area, x, y)
                     && self.focus == FocusPanel::Sessions
                 {
@@ -359,6 +3265,87 @@ impl App {
         }
     }
 
+    /// Select the session under a click at screen row `y`; a second click on
+    /// the same row opens it in the chat pane
+    fn click_session_row(&mut self, y: u16) {
+        let area = self.session_list_area;
+        // Rows start below the top border and stop above the bottom one
+        if y <= area.y || y + 1 >= area.y + area.height {
+            return;
+        }
+        // The offset counts rows, headings included
+        let row = self.list_state.offset() + (y - area.y - 1) as usize;
+        let Some(ListRow::Session(idx)) = self.list_rows().get(row).cloned() else {
+            return;
+        };
+        let double = self
+            .last_click
+            .is_some_and(|(at, row)| row == idx && at.elapsed() < DOUBLE_CLICK);
+        self.select_index(idx);
+        if double {
+            self.last_click = None;
+            self.select_current();
+        } else {
+            self.last_click = Some((Instant::now(), idx));
+        }
+    }
+
+    /// Put the message cursor on the message rendered at screen row `y`
+    fn click_chat_row(&mut self, y: u16) {
+        let area = self.chat_area;
+        if y <= area.y || y + 1 >= area.y + area.height {
+            return;
+        }
+        let line = self.chat_scroll + (y - area.y - 1) as usize;
+        if let Some(&(idx, _)) = self
+            .chat_message_lines
+            .iter()
+            .rev()
+            .find(|(_, start)| *start <= line)
+        {
+            self.selected_message = Some(idx);
+        }
+    }
+
+    /// Select the side panel row at screen row `y`; clicking the selected
+    /// row again activates it
+    fn click_side_row(&mut self, y: u16) {
+        let area = self.side_area;
+        if y <= area.y || y + 1 >= area.y + area.height {
+            return;
+        }
+        let row = self.side_offset + (y - area.y - 1) as usize;
+        if row >= self.side_panel_len() {
+            return;
+        }
+        if row == self.side_selected {
+            self.activate_side_row();
+        }
+        self.side_selected = row;
+        self.follow_side_selection();
+    }
+
+    fn run_info_action(&mut self, action: InfoAction) {
+        match action {
+            InfoAction::CopyId(id) => match crate::clipboard::copy(&id) {
+                Ok(()) => self.set_status(format!("Copied {}", id)),
+                Err(e) => self.set_status(format!("Copy failed: {}", e)),
+            },
+            InfoAction::OpenCwd(cwd) => match crate::opener::open(&cwd) {
+                Ok(()) => self.set_status(format!("Opened {}", self.private(&cwd))),
+                Err(e) => self.set_status(format!("Open failed: {}", e)),
+            },
+            InfoAction::FilterBranch(branch) => {
+                self.filter_text = Some(format!("branch:{}", branch));
+                self.update_sort();
+            }
+            InfoAction::FilterRepo(repo) => {
+                self.filter_text = Some(format!("repo:{}", repo));
+                self.update_sort();
+            }
+        }
+    }
+
     fn rect_contains(&self, rect: Rect, x: u16, y: u16) -> bool {
         x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
     }
@@ -371,31 +3358,66 @@ impl App {
     }
 
     fn refresh_all(&mut self) {
-        if let Ok(sessions) = session::discover_sessions(&self.base_path) {
+        if let Ok(mut sessions) =
+            session::discover_all(&self.base_paths, &self.config.ignore_projects)
+        {
+            self.slug_names.request(sessions.values());
+            sessions.values_mut().for_each(|s| {
+                self.state.apply(s);
+                s.apply_aliases(&self.config.project_aliases, &self.slug_names);
+            });
             self.sessions = sessions;
             self.update_sort();
         }
     }
 
+    /// Tick interval for the next loop iteration: the configured rate while
+    /// anything is happening, the idle rate once input and file events have
+    /// been quiet for a while and no session is active
+    fn current_tick_rate(&self) -> Duration {
+        let busy = Duration::from_millis(self.config.tick_rate_ms.max(10));
+        let idle = Duration::from_millis(self.config.idle_tick_rate_ms.max(10));
+        if idle <= busy
+            || self.last_event.elapsed() < IDLE_AFTER
+            || self.replay.as_ref().is_some_and(|r| r.playing)
+        {
+            return busy;
+        }
+        // Counted at the last tick; checking here would stat every
+        // transcript on each loop iteration
+        if self.counters.active > 0 { busy } else { idle }
+    }
+
     pub fn run_event_loop(&mut self, terminal: &mut ratatui::DefaultTerminal) -> Result<()> {
         // Enable mouse capture
         crossterm::execute!(std::io::stdout(), crossterm::event::EnableMouseCapture)?;
 
-        let tick_rate = Duration::from_millis(250);
         let mut last_tick = Instant::now();
 
         loop {
-            terminal.draw(|f| crate::ui::draw(f, self))?;
+            let frame = terminal.draw(|f| crate::ui::draw(f, self))?;
+            if let Some(scope) = self.pending_snapshot.take() {
+                self.write_snapshot(frame.buffer, scope);
+            }
+            if self.config.hyperlinks {
+                self.write_hyperlinks(frame.buffer);
+            }
+            // Checked against what was just drawn for the selected session
+            self.backfill_selected();
+            let tick_rate = self.current_tick_rate();
 
             // Wait for at least one event, then batch-process ALL pending
             // events before next redraw. This ensures focus changes (click/Tab)
             // take effect immediately even with many queued scroll events.
             let timeout = tick_rate.saturating_sub(last_tick.elapsed());
             if event::poll(timeout)? {
+                self.last_event = Instant::now();
                 loop {
                     match event::read()? {
                         Event::Key(key) => self.handle_key_event(key),
                         Event::Mouse(mouse) => self.handle_mouse_event(mouse),
+                        // Everything is drawn again, without the links
+                        Event::Resize(_, _) => self.hyperlinks.clear(),
                         _ => {}
                     }
                     if self.should_quit {
@@ -408,6 +3430,10 @@ impl App {
                 }
             }
 
+            if let Some(action) = self.pending_external.take() {
+                self.run_external(terminal, action);
+            }
+
             if last_tick.elapsed() >= tick_rate {
                 self.tick();
                 last_tick = Instant::now();
@@ -418,20 +3444,268 @@ impl App {
             }
         }
 
+        self.save_ui_state();
         crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture)?;
         Ok(())
     }
 }
 
+/// Start the requested watcher backend, degrading from native events to
+/// polling to none. Returns a warning to show when it had to degrade, and
+/// whether that was because the OS watch limit is exhausted.
+fn start_watcher(
+    base_paths: &[PathBuf],
+    ignore: &IgnoreList,
+    backend: WatcherBackend,
+    poll_interval: Duration,
+) -> (Option<SessionWatcher>, Option<String>, bool) {
+    let start = |interval| SessionWatcher::new(base_paths, ignore, Config::path(), interval);
+    let native_err = match backend {
+        WatcherBackend::Off => return (None, None, false),
+        WatcherBackend::Native => match start(None) {
+            Ok(w) => return (Some(w), None, false),
+            Err(e) => {
+                tracing::warn!(error = %e, "native watcher failed");
+                Some(e)
+            }
+        },
+        WatcherBackend::Poll => None,
+    };
+    let watch_limit = native_err.as_ref().is_some_and(watcher::is_watch_limit);
+    match start(Some(poll_interval)) {
+        Ok(w) => {
+            let warning = native_err.map(|e| {
+                let reason = if watch_limit {
+                    format!("Watch limit reached ({})", watcher::WATCH_LIMIT_FIX)
+                } else {
+                    format!("File events unavailable ({})", e)
+                };
+                format!("{}; polling every {}s", reason, poll_interval.as_secs())
+            });
+            (Some(w), warning, watch_limit)
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "polling watcher failed");
+            let e = native_err.unwrap_or(e);
+            (
+                None,
+                Some(format!("File watcher failed ({}); press r to refresh", e)),
+                watch_limit,
+            )
+        }
+    }
+}
+
+/// Names of the classes configured to start hidden
+fn initially_hidden(classes: &[MessageClass]) -> HashSet<String> {
+    classes
+        .iter()
+        .filter(|c| c.hidden)
+        .map(|c| c.name.clone())
+        .collect()
+}
+
+/// Session fields that decide slug dedup and filter matches
+fn filter_fields(session: &Session) -> impl PartialEq + use<> {
+    (
+        session.slug.clone(),
+        session.custom_title.clone(),
+        session.git_branch.clone(),
+        session.cwd.clone(),
+    )
+}
+
 fn sort_session_ids(sessions: &HashMap<String, Session>) -> Vec<String> {
     let mut ids: Vec<String> = sessions.keys().cloned().collect();
     ids.sort_by(|a, b| {
         let sa = sessions.get(a);
         let sb = sessions.get(b);
         match (sa, sb) {
-            (Some(a), Some(b)) => b.last_activity.cmp(&a.last_activity),
-            _ => std::cmp::Ordering::Equal,
+            (Some(a), Some(b)) => newest_first(a, b),
+            _ => Ordering::Equal,
         }
     });
     ids
 }
+
+/// Latest activity first; sessions active at the same moment go by name,
+/// then id, so they keep their places from one sort to the next
+fn newest_first(a: &Session, b: &Session) -> Ordering {
+    b.last_activity
+        .cmp(&a.last_activity)
+        .then_with(|| a.display_name().cmp(&b.display_name()))
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+/// $VISUAL, else $EDITOR, else vi, split into program and arguments, as
+/// it may carry some (e.g. "code --wait")
+fn editor_command() -> (String, Vec<String>) {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace().map(str::to_string);
+    let program = parts.next().unwrap_or_else(|| "vi".to_string());
+    (program, parts.collect())
+}
+
+/// Open `initial` in the user's editor and return the saved contents. The
+/// file is created fresh with a random name, readable only by the user,
+/// and removed afterwards.
+fn edit_in_editor(session_id: &str, initial: &str) -> Result<String> {
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("claudy-note-{}-", session_id))
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(initial.as_bytes())?;
+    file.flush()?;
+    let (program, args) = editor_command();
+    let status = std::process::Command::new(&program)
+        .args(args)
+        .arg(file.path())
+        .status()?;
+    // Editors may save by replacing the file, so read it by path
+    let text = std::fs::read_to_string(file.path())?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program, status);
+    }
+    Ok(text)
+}
+
+/// Open `path` in the user's editor with the cursor on `line`, in the
+/// syntax the editor understands
+fn open_in_editor(path: &Path, line: u32) -> Result<()> {
+    let (program, args) = editor_command();
+    let name = Path::new(&program)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(&program);
+    let mut command = std::process::Command::new(&program);
+    command.args(args);
+    match name {
+        "code" | "code-insiders" | "cursor" | "codium" => {
+            command
+                .arg("--goto")
+                .arg(format!("{}:{}", path.display(), line))
+        }
+        "subl" | "zed" | "hx" | "helix" => command.arg(format!("{}:{}", path.display(), line)),
+        // vi, vim, nvim, nano, emacs, micro, kak and most others
+        _ => command.arg(format!("+{}", line)).arg(path),
+    };
+    let status = command.status()?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app watching nothing, over one session `s` of `prompts` prompts
+    fn app_with_session(prompts: usize) -> (App, tempfile::TempDir) {
+        let base = tempfile::tempdir().unwrap();
+        let project = base.path().join("-tmp-p");
+        fs::create_dir_all(&project).unwrap();
+        let lines: Vec<String> = (0..prompts).map(session::fixtures::prompt).collect();
+        fs::write(project.join("s.jsonl"), lines.join("\n") + "\n").unwrap();
+        let app = App::new(
+            base.path().to_path_buf(),
+            None,
+            Config::default(),
+            RuntimeOverrides::default(),
+            ClaudyState::default(),
+            PriceTable::default(),
+            WatcherBackend::Off,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        (app, base)
+    }
+
+    #[test]
+    fn help_pages_by_the_view_and_stops_at_the_end() {
+        let (mut app, _base) = app_with_session(0);
+        app.view_area = Rect::new(0, 0, 80, 12);
+        app.views.help_max_scroll = 25;
+        let key = |code| event::KeyEvent::new(code, event::KeyModifiers::NONE);
+        app.handle_help_key(key(KeyCode::PageDown));
+        assert_eq!(app.views.help_scroll, 10);
+        app.handle_help_key(key(KeyCode::PageDown));
+        app.handle_help_key(key(KeyCode::PageDown));
+        assert_eq!(app.views.help_scroll, 25);
+        app.handle_help_key(key(KeyCode::Char('k')));
+        assert_eq!(app.views.help_scroll, 24);
+    }
+
+    #[test]
+    fn popups_swallow_clicks() {
+        let (mut app, _base) = app_with_session(1);
+        app.session_list_area = Rect::new(0, 0, 40, 20);
+        app.info_regions = vec![(Rect::new(0, 0, 40, 20), InfoAction::FilterRepo("x".into()))];
+        app.popup = Some(Popup::Starred { selected: 0 });
+        app.handle_mouse_event(event::MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 5,
+            row: 5,
+            modifiers: KeyModifiers::NONE,
+        });
+        assert_eq!(app.filter_text, None);
+        assert!(app.popup.is_some());
+    }
+
+    #[test]
+    fn forgetting_a_session_drops_its_view_state() {
+        let (mut app, _base) = app_with_session(2);
+        app.paused = Some(("s".to_string(), 1));
+        app.compare_base = Some("s".to_string());
+        app.unfolded_messages
+            .insert("s".to_string(), HashSet::from([0]));
+        app.forget_session("s");
+        assert!(app.sessions.is_empty());
+        assert_eq!(app.paused, None);
+        assert_eq!(app.compare_base, None);
+        assert!(app.unfolded_messages.is_empty());
+    }
+
+    #[test]
+    fn shifting_moves_every_index_into_the_session() {
+        let (mut app, _base) = app_with_session(3);
+        assert_eq!(app.selected_session.as_deref(), Some("s"));
+        app.selected_message = Some(1);
+        app.chat_jump_to = Some(2);
+        app.chat_anchor = Some((3, 7));
+        app.paused = Some(("s".to_string(), 4));
+        app.unfolded_messages
+            .insert("s".to_string(), HashSet::from([5]));
+        let mut replay = Replay::new("s".to_string());
+        replay.position = 6;
+        app.replay = Some(replay);
+
+        app.shift_message_indices("s", 10);
+        assert_eq!(app.selected_message, Some(11));
+        assert_eq!(app.chat_jump_to, Some(12));
+        assert_eq!(app.chat_anchor, Some((13, 7)));
+        assert_eq!(app.paused, Some(("s".to_string(), 14)));
+        assert_eq!(app.unfolded_messages["s"], HashSet::from([15]));
+        assert_eq!(app.replay.as_ref().map(|r| r.position), Some(16));
+
+        // Another session's messages moving leaves them alone
+        app.shift_message_indices("other", 10);
+        assert_eq!(app.selected_message, Some(11));
+        assert_eq!(app.replay.as_ref().map(|r| r.position), Some(16));
+    }
+
+    #[test]
+    fn loading_the_full_transcript_keeps_positions() {
+        let (mut app, _base) = app_with_session(session::TAIL_MESSAGES + 5);
+        app.chat_anchor = Some((0, 2));
+        let mut replay = Replay::new("s".to_string());
+        replay.position = 3;
+        app.replay = Some(replay);
+        app.load_full_transcript("s").unwrap();
+        assert_eq!(app.sessions["s"].messages.len(), session::TAIL_MESSAGES + 5);
+        assert_eq!(app.chat_anchor, Some((5, 2)));
+        assert_eq!(app.replay.as_ref().map(|r| r.position), Some(8));
+    }
+}
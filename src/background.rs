@@ -0,0 +1,99 @@
+use std::io::{IsTerminal, Read, Write};
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
+
+use crossterm::terminal;
+use serde::Deserialize;
+
+/// Longest wait for the terminal to answer
+const REPLY_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Terminal background the colors are picked for (`background`)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Background {
+    /// Ask the terminal at startup, assuming dark if it doesn't say
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+/// Ask the terminal for its background color (OSC 11) and tell light from
+/// dark by its luminance; None if it doesn't answer. The query is followed
+/// by a device attributes request, which every terminal answers, so one
+/// ignoring OSC 11 costs no more than a round trip. Run it before the TUI
+/// reads input: the reply arrives on stdin.
+pub fn detect() -> Option<Background> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    terminal::enable_raw_mode().ok()?;
+    let reply = query();
+    let _ = terminal::disable_raw_mode();
+    let (r, g, b) = parse_rgb(&reply?)?;
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+/// Everything the terminal sent back up to its device attributes reply
+fn query() -> Option<String> {
+    let mut out = std::io::stdout();
+    write!(out, "\x1b]11;?\x1b\\\x1b[c").ok()?;
+    out.flush().ok()?;
+
+    let mut stdin = std::io::stdin();
+    let mut reply = Vec::new();
+    let deadline = Instant::now() + REPLY_TIMEOUT;
+    // The device attributes reply, `ESC [ ? … c`, comes last
+    while !reply
+        .windows(3)
+        .position(|w| w == b"\x1b[?")
+        .is_some_and(|at| reply[at..].contains(&b'c'))
+    {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() || !readable(&stdin, left) {
+            break;
+        }
+        let mut buf = [0; 64];
+        match stdin.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => reply.extend_from_slice(&buf[..n]),
+        }
+    }
+    Some(String::from_utf8_lossy(&reply).into_owned())
+}
+
+/// Whether `stdin` has input within `timeout`, so a silent terminal can't
+/// leave a read blocked (and later steal a keypress)
+fn readable(stdin: &std::io::Stdin, timeout: Duration) -> bool {
+    let mut fd = libc::pollfd {
+        fd: stdin.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // SAFETY: one valid pollfd for the duration of the call
+    let ready = unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) };
+    ready > 0
+}
+
+/// Components of the `rgb:RRRR/GGGG/BBBB` color in an OSC 11 reply, each
+/// scaled to 0..=1 (terminals use one to four hex digits per component)
+fn parse_rgb(reply: &str) -> Option<(f64, f64, f64)> {
+    let start = reply.find("rgb:")? + "rgb:".len();
+    let mut components = reply[start..].split('/').map(|part| {
+        let digits: String = part.chars().take_while(char::is_ascii_hexdigit).collect();
+        let value = u32::from_str_radix(&digits, 16).ok()?;
+        let max = 16u32.checked_pow(digits.len() as u32)? - 1;
+        Some(value as f64 / max as f64)
+    });
+    Some((
+        components.next()??,
+        components.next()??,
+        components.next()??,
+    ))
+}
@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+
+use crate::message::{MessageType, SessionMessage};
+
+/// A custom message type assigned by pattern (`[[message_classes]]` in
+/// config.toml), e.g. Bash commands containing `rm -rf` as "Danger". A
+/// message must satisfy every condition given; the first class that
+/// matches wins.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MessageClass {
+    /// Label shown in place of the role
+    pub name: String,
+    /// Tool called by the message; a trailing `*` matches a prefix
+    pub tool: Option<String>,
+    /// Text the content must contain
+    pub contains: Option<String>,
+    /// Message types the class applies to; empty means all
+    #[serde(default)]
+    pub types: Vec<MessageType>,
+    /// Label color: a name ("red", "lightblue"), index or "#rrggbb"
+    #[serde(default = "default_color", deserialize_with = "deserialize_color")]
+    pub color: Color,
+    /// Start with these messages hidden from the chat view
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+fn default_color() -> Color {
+    Color::Yellow
+}
+
+fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Color::from_str(&name)
+        .map_err(|_| serde::de::Error::custom(format!("unknown color {:?}", name)))
+}
+
+impl MessageClass {
+    fn matches(&self, message: &SessionMessage) -> bool {
+        let tool_matches = self.tool.as_deref().is_none_or(|pattern| {
+            message
+                .tool_names()
+                .any(|tool| match pattern.strip_suffix('*') {
+                    Some(prefix) => tool.starts_with(prefix),
+                    None => tool == pattern,
+                })
+        });
+        (self.types.is_empty() || self.types.contains(&message.msg_type))
+            && tool_matches
+            && self
+                .contains
+                .as_ref()
+                .is_none_or(|needle| message.content.contains(needle.as_str()))
+    }
+}
+
+/// The first class in `classes` that `message` falls into
+pub fn classify<'a>(
+    classes: &'a [MessageClass],
+    message: &SessionMessage,
+) -> Option<&'a MessageClass> {
+    classes.iter().find(|c| c.matches(message))
+}
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+
+use chrono::TimeDelta;
+
+use crate::message::{MessageType, SessionMessage};
+use crate::pricing::PriceTable;
+use crate::session::Session;
+
+/// A user prompt and everything Claude did until the next one
+#[derive(Debug, Clone)]
+pub struct Turn {
+    pub prompt: String,
+    /// Assistant text of the turn, tool calls left out
+    pub response: String,
+    /// Tools called during the turn, in order
+    pub tools: Vec<String>,
+}
+
+/// Split a transcript into turns; anything before the first prompt is dropped
+pub fn turns(messages: &[SessionMessage]) -> Vec<Turn> {
+    let mut turns: Vec<Turn> = Vec::new();
+    for message in messages {
+        if message.is_prompt() {
+            turns.push(Turn {
+                prompt: message.content.clone(),
+                response: String::new(),
+                tools: Vec::new(),
+            });
+            continue;
+        }
+        let Some(turn) = turns.last_mut() else {
+            continue;
+        };
+        match message.msg_type {
+            MessageType::Assistant => {
+                if !turn.response.is_empty() {
+                    turn.response.push('\n');
+                }
+                turn.response.push_str(&message.content);
+            }
+            MessageType::ToolUse => turn.tools.extend(message.tool_names().map(str::to_string)),
+            _ => {}
+        }
+    }
+    turns
+}
+
+/// Text compared with whitespace runs collapsed, so re-wrapped answers match
+fn normalized(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// One line of the comparison: the turns answering the same prompt, or a
+/// turn only one session has
+#[derive(Debug, Clone)]
+pub struct TurnRow {
+    pub left: Option<Turn>,
+    pub right: Option<Turn>,
+}
+
+impl TurnRow {
+    /// Whether both sides asked the same thing
+    pub fn same_prompt(&self) -> bool {
+        match (&self.left, &self.right) {
+            (Some(l), Some(r)) => normalized(&l.prompt) == normalized(&r.prompt),
+            _ => false,
+        }
+    }
+
+    /// Whether Claude's answers (or tool use) differ between the sessions
+    pub fn differs(&self) -> bool {
+        match (&self.left, &self.right) {
+            (Some(l), Some(r)) => {
+                normalized(&l.response) != normalized(&r.response) || l.tools != r.tools
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Pair up the turns of two sessions. Identical prompts are matched in
+/// order (longest common subsequence); the unmatched turns between two
+/// matches are paired side by side, as they are most likely rewordings of
+/// the same request.
+pub fn align(left: Vec<Turn>, right: Vec<Turn>) -> Vec<TurnRow> {
+    let l: Vec<String> = left.iter().map(|t| normalized(&t.prompt)).collect();
+    let r: Vec<String> = right.iter().map(|t| normalized(&t.prompt)).collect();
+    // lcs[i][j]: length of the common subsequence of l[i..] and r[j..]
+    let mut lcs = vec![vec![0usize; r.len() + 1]; l.len() + 1];
+    for i in (0..l.len()).rev() {
+        for j in (0..r.len()).rev() {
+            lcs[i][j] = if l[i] == r[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut left = left.into_iter().map(Some).collect::<Vec<_>>();
+    let mut right = right.into_iter().map(Some).collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    let (mut pending_left, mut pending_right) = (Vec::new(), Vec::new());
+    let flush = |rows: &mut Vec<TurnRow>, ls: &mut Vec<Turn>, rs: &mut Vec<Turn>| {
+        let mut ls = ls.drain(..);
+        let mut rs = rs.drain(..);
+        loop {
+            match (ls.next(), rs.next()) {
+                (None, None) => break,
+                (left, right) => rows.push(TurnRow { left, right }),
+            }
+        }
+    };
+    while i < l.len() || j < r.len() {
+        if i < l.len() && j < r.len() && l[i] == r[j] {
+            flush(&mut rows, &mut pending_left, &mut pending_right);
+            rows.push(TurnRow {
+                left: left[i].take(),
+                right: right[j].take(),
+            });
+            i += 1;
+            j += 1;
+        } else if j == r.len() || (i < l.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            pending_left.extend(left[i].take());
+            i += 1;
+        } else {
+            pending_right.extend(right[j].take());
+            j += 1;
+        }
+    }
+    flush(&mut rows, &mut pending_left, &mut pending_right);
+    rows
+}
+
+/// Totals of one session for the stats delta
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub prompts: usize,
+    pub messages: usize,
+    pub tokens: u64,
+    pub cost: f64,
+    pub duration: Option<TimeDelta>,
+    /// Calls per tool
+    pub tools: BTreeMap<String, usize>,
+    /// Model of the last reply
+    pub model: Option<String>,
+}
+
+impl Stats {
+    pub fn new(session: &Session, prices: &PriceTable) -> Self {
+        let mut tools = BTreeMap::new();
+        for name in session.messages.iter().flat_map(|m| m.tool_names()) {
+            *tools.entry(name.to_string()).or_insert(0) += 1;
+        }
+        Self {
+            prompts: session.messages.iter().filter(|m| m.is_prompt()).count(),
+            messages: session.message_count(),
+            tokens: session.total_tokens(),
+            cost: prices.total_cost(&session.usage).0,
+            duration: session.duration(),
+            tools,
+            model: session
+                .usage
+                .iter()
+                .rev()
+                .find_map(|p| p.model.as_deref().map(str::to_string)),
+        }
+    }
+
+    pub fn tool_calls(&self) -> usize {
+        self.tools.values().sum()
+    }
+}
+
+/// Two sessions lined up turn by turn (`D` on one session, then another)
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub left_id: String,
+    pub right_id: String,
+    pub left: Stats,
+    pub right: Stats,
+    pub rows: Vec<TurnRow>,
+}
+
+impl Comparison {
+    /// Compare the loaded messages of two sessions; load the whole
+    /// transcripts first for a full comparison
+    pub fn new(left: &Session, right: &Session, prices: &PriceTable) -> Self {
+        Self {
+            left_id: left.id.clone(),
+            right_id: right.id.clone(),
+            left: Stats::new(left, prices),
+            right: Stats::new(right, prices),
+            rows: align(turns(&left.messages), turns(&right.messages)),
+        }
+    }
+
+    /// Every tool either session called, with both call counts
+    pub fn tool_counts(&self) -> Vec<(&str, usize, usize)> {
+        let mut names: Vec<&str> = self
+            .left
+            .tools
+            .keys()
+            .chain(self.right.tools.keys())
+            .map(String::as_str)
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+            .into_iter()
+            .map(|name| {
+                (
+                    name,
+                    self.left.tools.get(name).copied().unwrap_or(0),
+                    self.right.tools.get(name).copied().unwrap_or(0),
+                )
+            })
+            .collect()
+    }
+}
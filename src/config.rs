@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::alerts::{
+    ErrorBurstConfig, NotifierConfig, NtfyConfig, TerminalNotifyConfig, WebhookConfig,
+};
+use crate::background::Background;
+use crate::classes::MessageClass;
+use crate::context::ContextConfig;
+use crate::health::HealthConfig;
+use crate::hooks::HookConfig;
+use crate::otlp::OtlpConfig;
+use crate::pricing::Currency;
+use crate::redact::RedactionConfig;
+use crate::render::RenderRule;
+use crate::script::ScriptColumn;
+use crate::session::IgnoreList;
+use crate::snapshot::SnapshotConfig;
+use crate::statsd::StatsdConfig;
+use crate::timezone::TimeFormat;
+use crate::ui::{ChatStyle, ContentAlign, Density, SessionColumn};
+use crate::watcher::WatcherBackend;
+
+/// User configuration, read from ~/.config/claudy/config.toml.
+/// Every field is optional; a missing file means all defaults.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Claude projects directory, if not ~/.claude/projects; `--path`
+    /// overrides, and the first-run setup writes it
+    pub projects_dir: Option<PathBuf>,
+    /// Named sets of projects directories to switch between (Ctrl-W, or
+    /// `--workspace` at startup)
+    pub workspaces: Vec<Workspace>,
+    /// Display names for projects (`[project_aliases]`), keyed by project
+    /// slug (`-Users-me-code-acme-api`) or by the name claudy would show
+    pub project_aliases: HashMap<String, String>,
+    /// Projects never discovered or watched: slugs or path globs, such as
+    /// `"/tmp/*"` or `"~/scratch/**"`
+    pub ignore_projects: IgnoreList,
+    /// Named filters offered in the quick-pick menu (`F`)
+    pub saved_filters: Vec<SavedFilter>,
+    /// Redraw and file-poll interval while there is activity
+    pub tick_rate_ms: u64,
+    /// Slower interval used once nothing has happened for a while and no
+    /// session is active; set equal to `tick_rate_ms` to disable
+    pub idle_tick_rate_ms: u64,
+    /// Lines of a single message rendered before the rest is cut off
+    /// (toggle with `T`); 0 renders every message in full
+    pub max_message_lines: usize,
+    /// Assistant messages longer than this render folded to their first
+    /// line until expanded with `o`; 0 disables folding
+    pub fold_lines: usize,
+    /// Wrap chat text at this many columns even in a wider pane; 0 uses
+    /// the whole width
+    pub max_content_width: u16,
+    /// Where capped chat text sits in a wider pane: "left" or "center"
+    pub content_align: ContentAlign,
+    /// Chat layout at startup: "stream", "bubbles" for prompts and
+    /// replies in boxes on opposite sides, or "oneline" (cycle with `V`)
+    pub chat_style: ChatStyle,
+    /// Chat spacing: "comfortable", or "compact" to drop the blank line
+    /// between messages, the header times and most of the indent
+    pub density: Density,
+    /// File watcher backend (`native`, `poll` or `off`); `--watcher` overrides
+    pub watcher: WatcherBackend,
+    /// Scan interval for the `poll` backend and the automatic fallback to it
+    pub poll_interval_secs: u64,
+    /// Tokens your plan allows per 5-hour usage block; 0 shows usage
+    /// without a gauge
+    pub block_token_limit: u64,
+    /// Percentage of `block_token_limit` at which the gauge turns yellow
+    pub block_warn_percent: u64,
+    /// Tokens you want to spend per day; 0 disables the daily projection
+    pub daily_token_budget: u64,
+    /// Currency and number format for estimated costs
+    pub currency: Currency,
+    /// Extra right-aligned columns in the session list, e.g.
+    /// `["tokens", "cost"]`; dropped from the right when the panel is narrow
+    pub session_columns: Vec<SessionColumn>,
+    /// Headings such as "Today" and "Last week" between sessions last
+    /// active in different periods
+    pub date_separators: bool,
+    /// Columns computed by script expressions, named in `session_columns`
+    pub script_columns: Vec<ScriptColumn>,
+    /// Export session activity to an OpenTelemetry collector
+    pub otlp: Option<OtlpConfig>,
+    /// Send counters and gauges to a StatsD daemon
+    pub statsd: Option<StatsdConfig>,
+    /// Slack/Discord webhooks told when sessions finish or need input
+    pub webhooks: Vec<WebhookConfig>,
+    /// ntfy topics pushed the same alerts
+    pub ntfy: Vec<NtfyConfig>,
+    /// Ring the terminal bell when a session starts waiting for you
+    pub attention_bell: bool,
+    /// Flash that session's list entry in inverse video
+    pub attention_flash: bool,
+    /// How many API errors in how long mark a session as failing
+    pub error_burst: ErrorBurstConfig,
+    /// Context window sizes for the estimated fill in the info panel
+    pub context: ContextConfig,
+    /// When a turn makes a session's health glyph read slow
+    pub health: HealthConfig,
+    /// Start in privacy mode (toggle with `P`): prompts and working
+    /// directories are blurred for screen sharing
+    pub privacy_mode: bool,
+    /// Show the one-column transcript map at the chat pane's right edge
+    /// (toggle with `M`)
+    pub minimap: bool,
+    /// Desktop notifications via OSC 9 / OSC 777 escape sequences
+    pub terminal_notify: Option<TerminalNotifyConfig>,
+    /// Make file references and URLs in the chat clickable with OSC 8
+    /// hyperlinks; turn off on terminals that print the escapes as text
+    pub hyperlinks: bool,
+    /// Draw without colors, keeping their distinctions as bold, underline
+    /// and reverse; also set by `NO_COLOR` or `--no-color`
+    pub no_color: bool,
+    /// Terminal background to pick colors for: "auto" asks the terminal,
+    /// "dark" or "light" skip asking
+    pub background: Background,
+    /// Tint messages by role and shade the focused panel's border when the
+    /// terminal takes 24-bit colors; turn off to keep the 16-color scheme
+    pub truecolor: bool,
+    /// Screen reader mode: words instead of glyphs and colors for state,
+    /// each chat line prefixed with its role, nothing blinking, and no
+    /// minimap or bubbles; also set by `--screen-reader`
+    pub screen_reader: bool,
+    /// Draw glyphs, borders and bars in plain ASCII, for terminals that
+    /// garble them; also set by `--ascii`
+    pub ascii: bool,
+    /// Zone for the times shown: "local" (the default), "UTC", or an IANA
+    /// name such as "Europe/Berlin"
+    pub timezone: String,
+    /// "24h" (the default) or "12h" clock for the times shown
+    pub time_format: TimeFormat,
+    /// Commands run with each new message's JSON on stdin
+    pub hooks: Vec<HookConfig>,
+    /// Custom renderings for messages matching a tool or content pattern
+    pub renderers: Vec<RenderRule>,
+    /// Custom message types assigned by tool name or content
+    pub message_classes: Vec<MessageClass>,
+    /// Masking of API keys and tokens in displayed and copied content
+    pub redaction: RedactionConfig,
+    /// Where `x`/`X` snapshots go and how they become PNGs
+    pub snapshot: SnapshotConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            projects_dir: None,
+            workspaces: Vec::new(),
+            project_aliases: HashMap::new(),
+            ignore_projects: IgnoreList::default(),
+            saved_filters: Vec::new(),
+            tick_rate_ms: 250,
+            idle_tick_rate_ms: 1000,
+            max_message_lines: 40,
+            fold_lines: 12,
+            max_content_width: 0,
+            content_align: ContentAlign::default(),
+            chat_style: ChatStyle::default(),
+            density: Density::default(),
+            watcher: WatcherBackend::Native,
+            poll_interval_secs: 2,
+            block_token_limit: 0,
+            block_warn_percent: 80,
+            daily_token_budget: 0,
+            currency: Currency::default(),
+            session_columns: Vec::new(),
+            date_separators: true,
+            script_columns: Vec::new(),
+            otlp: None,
+            statsd: None,
+            webhooks: Vec::new(),
+            ntfy: Vec::new(),
+            attention_bell: false,
+            attention_flash: false,
+            error_burst: ErrorBurstConfig::default(),
+            context: ContextConfig::default(),
+            health: HealthConfig::default(),
+            privacy_mode: false,
+            minimap: true,
+            terminal_notify: None,
+            hyperlinks: true,
+            no_color: false,
+            background: Background::default(),
+            truecolor: true,
+            screen_reader: false,
+            ascii: false,
+            timezone: "local".to_string(),
+            time_format: TimeFormat::default(),
+            hooks: Vec::new(),
+            renderers: Vec::new(),
+            message_classes: Vec::new(),
+            redaction: RedactionConfig::default(),
+            snapshot: SnapshotConfig::default(),
+        }
+    }
+}
+
+/// A named set of projects directories to watch together (`[[workspaces]]`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    pub paths: Vec<PathBuf>,
+}
+
+impl Workspace {
+    /// The paths, with a leading `~/` taken as the home directory
+    pub fn dirs(&self) -> Vec<PathBuf> {
+        self.paths
+            .iter()
+            .map(|path| match (path.strip_prefix("~"), dirs::home_dir()) {
+                (Ok(rest), Some(home)) => home.join(rest),
+                _ => path.clone(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SavedFilter {
+    pub name: String,
+    /// Filter text, using the same syntax as `/`
+    pub filter: String,
+}
+
+impl Config {
+    /// Every configured alert destination
+    pub fn notifiers(&self) -> Vec<NotifierConfig> {
+        let webhooks = self.webhooks.iter().cloned().map(NotifierConfig::Webhook);
+        let ntfy = self.ntfy.iter().cloned().map(NotifierConfig::Ntfy);
+        let terminal = self
+            .terminal_notify
+            .iter()
+            .cloned()
+            .map(NotifierConfig::Terminal);
+        webhooks.chain(ntfy).chain(terminal).collect()
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("claudy").join("config.toml"))
+    }
+
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&data).with_context(|| format!("invalid config {}", path.display()))
+    }
+}
+
+/// What the command line and the terminal decide over the config file:
+/// applied at startup and again after every reload, which starts from
+/// what the file says
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuntimeOverrides {
+    /// `--no-color` or `NO_COLOR`
+    pub no_color: bool,
+    pub screen_reader: bool,
+    pub ascii: bool,
+    /// Whether the terminal takes 24-bit colors
+    pub truecolor: bool,
+    /// The terminal's background, as detected at startup
+    pub background: Option<Background>,
+}
+
+impl RuntimeOverrides {
+    pub fn apply(&self, config: &mut Config) {
+        config.no_color |= self.no_color;
+        config.screen_reader |= self.screen_reader;
+        config.ascii |= self.ascii;
+        if config.screen_reader {
+            // Only what reads out linearly
+            config.no_color = true;
+            config.minimap = false;
+            config.chat_style = ChatStyle::Stream;
+        }
+        config.truecolor &= !config.no_color && self.truecolor;
+        if config.background == Background::Auto {
+            config.background = self.background.unwrap_or(Background::Dark);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_survive_a_reload() {
+        let overrides = RuntimeOverrides {
+            screen_reader: true,
+            truecolor: true,
+            background: Some(Background::Light),
+            ..RuntimeOverrides::default()
+        };
+        // A reload starts again from the file's values
+        for _ in 0..2 {
+            let mut config = Config {
+                background: Background::Auto,
+                ..Config::default()
+            };
+            overrides.apply(&mut config);
+            assert!(config.no_color && config.screen_reader);
+            assert!(!config.truecolor && !config.minimap);
+            assert_eq!(config.background, Background::Light);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::session::Session;
+
+/// Context window sizes (`[context]` in config.toml)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    /// Tokens a model can hold, unless listed in `models`
+    pub window: u64,
+    /// Windows of specific models (e.g. a 1M-token beta), matched against
+    /// model ids by substring
+    pub models: BTreeMap<String, u64>,
+    /// Fill at which a session gets a yellow warning; Claude Code compacts
+    /// automatically a little short of a full window
+    pub warn_percent: u64,
+    /// Fill at which the warning turns red
+    pub critical_percent: u64,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            window: 200_000,
+            models: BTreeMap::new(),
+            warn_percent: 70,
+            critical_percent: 85,
+        }
+    }
+}
+
+/// Estimated fill of a session's context window
+#[derive(Debug, Clone, Copy)]
+pub struct ContextUsage {
+    pub tokens: u64,
+    pub window: u64,
+}
+
+impl ContextUsage {
+    pub fn percent(&self) -> u64 {
+        self.tokens * 100 / self.window.max(1)
+    }
+}
+
+/// How close a session is to compaction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContextLevel {
+    Warn,
+    Critical,
+}
+
+impl ContextConfig {
+    pub fn window_for(&self, model: Option<&str>) -> u64 {
+        model
+            .and_then(|model| {
+                self.models
+                    .iter()
+                    .filter(|(pattern, _)| model.contains(pattern.as_str()))
+                    .max_by_key(|(pattern, _)| pattern.len())
+            })
+            .map_or(self.window, |(_, window)| *window)
+    }
+
+    /// The context of the latest reply: everything it read (cached or
+    /// not) plus what it wrote, which the next request carries along.
+    /// Unknown after a compaction until the next reply.
+    pub fn usage(&self, session: &Session) -> Option<ContextUsage> {
+        let last = session
+            .usage
+            .iter()
+            .rev()
+            .find(|p| p.usage.tokens_in() > 0)?;
+        if session.compactions.last() > Some(&last.timestamp) {
+            return None;
+        }
+        Some(ContextUsage {
+            tokens: last.usage.tokens_in() + last.usage.output,
+            window: self.window_for(last.model.as_deref()),
+        })
+    }
+
+    /// Warning for a session whose context crossed a threshold
+    pub fn level(&self, usage: &ContextUsage) -> Option<ContextLevel> {
+        let percent = usage.percent();
+        if percent >= self.critical_percent {
+            Some(ContextLevel::Critical)
+        } else if percent >= self.warn_percent {
+            Some(ContextLevel::Warn)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::session::Session;
+
+/// Parsed session list filter.
+///
+/// `tag:<name>` terms require the session to carry that tag, `branch:<name>`
+/// terms match the git branch exactly and `repo:<name>` terms the project,
+/// which for a linked worktree is its primary repository (both
+/// case-insensitive). Everything else
+/// is joined back together and matched as a case-insensitive substring of the
+/// display name, session id, or summary. An `expr:` term takes the rest of
+/// the input as a script expression (see `script.rs`), evaluated by the app.
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    tags: Vec<String>,
+    branches: Vec<String>,
+    repos: Vec<String>,
+    text: Option<String>,
+    script: Option<String>,
+}
+
+impl SessionFilter {
+    pub fn parse(input: &str) -> Self {
+        let mut filter = SessionFilter::default();
+        let mut text_words = Vec::new();
+        // Expressions contain spaces, so `expr:` swallows everything after it
+        let input = match input.split_once("expr:") {
+            Some((before, expr)) if before.is_empty() || before.ends_with(' ') => {
+                if !expr.trim().is_empty() {
+                    filter.script = Some(expr.trim().to_string());
+                }
+                before
+            }
+            _ => input,
+        };
+        for word in input.split(' ') {
+            if let Some(tag) = word.strip_prefix("tag:") {
+                let tag = tag.trim_start_matches('#');
+                if !tag.is_empty() {
+                    filter.tags.push(tag.to_lowercase());
+                }
+            } else if let Some(branch) = word.strip_prefix("branch:") {
+                if !branch.is_empty() {
+                    filter.branches.push(branch.to_lowercase());
+                }
+            } else if let Some(repo) = word.strip_prefix("repo:") {
+                if !repo.is_empty() {
+                    filter.repos.push(repo.to_lowercase());
+                }
+            } else {
+                text_words.push(word);
+            }
+        }
+        let text = text_words.join(" ").trim().to_lowercase();
+        if !text.is_empty() {
+            filter.text = Some(text);
+        }
+        filter
+    }
+
+    /// Free-text part of the filter (lowercased), if any
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Script expression from an `expr:` term, if any
+    pub fn script(&self) -> Option<&str> {
+        self.script.as_deref()
+    }
+
+    pub fn matches(&self, session: &Session) -> bool {
+        let tags_match = self
+            .tags
+            .iter()
+            .all(|want| session.tags.iter().any(|t| t.to_lowercase() == *want));
+        let branch_lower = session.git_branch.as_deref().map(str::to_lowercase);
+        let branches_match = self
+            .branches
+            .iter()
+            .all(|want| branch_lower.as_deref() == Some(want.as_str()));
+        let repos_match = self
+            .repos
+            .iter()
+            .all(|want| session.project_name().to_lowercase() == *want);
+        if !tags_match || !branches_match || !repos_match {
+            return false;
+        }
+        let Some(ref text) = self.text else {
+            return true;
+        };
+        session.display_name().to_lowercase().contains(text)
+            || session.id.contains(text)
+            || session
+                .summary
+                .as_deref()
+                .unwrap_or("")
+                .to_lowercase()
+                .contains(text)
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::message;
+use crate::session::Session;
+
+/// How long an answer is shown before it is queried again
+const ANSWER_TTL: Duration = Duration::from_secs(10);
+
+/// Time after a session's last entry its commits may still land
+const COMMIT_SLACK: TimeDelta = TimeDelta::minutes(2);
+
+/// State of a working tree, from `git status --porcelain=v2 --branch`
+#[derive(Debug, Clone)]
+pub struct GitStatus {
+    /// None on a detached HEAD
+    pub branch: Option<String>,
+    /// Changed, staged and untracked files
+    pub dirty: usize,
+    /// Commits ahead of and behind the upstream, if there is one
+    pub ahead_behind: Option<(usize, usize)>,
+}
+
+/// Status of the repository containing `dir`, or None if it isn't in one;
+/// blocking, so call it off the UI thread. Runs without optional locks, so
+/// refreshing the index never holds `index.lock` while the session's own
+/// git commands want it.
+pub fn status(dir: &str) -> Option<GitStatus> {
+    let output = Command::new("git")
+        .args([
+            "--no-optional-locks",
+            "-C",
+            dir,
+            "status",
+            "--porcelain=v2",
+            "--branch",
+        ])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut status = GitStatus {
+        branch: None,
+        dirty: 0,
+        ahead_behind: None,
+    };
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            status.branch = (head != "(detached)").then(|| head.to_string());
+        } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            let mut counts = ab
+                .split(' ')
+                .map(|n| n.trim_start_matches(['+', '-']).parse().ok());
+            if let (Some(Some(ahead)), Some(Some(behind))) = (counts.next(), counts.next()) {
+                status.ahead_behind = Some((ahead, behind));
+            }
+        } else if !line.starts_with('#') {
+            status.dirty += 1;
+        }
+    }
+    Some(status)
+}
+
+/// A linked worktree and the repository it belongs to
+#[derive(Debug, Clone)]
+pub struct Worktree {
+    /// Name of the primary repository's directory
+    pub repo: String,
+    /// Name of the worktree's directory
+    pub name: String,
+}
+
+/// The worktree `dir` lies in, if it is a linked one: its `.git` is a file
+/// pointing into `<repo>/.git/worktrees/` (or `<repo>.git/worktrees/` of a
+/// bare repository). Only reads the filesystem, so it's cheap enough to
+/// call while loading sessions.
+pub fn worktree(dir: &str) -> Option<Worktree> {
+    let root = Path::new(dir)
+        .ancestors()
+        .find(|d| d.join(".git").exists())?;
+    let link = fs::read_to_string(root.join(".git")).ok()?;
+    Some(Worktree {
+        repo: linked_repo(root, &link)?,
+        name: root.file_name()?.to_str()?.to_string(),
+    })
+}
+
+/// Name of the repository a worktree at `root` belongs to, from the
+/// `gitdir: <path>` line of its `.git` file; the path may be relative to
+/// `root`
+fn linked_repo(root: &Path, link: &str) -> Option<String> {
+    let gitdir = normalize(&root.join(link.strip_prefix("gitdir:")?.trim()));
+    // <common>/worktrees/<name>; submodules point into .git/modules
+    let common = gitdir
+        .parent()
+        .filter(|p| p.ends_with("worktrees"))?
+        .parent()?;
+    let repo = match common.file_name()?.to_str()? {
+        ".git" => common.parent()?.file_name()?.to_str()?,
+        bare => bare.strip_suffix(".git").unwrap_or(bare),
+    };
+    (!repo.is_empty()).then(|| repo.to_string())
+}
+
+/// `path` with `.` and `..` worked out without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normal.pop();
+            }
+            c => normal.push(c),
+        }
+    }
+    normal
+}
+
+/// A commit as listed by `log`
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub hash: String,
+    /// Committer date
+    pub time: DateTime<Utc>,
+    pub author: String,
+    pub subject: String,
+}
+
+/// Commits on any branch of the repository containing `dir` committed
+/// between `since` and `until`, newest first; blocking, so call it off the
+/// UI thread
+pub fn log(dir: &str, since: DateTime<Utc>, until: DateTime<Utc>) -> Option<Vec<Commit>> {
+    let output = Command::new("git")
+        .args(["-C", dir, "log", "--all", "--format=%H%x1f%cI%x1f%an%x1f%s"])
+        .arg(format!("--since={}", since.to_rfc3339()))
+        .arg(format!("--until={}", until.to_rfc3339()))
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let commits = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                time: DateTime::parse_from_rfc3339(fields.next()?)
+                    .ok()?
+                    .with_timezone(&Utc),
+                author: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect();
+    Some(commits)
+}
+
+/// `git show` of `hash` in the repository containing `dir`: header, stat
+/// and patch, uncolored; blocking, so call it off the UI thread
+pub fn show(dir: &str, hash: &str) -> Option<String> {
+    if !message::is_commit_hash(hash) {
+        return None;
+    }
+    let output = Command::new("git")
+        .args([
+            "-C",
+            dir,
+            "show",
+            "--stat",
+            "--patch",
+            "--format=fuller",
+            "--no-color",
+            "--end-of-options",
+        ])
+        .arg(hash)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Time span a commit list covers
+pub type Window = (DateTime<Utc>, DateTime<Utc>);
+
+/// Span in which commits count as made during `session`: its first to its
+/// last entry, plus a little for a commit finishing after the last reply
+pub fn activity_window(session: &Session) -> Option<Window> {
+    let start = session.last_activity - session.duration()?;
+    Some((start, session.last_activity + COMMIT_SLACK))
+}
+
+enum Answer {
+    Status(String, Option<GitStatus>),
+    Log(String, Window, Option<Vec<Commit>>),
+    Show(String, String, Option<String>),
+}
+
+/// Git queries run on background threads, with their latest answers
+/// cached per directory for the UI to read
+pub struct GitCache {
+    tx: mpsc::Sender<Answer>,
+    rx: mpsc::Receiver<Answer>,
+    statuses: HashMap<String, (Option<GitStatus>, Instant)>,
+    logs: HashMap<String, (Window, Option<Vec<Commit>>, Instant)>,
+    /// `git show` output per (directory, hash); commits don't change, so
+    /// these never expire, and a pending query is an entry of None
+    shows: HashMap<(String, String), Option<Option<String>>>,
+    /// Directories with a status query under way
+    pending_status: HashSet<String>,
+    /// Directories with a log query under way
+    pending_log: HashSet<String>,
+}
+
+impl GitCache {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            tx,
+            rx,
+            statuses: HashMap::new(),
+            logs: HashMap::new(),
+            shows: HashMap::new(),
+            pending_status: HashSet::new(),
+            pending_log: HashSet::new(),
+        }
+    }
+
+    /// Query the status of `dir` again unless a recent answer or a query
+    /// is under way
+    pub fn refresh(&mut self, dir: &str) {
+        let fresh = self
+            .statuses
+            .get(dir)
+            .is_some_and(|(_, at)| at.elapsed() < ANSWER_TTL);
+        if fresh || !self.pending_status.insert(dir.to_string()) {
+            return;
+        }
+        let dir = dir.to_string();
+        self.spawn(move || Answer::Status(dir.clone(), status(&dir)));
+    }
+
+    /// Like `refresh`, for the commits made within `window`; a different
+    /// window always queries again
+    pub fn refresh_log(&mut self, dir: &str, window: Window) {
+        let fresh = self
+            .logs
+            .get(dir)
+            .is_some_and(|(w, _, at)| *w == window && at.elapsed() < ANSWER_TTL);
+        if fresh || !self.pending_log.insert(dir.to_string()) {
+            return;
+        }
+        let dir = dir.to_string();
+        self.spawn(move || Answer::Log(dir.clone(), window, log(&dir, window.0, window.1)));
+    }
+
+    /// Fetch commit `hash` of `dir` unless it was already
+    pub fn request_show(&mut self, dir: &str, hash: &str) {
+        let key = (dir.to_string(), hash.to_string());
+        if self.shows.contains_key(&key) {
+            return;
+        }
+        self.shows.insert(key, None);
+        let (dir, hash) = (dir.to_string(), hash.to_string());
+        self.spawn(move || {
+            let output = show(&dir, &hash);
+            Answer::Show(dir, hash, output)
+        });
+    }
+
+    fn spawn(&self, query: impl FnOnce() -> Answer + Send + 'static) {
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            // The receiver only goes away on exit
+            let _ = tx.send(query());
+        });
+    }
+
+    /// Take in the answers that came back since the last call
+    pub fn poll(&mut self) {
+        while let Ok(answer) = self.rx.try_recv() {
+            match answer {
+                Answer::Status(dir, status) => {
+                    self.pending_status.remove(&dir);
+                    self.statuses.insert(dir, (status, Instant::now()));
+                }
+                Answer::Log(dir, window, commits) => {
+                    self.pending_log.remove(&dir);
+                    self.logs.insert(dir, (window, commits, Instant::now()));
+                }
+                Answer::Show(dir, hash, output) => {
+                    self.shows.insert((dir, hash), Some(output));
+                }
+            }
+        }
+    }
+
+    /// Latest known status of `dir`: None while unknown, Some(None) if it
+    /// isn't in a repository
+    pub fn status(&self, dir: &str) -> Option<Option<&GitStatus>> {
+        self.statuses.get(dir).map(|(status, _)| status.as_ref())
+    }
+
+    /// Latest known commits of `dir` for a window starting at `since`
+    /// (its end may have moved on since): None while unknown, Some(None)
+    /// if it isn't in a repository
+    pub fn log(&self, dir: &str, since: DateTime<Utc>) -> Option<Option<&[Commit]>> {
+        self.logs
+            .get(dir)
+            .filter(|(window, _, _)| window.0 == since)
+            .map(|(_, commits, _)| commits.as_deref())
+    }
+
+    /// Fetched `git show` output: None while under way (or never asked
+    /// for), Some(None) if git failed
+    pub fn shown(&self, dir: &str, hash: &str) -> Option<Option<&str>> {
+        self.shows
+            .get(&(dir.to_string(), hash.to_string()))?
+            .as_ref()
+            .map(|output| output.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linked_worktrees_name_their_repository() {
+        let root = Path::new("/src/app-fix");
+        assert_eq!(
+            linked_repo(root, "gitdir: /src/app/.git/worktrees/app-fix\n").as_deref(),
+            Some("app")
+        );
+        assert_eq!(
+            linked_repo(root, "gitdir: ../app/.git/worktrees/app-fix\n").as_deref(),
+            Some("app")
+        );
+        // Bare repository
+        assert_eq!(
+            linked_repo(root, "gitdir: /srv/app.git/worktrees/app-fix").as_deref(),
+            Some("app")
+        );
+        // Submodule
+        assert_eq!(linked_repo(root, "gitdir: ../.git/modules/lib"), None);
+    }
+}
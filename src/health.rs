@@ -0,0 +1,137 @@
+use chrono::{TimeDelta, Utc};
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::latency::{self, format_latency};
+use crate::message::MessageType;
+use crate::session::Session;
+
+/// When a turn counts as slow (`[health]` in config.toml)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub slow_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self { slow_secs: 300 }
+    }
+}
+
+/// Overall state of a session, worst first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HealthState {
+    /// Stuck in a burst of API errors
+    Erroring,
+    /// Claude stopped on a tool call, most likely waiting for permission
+    Waiting,
+    /// Context past the warning threshold
+    NearLimit,
+    /// The current or last turn ran longer than `slow_secs`
+    Slow,
+    Ok,
+}
+
+impl HealthState {
+    pub fn glyph(self) -> &'static str {
+        match self {
+            HealthState::Erroring => "✗",
+            HealthState::Waiting => "?",
+            HealthState::NearLimit => "◔",
+            HealthState::Slow => "◷",
+            HealthState::Ok => "·",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HealthState::Erroring => "erroring",
+            HealthState::Waiting => "stuck waiting",
+            HealthState::NearLimit => "near context limit",
+            HealthState::Slow => "slow",
+            HealthState::Ok => "ok",
+        }
+    }
+}
+
+/// A session's health and why
+#[derive(Debug, Clone)]
+pub struct Health {
+    pub state: HealthState,
+    pub reason: String,
+}
+
+impl Health {
+    fn new(state: HealthState, reason: String) -> Self {
+        Self { state, reason }
+    }
+}
+
+/// The worst thing going on in `session`
+pub fn health(session: &Session, config: &Config) -> Health {
+    if let Some(burst) = config.error_burst.burst(session) {
+        return Health::new(
+            HealthState::Erroring,
+            format!(
+                "{} API errors in {}, last {}",
+                burst.count,
+                format_latency(burst.last - burst.first),
+                burst.label
+            ),
+        );
+    }
+
+    let last = session
+        .messages
+        .iter()
+        .rev()
+        .find(|m| !matches!(m.msg_type, MessageType::Progress | MessageType::Other));
+    let awaiting = session.awaiting_user();
+    if awaiting && let Some(last) = last.filter(|m| m.msg_type == MessageType::ToolUse) {
+        return Health::new(
+            HealthState::Waiting,
+            format!(
+                "tool call unanswered for {}",
+                format_latency(Utc::now() - last.timestamp)
+            ),
+        );
+    }
+
+    if let Some(usage) = config.context.usage(session)
+        && config.context.level(&usage).is_some()
+    {
+        return Health::new(
+            HealthState::NearLimit,
+            format!("context ~{}% full", usage.percent()),
+        );
+    }
+
+    let slow = TimeDelta::seconds(config.health.slow_secs as i64);
+    if session.is_active() {
+        // A turn still running counts with the time so far
+        let running = (!awaiting)
+            .then(|| session.messages.iter().rev().find(|m| m.is_prompt()))
+            .flatten()
+            .map(|prompt| Utc::now() - prompt.timestamp);
+        if let Some(elapsed) = running.filter(|&e| e > slow) {
+            return Health::new(
+                HealthState::Slow,
+                format!("turn running for {}", format_latency(elapsed)),
+            );
+        }
+        let last_turn = latency::turn_latencies(&session.messages, true)
+            .last()
+            .map(|t| t.elapsed);
+        if running.is_none()
+            && let Some(elapsed) = last_turn.filter(|&e| e > slow)
+        {
+            return Health::new(
+                HealthState::Slow,
+                format!("last turn took {}", format_latency(elapsed)),
+            );
+        }
+    }
+
+    Health::new(HealthState::Ok, String::new())
+}
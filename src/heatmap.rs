@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::session::Session;
+use crate::timezone::DisplayZone;
+
+/// What the activity heatmap's shades measure; `m` switches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeatMetric {
+    Tokens,
+    Messages,
+}
+
+impl HeatMetric {
+    pub fn label(self) -> &'static str {
+        match self {
+            HeatMetric::Tokens => "tokens",
+            HeatMetric::Messages => "messages",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            HeatMetric::Tokens => HeatMetric::Messages,
+            HeatMetric::Messages => HeatMetric::Tokens,
+        }
+    }
+}
+
+/// `metric` summed per day across `sessions`, days as `zone` has them.
+/// Tokens come from every reply in the transcripts. Messages not loaded
+/// yet have no timestamps of their own, so a session's unloaded ones are
+/// spread over the days of its replies before the loaded tail.
+pub fn daily_totals(
+    sessions: &HashMap<String, Session>,
+    zone: &DisplayZone,
+    metric: HeatMetric,
+) -> HashMap<NaiveDate, u64> {
+    let day = |time| zone.convert(time).date_naive();
+    let mut totals: HashMap<NaiveDate, f64> = HashMap::new();
+    for session in sessions.values() {
+        match metric {
+            HeatMetric::Tokens => {
+                for point in &session.usage {
+                    *totals.entry(day(point.timestamp)).or_default() += point.tokens() as f64;
+                }
+            }
+            HeatMetric::Messages => {
+                for message in &session.messages {
+                    *totals.entry(day(message.timestamp)).or_default() += 1.0;
+                }
+                let loaded_from = session.messages.first().map(|m| m.timestamp);
+                let earlier: Vec<_> = session
+                    .usage
+                    .iter()
+                    .filter(|p| loaded_from.is_none_or(|t| p.timestamp < t))
+                    .collect();
+                if session.unloaded_messages == 0 || earlier.is_empty() {
+                    continue;
+                }
+                let share = session.unloaded_messages as f64 / earlier.len() as f64;
+                for point in earlier {
+                    *totals.entry(day(point.timestamp)).or_default() += share;
+                }
+            }
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(day, total)| (day, total.round() as u64))
+        .collect()
+}
+
+/// Shade 0 (nothing) to 4 (the busiest day's quarter) for `value`
+pub fn level(value: u64, max: u64) -> usize {
+    if value == 0 || max == 0 {
+        return 0;
+    }
+    (value * 4).div_ceil(max).clamp(1, 4) as usize
+}
@@ -0,0 +1,129 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::message::{MessageType, SessionMessage};
+use crate::session::Session;
+
+/// A command run for each new message (`[[hooks]]` in config.toml)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HookConfig {
+    /// Shell command; gets the message as JSON on stdin
+    pub on_message: String,
+    /// Project names to run for (a trailing `*` matches a prefix); empty
+    /// means all
+    #[serde(default)]
+    pub projects: Vec<String>,
+    /// Message types to run for; empty means all but `progress`
+    #[serde(default)]
+    pub types: Vec<MessageType>,
+}
+
+impl HookConfig {
+    fn matches(&self, project: &str, message: &SessionMessage) -> bool {
+        let project_ok = self.projects.is_empty()
+            || self.projects.iter().any(|p| match p.strip_suffix('*') {
+                Some(prefix) => project.starts_with(prefix),
+                None => project == p,
+            });
+        let type_ok = if self.types.is_empty() {
+            message.msg_type != MessageType::Progress
+        } else {
+            self.types.contains(&message.msg_type)
+        };
+        project_ok && type_ok
+    }
+}
+
+/// Hook runs waiting for the worker; past this, new ones are dropped
+/// rather than piling up behind a hook slower than the messages
+const QUEUE_LEN: usize = 256;
+
+/// A hook command and the JSON it gets on stdin
+type Job = (String, String);
+
+/// Runs the configured hooks for messages that arrive while claudy is open
+pub struct Hooks {
+    pub config: Vec<HookConfig>,
+    /// Messages older than this are history and never trigger hooks
+    started: DateTime<Utc>,
+    /// Queue of the worker thread that runs the hooks one at a time; None
+    /// without hooks. Dropping it lets the worker finish what is queued
+    /// and exit.
+    tx: Option<mpsc::SyncSender<Job>>,
+}
+
+impl Hooks {
+    pub fn new(config: Vec<HookConfig>) -> Self {
+        let tx = (!config.is_empty()).then(|| {
+            let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+            std::thread::spawn(move || run_worker(&rx));
+            tx
+        });
+        Self {
+            config,
+            started: Utc::now(),
+            tx,
+        }
+    }
+
+    /// Queue every matching hook for `messages`, in order, for the worker
+    /// thread, so a slow script never holds up the UI
+    pub fn run(&self, session: &Session, messages: &[SessionMessage]) {
+        let Some(ref tx) = self.tx else {
+            return;
+        };
+        let project = session.project_name();
+        for message in messages.iter().filter(|m| m.timestamp >= self.started) {
+            let payload = json!({
+                "session_id": session.id,
+                "project": project,
+                "cwd": session.cwd,
+                "git_branch": session.git_branch,
+                "uuid": message.uuid,
+                "type": message.msg_type.as_str(),
+                "timestamp": message.timestamp.to_rfc3339(),
+                "content": message.content,
+            })
+            .to_string();
+            for hook in self.config.iter().filter(|h| h.matches(project, message)) {
+                let job = (hook.on_message.clone(), payload.clone());
+                if let Err(mpsc::TrySendError::Full(_)) = tx.try_send(job) {
+                    tracing::warn!(command = %hook.on_message, "hook queue full; dropping run");
+                }
+            }
+        }
+    }
+}
+
+/// Run queued hooks in arrival order until the `Hooks` is dropped
+fn run_worker(rx: &mpsc::Receiver<Job>) {
+    for (command, payload) in rx {
+        if let Err(e) = run_hook(&command, &payload) {
+            tracing::warn!(command = %command, error = %e, "hook failed");
+        }
+    }
+}
+
+/// Run `command` through the shell with `payload` on stdin and wait for it
+fn run_hook(command: &str, payload: &str) -> std::io::Result<()> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores its input closes the pipe early; that's fine
+        let _ = stdin.write_all(payload.as_bytes());
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        tracing::debug!(command = %command, %status, "hook exited unsuccessfully");
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{Result, bail};
+
+/// POST a JSON body with `curl` and wait for the response; blocking, so
+/// call it off the UI thread. The URL, headers and body go to curl as a
+/// config on stdin: webhook URLs and auth headers are secrets, and the
+/// command line is readable by every local user.
+pub fn post_json<'a>(
+    url: &str,
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+    body: &str,
+) -> Result<()> {
+    let config = curl_config(url, headers, body);
+    let mut child = Command::new("curl")
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "{}: {}",
+            origin(url),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// `post_json` on a background thread, logging failures
+pub fn post_json_detached(url: String, headers: Vec<(String, String)>, body: String) {
+    std::thread::spawn(move || {
+        let headers = headers.iter().map(|(n, v)| (n.as_str(), v.as_str()));
+        if let Err(e) = post_json(&url, headers, &body) {
+            tracing::warn!(error = format!("{:#}", e), "notification post failed");
+        }
+    });
+}
+
+/// curl config file for the request, one option per line
+fn curl_config<'a>(
+    url: &str,
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+    body: &str,
+) -> String {
+    let mut config = String::from("fail\nsilent\nshow-error\nmax-time = 10\n");
+    config.push_str(&format!("url = {}\n", quote(url)));
+    config.push_str("header = \"Content-Type: application/json\"\n");
+    for (name, value) in headers {
+        config.push_str(&format!(
+            "header = {}\n",
+            quote(&format!("{}: {}", name, value))
+        ));
+    }
+    // Unlike `data-binary`, never reads a file for a leading '@'
+    config.push_str(&format!("data-raw = {}\n", quote(body)));
+    config
+}
+
+/// `value` as a double-quoted curl config string
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Scheme and host of `url`, for errors: a webhook's path is its secret
+fn origin(url: &str) -> &str {
+    let start = url.find("://").map_or(0, |i| i + 3);
+    match url[start..].find('/') {
+        Some(end) => &url[..start + end],
+        None => url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_escapes_config_syntax() {
+        assert_eq!(quote(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+        assert_eq!(quote("x\ny"), r#""x\ny""#);
+    }
+
+    #[test]
+    fn config_carries_url_headers_and_body() {
+        let config = curl_config(
+            "https://hooks.example.com/T0/B0/secret",
+            [("Authorization", "Bearer tk")],
+            r#"{"text":"@here"}"#,
+        );
+        assert!(config.contains("url = \"https://hooks.example.com/T0/B0/secret\"\n"));
+        assert!(config.contains("header = \"Authorization: Bearer tk\"\n"));
+        assert!(config.contains(r#"data-raw = "{\"text\":\"@here\"}""#));
+    }
+
+    #[test]
+    fn origin_drops_the_webhook_path() {
+        assert_eq!(
+            origin("https://hooks.slack.com/services/T0/B0/secret"),
+            "https://hooks.slack.com"
+        );
+        assert_eq!(origin("http://localhost:4318"), "http://localhost:4318");
+    }
+}
@@ -0,0 +1,77 @@
+use std::io::Write;
+
+use anyhow::Result;
+use crossterm::{cursor::MoveTo, queue};
+use ratatui::{buffer::Buffer, layout::Rect, style::Style};
+
+use crate::refs::{self, Link};
+use crate::snapshot::{row_cells, sgr};
+
+/// A link found on screen: where it starts, what it opens, and the cells
+/// it covers so they can be written again inside the OSC 8 sequence
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hyperlink {
+    x: u16,
+    y: u16,
+    target: String,
+    cells: Vec<(String, Style)>,
+}
+
+/// URLs and file references drawn in `area` of `buffer`, row by row (a
+/// link wrapped onto the next row isn't found). Relative file paths are
+/// resolved against `cwd`.
+pub fn find(buffer: &Buffer, area: Rect, cwd: Option<&str>) -> Vec<Hyperlink> {
+    let mut found = Vec::new();
+    for y in area.top()..area.bottom() {
+        // The row's text, and for each of its bytes the cell it came from
+        let mut text = String::new();
+        let mut cells = Vec::new();
+        let mut cell_of_byte = Vec::new();
+        for (x, symbol, style) in row_cells(buffer, area, y) {
+            cell_of_byte.extend(std::iter::repeat_n(cells.len(), symbol.len()));
+            cells.push((x, symbol.to_string(), style));
+            text.push_str(symbol);
+        }
+        for (range, link) in refs::links(&text) {
+            let target = match link {
+                Link::Url(url) => url,
+                Link::File(file_ref) => match file_ref.resolve(cwd).canonicalize() {
+                    Ok(path) => format!("file://{}", path.display()),
+                    // Only files that exist; a link to nowhere is noise
+                    Err(_) => continue,
+                },
+            };
+            let first = cell_of_byte[range.start];
+            let last = cell_of_byte[range.end - 1];
+            found.push(Hyperlink {
+                x: cells[first].0,
+                y,
+                target,
+                cells: cells[first..=last]
+                    .iter()
+                    .map(|(_, symbol, style)| (symbol.clone(), *style))
+                    .collect(),
+            });
+        }
+    }
+    found
+}
+
+/// Write `links` over what was just drawn, as OSC 8 hyperlinks around the
+/// same cells. The escapes can't go into the buffer itself: ratatui would
+/// count them as visible characters and misplace the rest of the row.
+pub fn write(links: &[Hyperlink]) -> Result<()> {
+    let mut out = std::io::stdout();
+    for link in links {
+        queue!(out, MoveTo(link.x, link.y))?;
+        // Control characters would end the sequence early
+        let target: String = link.target.chars().filter(|c| !c.is_control()).collect();
+        write!(out, "\x1b]8;;{}\x07", target)?;
+        for (symbol, style) in &link.cells {
+            write!(out, "{}{}", sgr(*style), symbol)?;
+        }
+        write!(out, "\x1b]8;;\x07\x1b[0m")?;
+    }
+    out.flush()?;
+    Ok(())
+}
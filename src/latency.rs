@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::message::{MessageType, SessionMessage};
+use crate::report::Breakdown;
+use crate::session::Session;
+
+/// How long Claude took to answer one prompt
+#[derive(Debug, Clone, Copy)]
+pub struct TurnLatency {
+    /// Index of the prompt
+    pub prompt: usize,
+    /// Index of the last assistant message before the next prompt
+    pub last_reply: usize,
+    pub elapsed: TimeDelta,
+    /// `elapsed` is the duration Claude Code recorded for the turn, not
+    /// the time from the prompt to the last reply
+    pub recorded: bool,
+}
+
+/// Latency of every answered turn in `messages`. The last turn counts only
+/// if `last_complete` or its duration was recorded, as Claude may still be
+/// working on it.
+pub fn turn_latencies(messages: &[SessionMessage], last_complete: bool) -> Vec<TurnLatency> {
+    let answered = |t: &TurnLatency| t.last_reply > t.prompt;
+    let mut latencies = Vec::new();
+    let mut current: Option<TurnLatency> = None;
+    for (idx, message) in messages.iter().enumerate() {
+        if message.is_prompt() {
+            latencies.extend(current.take().filter(answered));
+            current = Some(TurnLatency {
+                prompt: idx,
+                last_reply: idx,
+                elapsed: TimeDelta::zero(),
+                recorded: false,
+            });
+            continue;
+        }
+        let Some(ref mut turn) = current else {
+            continue;
+        };
+        if let Some(duration) = message.duration {
+            turn.elapsed = duration;
+            turn.recorded = true;
+        } else if matches!(
+            message.msg_type,
+            MessageType::Assistant | MessageType::ToolUse
+        ) {
+            turn.last_reply = idx;
+            if !turn.recorded {
+                turn.elapsed = message.timestamp - messages[turn.prompt].timestamp;
+            }
+        }
+    }
+    latencies.extend(current.filter(|t| answered(t) && (last_complete || t.recorded)));
+    latencies
+}
+
+/// Distribution of turn latencies
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyStats {
+    pub turns: usize,
+    pub median: TimeDelta,
+    pub p90: TimeDelta,
+    pub max: TimeDelta,
+}
+
+impl LatencyStats {
+    pub fn new(elapsed: impl IntoIterator<Item = TimeDelta>) -> Option<Self> {
+        let mut elapsed: Vec<TimeDelta> = elapsed.into_iter().collect();
+        elapsed.sort_unstable();
+        let max = *elapsed.last()?;
+        // Nearest-rank percentile
+        let percentile = |p: usize| elapsed[(elapsed.len() * p).div_ceil(100).max(1) - 1];
+        Some(Self {
+            turns: elapsed.len(),
+            median: percentile(50),
+            p90: percentile(90),
+            max,
+        })
+    }
+}
+
+/// Duration with seconds, e.g. `45s`, `2m14s` or `1h03m`
+pub fn format_latency(d: TimeDelta) -> String {
+    let secs = d.num_seconds().max(0);
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Time span of the response time chart; Tab cycles through them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChartWindow {
+    Day,
+    Week,
+    Month,
+}
+
+impl ChartWindow {
+    pub fn label(self) -> &'static str {
+        match self {
+            ChartWindow::Day => "24h",
+            ChartWindow::Week => "7 days",
+            ChartWindow::Month => "30 days",
+        }
+    }
+
+    pub fn span(self) -> TimeDelta {
+        match self {
+            ChartWindow::Day => TimeDelta::days(1),
+            ChartWindow::Week => TimeDelta::days(7),
+            ChartWindow::Month => TimeDelta::days(30),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ChartWindow::Day => ChartWindow::Week,
+            ChartWindow::Week => ChartWindow::Month,
+            ChartWindow::Month => ChartWindow::Day,
+        }
+    }
+}
+
+/// One answered turn, placed in time for the response time chart
+#[derive(Debug, Clone)]
+pub struct TurnSample {
+    /// When the prompt was sent
+    pub at: DateTime<Utc>,
+    pub elapsed: TimeDelta,
+    /// Project, model, or "all" depending on the breakdown
+    pub group: String,
+}
+
+/// Answered turns of the loaded messages of all sessions with prompts sent
+/// since `since`, grouped by `breakdown`. A turn's model is the one of the
+/// last reply with usage before the turn ended.
+pub fn samples(
+    sessions: &HashMap<String, Session>,
+    since: DateTime<Utc>,
+    breakdown: Breakdown,
+) -> Vec<TurnSample> {
+    let mut samples = Vec::new();
+    for session in sessions.values() {
+        if session.last_activity < since {
+            continue;
+        }
+        let last_complete = !session.is_active() || session.awaiting_user();
+        for turn in turn_latencies(&session.messages, last_complete) {
+            let at = session.messages[turn.prompt].timestamp;
+            if at < since {
+                continue;
+            }
+            let group = match breakdown {
+                Breakdown::Total => "all".to_string(),
+                Breakdown::Project => session.project_name().to_string(),
+                Breakdown::Model => {
+                    let end = session.messages[turn.last_reply].timestamp;
+                    let replies = session.usage.partition_point(|p| p.timestamp <= end);
+                    session.usage[..replies]
+                        .iter()
+                        .rev()
+                        .find_map(|p| p.model.as_deref())
+                        .unwrap_or("unknown")
+                        .to_string()
+                }
+            };
+            samples.push(TurnSample {
+                at,
+                elapsed: turn.elapsed,
+                group,
+            });
+        }
+    }
+    samples.sort_by_key(|s| s.at);
+    samples
+}
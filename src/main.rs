@@ -1,13 +1,55 @@
+mod alerts;
 mod app;
+mod background;
+mod classes;
+mod clipboard;
+mod compare;
+mod config;
+mod context;
+mod filter;
+mod git;
+mod health;
+mod heatmap;
+mod hooks;
+mod http;
+mod hyperlink;
+mod latency;
 mod message;
+mod opener;
+mod otlp;
+mod pricing;
+mod redact;
+mod refs;
+mod render;
+mod replay;
+mod report;
+mod script;
 mod session;
+mod setup;
+mod snapshot;
+mod state;
+mod statsd;
+mod terminal;
+mod timezone;
 mod ui;
+mod usage;
 mod watcher;
 
-use std::path::PathBuf;
+use std::fs::OpenOptions;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 
+use anyhow::Context;
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use tracing::Level;
+
+use crate::background::Background;
+use crate::report::{Breakdown, DeltaSides, Period, StatusFormat};
+use crate::timezone::DisplayZone;
+use crate::watcher::WatcherBackend;
 
 #[derive(Parser)]
 #[command(name = "claudy", about = "Claude Code Session Monitor TUI")]
@@ -15,26 +57,228 @@ struct Cli {
     /// Path to Claude projects directory
     #[arg(short, long)]
     path: Option<PathBuf>,
+
+    /// How to detect session file changes [default: native, or from config]
+    #[arg(long, value_enum)]
+    watcher: Option<WatcherBackend>,
+
+    /// Scan for file changes every SECS seconds instead of relying on
+    /// filesystem events (for NFS and other network mounts); implies --watcher poll
+    #[arg(long, value_name = "SECS")]
+    poll_interval: Option<u64>,
+
+    /// Watch the workspace of this name from the config instead of --path
+    #[arg(short, long)]
+    workspace: Option<String>,
+
+    /// Draw without colors (as does setting NO_COLOR)
+    #[arg(long)]
+    no_color: bool,
+
+    /// Describe state in words rather than glyphs and colors, for screen
+    /// readers
+    #[arg(long)]
+    screen_reader: bool,
+
+    /// Draw glyphs, borders and bars in plain ASCII
+    #[arg(long)]
+    ascii: bool,
+
+    /// Write a debug log to this file (the TUI owns the terminal)
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Log at debug level; logs to the default log file unless --log-file is given
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(flatten)]
+    Report(ReportCommand),
+    /// Show or refresh the model prices used for cost estimates
+    Pricing {
+        #[command(subcommand)]
+        action: PricingAction,
+    },
+}
+
+/// Subcommands that read the projects directory
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// List the largest sessions by file size and by token count
+    Du {
+        /// Number of sessions to list per table
+        #[arg(short = 'n', long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Token usage and estimated cost per calendar month or ISO week
+    Stats {
+        /// Bucket by calendar month (the default)
+        #[arg(long, conflicts_with = "weekly")]
+        monthly: bool,
+        /// Bucket by ISO week
+        #[arg(long)]
+        weekly: bool,
+        /// Split each period by model or project
+        #[arg(long, value_enum, default_value_t = Breakdown::Total)]
+        by: Breakdown,
+    },
+    /// Usage change between the last two weeks or months, or between two
+    /// tagged sets of sessions
+    Delta {
+        /// Compare this calendar month with the last
+        #[arg(long, conflicts_with_all = ["weekly", "tags"])]
+        monthly: bool,
+        /// Compare this ISO week with the last (the default)
+        #[arg(long, conflicts_with = "tags")]
+        weekly: bool,
+        /// Compare sessions tagged A with sessions tagged B instead
+        #[arg(long, num_args = 2, value_names = ["A", "B"])]
+        tags: Option<Vec<String>>,
+        /// Split the delta by project or model
+        #[arg(long, value_enum, default_value_t = Breakdown::Project)]
+        by: Breakdown,
+    },
+    /// One-line session summary for tmux status lines and shell prompts
+    Status {
+        #[arg(long, value_enum, default_value_t = StatusFormat::Plain)]
+        format: StatusFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum PricingAction {
+    /// Print the effective price table
+    Show,
+    /// Fetch the maintained LiteLLM price list and cache it for offline use
+    Update {
+        /// Price list to fetch (LiteLLM's JSON format)
+        #[arg(long, default_value = pricing::LITELLM_PRICES_URL)]
+        url: String,
+    },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    init_logging(cli.log_file.as_deref(), cli.verbose)?;
 
-    let base_path = cli.path.unwrap_or_else(|| {
-        let home = dirs::home_dir().expect("Could not determine home directory");
-        home.join(".claude").join("projects")
-    });
+    let command = match cli.command {
+        // Pricing commands do not need the projects directory
+        Some(Command::Pricing { action }) => return run_pricing(action),
+        Some(Command::Report(command)) => Some(command),
+        None => None,
+    };
+
+    let mut config = config::Config::load()?;
+    let chosen = cli.path.is_some();
+    let mut base_path = cli
+        .path
+        .or_else(|| config.projects_dir.clone())
+        .unwrap_or_else(|| {
+            let home = dirs::home_dir().expect("Could not determine home directory");
+            home.join(".claude").join("projects")
+        });
 
     if !base_path.exists() {
-        eprintln!(
-            "Claude projects directory not found: {}",
-            base_path.display()
-        );
-        eprintln!("Make sure Claude Code is installed and has been used at least once.");
-        std::process::exit(1);
+        // Ask, unless the path was given on the command line, a subcommand
+        // is running (its output may be piped or parsed), or nobody is
+        // there to answer
+        let interactive = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        let picked = if !chosen && command.is_none() && interactive {
+            setup::run(&base_path)?
+        } else {
+            None
+        };
+        match picked {
+            Some(path) => base_path = path,
+            None => {
+                eprintln!(
+                    "Claude projects directory not found: {}",
+                    base_path.display()
+                );
+                eprintln!("Make sure Claude Code is installed and has been used at least once.");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let overrides = config::RuntimeOverrides {
+        // https://no-color.org: set and not empty
+        no_color: cli.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()),
+        screen_reader: cli.screen_reader,
+        ascii: cli.ascii,
+        truecolor: terminal::supports_truecolor(),
+        // Only the TUI draws on the background
+        background: (command.is_none() && config.background == Background::Auto)
+            .then(background::detect)
+            .flatten(),
+    };
+    overrides.apply(&mut config);
+    let prices = pricing::PriceTable::load()?;
+    if let Some(command) = command {
+        let (zone, zone_warning) = DisplayZone::parse_or_local(&config.timezone);
+        if let Some(warning) = zone_warning {
+            eprintln!("{}", warning);
+        }
+        return match command {
+            ReportCommand::Du { top } => report::print_du(
+                &base_path,
+                top,
+                &config.project_aliases,
+                &config.ignore_projects,
+            ),
+            ReportCommand::Stats {
+                monthly: _,
+                weekly,
+                by,
+            } => {
+                let period = if weekly { Period::Week } else { Period::Month };
+                report::print_stats(&base_path, &prices, &config, &zone, period, by)
+            }
+            ReportCommand::Delta {
+                monthly,
+                weekly: _,
+                tags,
+                by,
+            } => {
+                let sides = match tags.as_deref() {
+                    Some([a, b]) => DeltaSides::Tags(a.clone(), b.clone()),
+                    _ if monthly => DeltaSides::Periods(Period::Month),
+                    _ => DeltaSides::Periods(Period::Week),
+                };
+                report::print_delta(&base_path, &prices, &config, &zone, &sides, by)
+            }
+            ReportCommand::Status { format } => {
+                report::print_status(&base_path, &prices, format, &config.ignore_projects, &zone)
+            }
+        };
     }
 
-    let mut app = app::App::new(base_path)?;
+    let backend = cli.watcher.unwrap_or(if cli.poll_interval.is_some() {
+        WatcherBackend::Poll
+    } else {
+        config.watcher
+    });
+    let poll_interval = Duration::from_secs(
+        cli.poll_interval
+            .unwrap_or(config.poll_interval_secs)
+            .max(1),
+    );
+    let mut app = app::App::new(
+        base_path,
+        cli.workspace,
+        config,
+        overrides,
+        state::ClaudyState::load(),
+        prices,
+        backend,
+        poll_interval,
+    )?;
 
     let mut terminal = ratatui::init();
     let result = app.run_event_loop(&mut terminal);
@@ -42,3 +286,50 @@ fn main() -> Result<()> {
 
     result
 }
+
+/// `claudy pricing`: print the price table or refresh its cache
+fn run_pricing(action: PricingAction) -> Result<()> {
+    match action {
+        PricingAction::Show => report::print_prices(&pricing::PriceTable::load()?),
+        PricingAction::Update { url } => {
+            let (path, count) = pricing::update(&url)?;
+            println!("Cached prices for {} models in {}", count, path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Default log location used by `--verbose` without `--log-file`
+fn default_log_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("claudy").join("claudy.log"))
+}
+
+/// Install a file-backed tracing subscriber; logging is off unless asked for
+fn init_logging(log_file: Option<&Path>, verbose: bool) -> Result<()> {
+    let path = match log_file {
+        Some(path) => path.to_path_buf(),
+        None if verbose => match default_log_path() {
+            Some(path) => path,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("cannot open log file {}", path.display()))?;
+
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_max_level(if verbose { Level::DEBUG } else { Level::INFO })
+        .init();
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "claudy starting");
+    Ok(())
+}
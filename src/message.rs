@@ -1,7 +1,12 @@
-use chrono::{DateTime, Utc};
+use std::ops::Range;
+
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MessageType {
     User,
     Assistant,
@@ -10,21 +15,157 @@ pub enum MessageType {
     Other,
 }
 
+impl MessageType {
+    /// Name used in config files and hook payloads
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageType::User => "user",
+            MessageType::Assistant => "assistant",
+            MessageType::Progress => "progress",
+            MessageType::ToolUse => "tool_use",
+            MessageType::Other => "other",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SessionMessage {
+    /// Entry uuid from the transcript, when present
+    pub uuid: Option<String>,
     pub msg_type: MessageType,
     pub timestamp: DateTime<Utc>,
     pub content: String,
-    pub tokens_in: Option<u64>,
-    pub tokens_out: Option<u64>,
+    /// Duration of the turn this entry closes, when Claude Code recorded
+    /// one (`"subtype": "turn_duration"` system entries)
+    pub duration: Option<TimeDelta>,
+    /// Why the reply ended, when that's anything but the usual end of turn
+    /// or tool call (e.g. `max_tokens`)
+    pub stop_reason: Option<String>,
+    /// API error this entry reports, e.g. `overloaded_529`
+    pub error: Option<String>,
+}
+
+impl SessionMessage {
+    /// Stable identifier for persisting per-message state such as stars
+    pub fn key(&self) -> String {
+        self.uuid
+            .clone()
+            .unwrap_or_else(|| self.timestamp.to_rfc3339())
+    }
+
+    /// A prompt from the user, as opposed to tool results sent back
+    pub fn is_prompt(&self) -> bool {
+        self.msg_type == MessageType::User && !self.content.starts_with("[tool result]")
+    }
+
+    /// Hash on the `[commit: …]` line of a tool result; only trustworthy
+    /// as the result of a `git commit` call (see `created_commit_at`)
+    fn commit_hash(&self) -> Option<&str> {
+        if self.msg_type != MessageType::User || !self.content.starts_with("[tool result]") {
+            return None;
+        }
+        self.content.lines().find_map(commit_line)
+    }
+
+    /// Whether a `ToolUse` message runs `git commit` in a shell
+    fn runs_git_commit(&self) -> bool {
+        self.msg_type == MessageType::ToolUse
+            && self
+                .content
+                .lines()
+                .filter_map(|line| line.strip_prefix("$ "))
+                .any(is_git_commit)
+    }
+
+    /// Names of the tools a `ToolUse` message calls
+    pub fn tool_names(&self) -> impl Iterator<Item = &str> {
+        self.content
+            .lines()
+            .filter_map(|line| line.strip_prefix("[tool: ")?.strip_suffix(']'))
+    }
+}
+
+/// Hash of the commit `messages[idx]` reports creating: a tool result
+/// answering a Bash call that ran `git commit`, naming a plain hex hash.
+/// Anything else that looks like the marker, such as pasted text, is not
+/// a commit and never reaches `git show`.
+pub fn created_commit_at(messages: &[SessionMessage], idx: usize) -> Option<&str> {
+    let hash = messages.get(idx)?.commit_hash()?;
+    messages[..idx]
+        .iter()
+        .rev()
+        .find(|m| matches!(m.msg_type, MessageType::ToolUse | MessageType::Assistant))
+        .filter(|m| m.runs_git_commit())
+        .map(|_| hash)
+}
+
+/// The hash of a `[commit: …]` content line
+pub fn commit_line(line: &str) -> Option<&str> {
+    let hash = line.strip_prefix("[commit: ")?.strip_suffix(']')?;
+    is_commit_hash(hash).then_some(hash)
+}
+
+/// Abbreviated or full lowercase hex object name, as git prints them
+pub fn is_commit_hash(text: &str) -> bool {
+    (7..=40).contains(&text.len()) && text.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Byte range of the first occurrence of `needle` in `text` at or after
+/// byte `from`, ignoring case: both sides are compared lowercased, char by
+/// char, so the chat search and its highlighting agree on every script
+pub fn find_ignore_case(text: &str, needle: &str, from: usize) -> Option<Range<usize>> {
+    if needle.is_empty() {
+        return None;
+    }
+    text[from..].char_indices().find_map(|(start, _)| {
+        let start = from + start;
+        let mut want = needle.chars().flat_map(char::to_lowercase).peekable();
+        for (i, c) in text[start..].char_indices() {
+            if !c.to_lowercase().all(|l| want.next() == Some(l)) {
+                return None;
+            }
+            if want.peek().is_none() {
+                return Some(start..start + i + c.len_utf8());
+            }
+        }
+        None
+    })
+}
+
+/// Whether a shell command line runs `git commit` in one of its commands,
+/// allowing variable assignments and git's global options before it
+fn is_git_commit(command: &str) -> bool {
+    command.split(['\n', ';', '&', '|', '(', ')']).any(|part| {
+        let mut words = part
+            .split_whitespace()
+            .skip_while(|w| w.contains('=') && !w.starts_with('-'));
+        if words.next() != Some("git") {
+            return false;
+        }
+        while let Some(word) = words.next() {
+            match word {
+                // Global options taking a separate value
+                "-C" | "-c" | "--git-dir" | "--work-tree" | "--namespace" => {
+                    words.next();
+                }
+                w if w.starts_with('-') => {}
+                w => return w == "commit",
+            }
+        }
+        false
+    })
 }
 
 #[derive(Debug, Deserialize)]
-struct RawMessage {
+struct RawMessage<C = RawMessageContent> {
     #[serde(rename = "type")]
     pub msg_type: String,
+    pub uuid: Option<String>,
     pub timestamp: Option<String>,
-    pub message: Option<RawMessageContent>,
+    /// API request the entry's reply came from
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
+    pub message: Option<C>,
     #[serde(rename = "sessionId")]
     pub session_id: Option<String>,
     #[serde(rename = "gitBranch")]
@@ -36,12 +177,76 @@ struct RawMessage {
     /// Present on "type": "custom-title" lines
     #[serde(rename = "customTitle")]
     pub custom_title: Option<String>,
+    /// Kind of a "type": "system" line
+    pub subtype: Option<String>,
+    /// Present on "subtype": "turn_duration" lines
+    #[serde(rename = "durationMs")]
+    pub duration_ms: Option<u64>,
+    /// Set on the assistant entries Claude Code writes for failed requests
+    #[serde(rename = "isApiErrorMessage")]
+    pub is_api_error: Option<bool>,
+    /// Error payload of a failed request, when recorded
+    pub error: Option<Value>,
 }
 
 #[derive(Debug, Deserialize)]
 struct RawMessageContent {
-    pub content: Option<serde_json::Value>,
+    pub content: Option<Value>,
+    pub stop_reason: Option<String>,
+}
+
+/// Message body without its content, for scanning lines cheaply
+#[derive(Debug, Deserialize)]
+struct RawUsageOnly {
+    /// API message id, repeated on every content block line of a reply
+    pub id: Option<String>,
+    pub model: Option<String>,
     pub usage: Option<RawUsage>,
+    pub stop_reason: Option<String>,
+}
+
+/// `stop_reason` unless it's one every turn ends with
+fn unusual_stop(stop_reason: Option<String>) -> Option<String> {
+    stop_reason.filter(|r| !matches!(r.as_str(), "end_turn" | "tool_use" | "stop_sequence"))
+}
+
+/// Short name of an API error such as `overloaded_529`, from the error
+/// payload when there is one, else from the "API Error: 529 {...}" text
+/// Claude Code shows in its place
+fn api_error_label(error: Option<&Value>, text: &str) -> String {
+    // The payload is either the error itself or wraps it in "error"
+    let inner = error.map(|e| e.get("error").unwrap_or(e));
+    let kind = inner
+        .and_then(|e| e.get("type"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| {
+            text.split(r#""type":""#)
+                .skip(1)
+                .filter_map(|rest| rest.split('"').next())
+                .find(|t| t.ends_with("_error"))
+                .map(str::to_string)
+        });
+    let status = error
+        .and_then(|e| e.get("status"))
+        .and_then(Value::as_u64)
+        .or_else(|| {
+            let rest = text.split_once("API Error: ")?.1;
+            rest.split(|c: char| !c.is_ascii_digit())
+                .next()?
+                .parse()
+                .ok()
+        });
+    let kind = kind.map(|k| k.trim_end_matches("_error").to_string());
+    match (kind, status) {
+        (Some(k), Some(s)) => format!("{}_{}", k, s),
+        (Some(k), None) => k,
+        (None, Some(s)) => format!("http_{}", s),
+        (None, None) => error
+            .and_then(Value::as_str)
+            .unwrap_or("api_error")
+            .to_string(),
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -52,10 +257,188 @@ struct RawUsage {
     pub cache_creation_input_tokens: Option<u64>,
 }
 
+/// Token counts reported by one assistant reply
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TokenUsage {
+    pub input: u64,
+    pub cache_creation: u64,
+    pub cache_read: u64,
+    pub output: u64,
+}
+
+impl TokenUsage {
+    /// Input tokens including cache reads and writes
+    pub fn tokens_in(&self) -> u64 {
+        self.input + self.cache_creation + self.cache_read
+    }
+
+    pub fn total(&self) -> u64 {
+        self.tokens_in() + self.output
+    }
+}
+
+impl From<&RawUsage> for TokenUsage {
+    fn from(raw: &RawUsage) -> Self {
+        Self {
+            input: raw.input_tokens.unwrap_or(0),
+            cache_creation: raw.cache_creation_input_tokens.unwrap_or(0),
+            cache_read: raw.cache_read_input_tokens.unwrap_or(0),
+            output: raw.output_tokens.unwrap_or(0),
+        }
+    }
+}
+
+impl<C> RawMessage<C> {
+    /// Session metadata carried by this line, if any
+    fn into_meta(self) -> Option<SessionMeta> {
+        // "type": "summary" lines have summary but no sessionId
+        if self.msg_type == "summary" {
+            return Some(SessionMeta {
+                git_branch: None,
+                cwd: None,
+                slug: None,
+                summary: self.summary,
+                custom_title: None,
+            });
+        }
+
+        // "type": "custom-title" lines carry the renamed title
+        if self.msg_type == "custom-title" {
+            return Some(SessionMeta {
+                git_branch: None,
+                cwd: None,
+                slug: None,
+                summary: None,
+                custom_title: self.custom_title,
+            });
+        }
+
+        // Regular messages need sessionId
+        self.session_id?;
+        Some(SessionMeta {
+            git_branch: self.git_branch,
+            cwd: self.cwd,
+            slug: self.slug,
+            summary: None,
+            custom_title: None,
+        })
+    }
+}
+
+/// A `message` body that can be picked out of a loosely shaped value
+trait LenientBody: Sized {
+    fn from_value_lenient(value: &Value) -> Self;
+}
+
+impl<C: LenientBody> RawMessage<C> {
+    /// Best-effort extraction of whichever fields have the expected shape,
+    /// for lines from newer schema versions that strict parsing rejects
+    fn from_value_lenient(value: &Value) -> Option<Self> {
+        let obj = value.as_object()?;
+        let text = |key: &str| obj.get(key).and_then(Value::as_str).map(str::to_string);
+        Some(Self {
+            msg_type: text("type")?,
+            uuid: text("uuid"),
+            timestamp: text("timestamp"),
+            request_id: text("requestId"),
+            message: obj.get("message").map(C::from_value_lenient),
+            session_id: text("sessionId"),
+            git_branch: text("gitBranch"),
+            cwd: text("cwd"),
+            slug: text("slug"),
+            summary: text("summary"),
+            custom_title: text("customTitle"),
+            subtype: text("subtype"),
+            duration_ms: obj.get("durationMs").and_then(Value::as_u64),
+            is_api_error: obj.get("isApiErrorMessage").and_then(Value::as_bool),
+            error: obj.get("error").cloned(),
+        })
+    }
+}
+
+impl LenientBody for RawMessageContent {
+    fn from_value_lenient(value: &Value) -> Self {
+        match value {
+            // A bare string is taken as the content itself
+            Value::String(_) => Self {
+                content: Some(value.clone()),
+                stop_reason: None,
+            },
+            _ => Self {
+                content: value.get("content").cloned(),
+                stop_reason: value
+                    .get("stop_reason")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            },
+        }
+    }
+}
+
+impl LenientBody for RawUsageOnly {
+    fn from_value_lenient(value: &Value) -> Self {
+        Self {
+            id: value.get("id").and_then(Value::as_str).map(str::to_string),
+            model: value
+                .get("model")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            stop_reason: value
+                .get("stop_reason")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            usage: value.get("usage").map(|usage| {
+                let count = |key: &str| usage.get(key).and_then(Value::as_u64);
+                RawUsage {
+                    input_tokens: count("input_tokens"),
+                    output_tokens: count("output_tokens"),
+                    cache_read_input_tokens: count("cache_read_input_tokens"),
+                    cache_creation_input_tokens: count("cache_creation_input_tokens"),
+                }
+            }),
+        }
+    }
+}
+
+/// Deserialize a line strictly, falling back to lenient field extraction
+/// when the JSON is valid but doesn't match `RawMessage`
+fn parse_raw<C>(line: &str) -> Result<RawMessage<C>, serde_json::Error>
+where
+    C: DeserializeOwned + LenientBody,
+{
+    let err = match serde_json::from_str(line) {
+        Ok(raw) => return Ok(raw),
+        Err(e) => e,
+    };
+    if err.is_data()
+        && let Ok(value) = serde_json::from_str::<Value>(line)
+        && let Some(raw) = RawMessage::from_value_lenient(&value)
+    {
+        tracing::debug!(error = %err, "recovered line with lenient parser");
+        return Ok(raw);
+    }
+    Err(err)
+}
+
+/// `parse_raw` for a line already parsed into a `Value`
+fn raw_from_value<C>(value: &Value) -> Result<RawMessage<C>, serde_json::Error>
+where
+    C: DeserializeOwned + LenientBody,
+{
+    match RawMessage::deserialize(value) {
+        Ok(raw) => Ok(raw),
+        Err(err) => {
+            let raw = RawMessage::from_value_lenient(value).ok_or(err)?;
+            tracing::debug!("recovered line with lenient parser");
+            Ok(raw)
+        }
+    }
+}
+
 /// Extract displayable text content from a message content value
-fn extract_text_content(content: &serde_json::Value) -> String {
+fn extract_text_content(content: &Value) -> String {
     match content {
-        serde_json::Value::String(s) => {
+        Value::String(s) => {
             // Strip XML-like tags for cleaner display
             let s = s.trim();
             if s.starts_with('<') && s.contains('>') {
@@ -75,7 +458,7 @@ fn extract_text_content(content: &serde_json::Value) -> String {
                 s.to_string()
             }
         }
-        serde_json::Value::Array(arr) => {
+        Value::Array(arr) => {
             let mut parts = Vec::new();
             for item in arr {
                 if let Some(obj) = item.as_object() {
@@ -94,9 +477,21 @@ fn extract_text_content(content: &serde_json::Value) -> String {
                                 .and_then(|n| n.as_str())
                                 .unwrap_or("unknown");
                             parts.push(format!("[tool: {}]", name));
+                            // Shell commands are kept so rules can match them
+                            if let Some(command) = obj
+                                .get("input")
+                                .and_then(|i| i.get("command"))
+                                .and_then(|c| c.as_str())
+                            {
+                                parts.push(format!("$ {}", command.trim()));
+                            }
                         }
                         Some("tool_result") => {
                             parts.push("[tool result]".to_string());
+                            // Kept so the chat can link to the commit
+                            if let Some(hash) = obj.get("content").and_then(created_commit) {
+                                parts.push(format!("[commit: {}]", hash));
+                            }
                         }
                         _ => {}
                     }
@@ -108,8 +503,27 @@ fn extract_text_content(content: &serde_json::Value) -> String {
     }
 }
 
+/// Hash of the commit a `git commit` run reports in its output, from the
+/// `[branch 1a2b3c4] subject` line
+fn created_commit(output: &Value) -> Option<String> {
+    let text = match output {
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    text.lines().find_map(|line| {
+        let (head, _) = line.strip_prefix('[')?.split_once("] ")?;
+        let (_, hash) = head.rsplit_once(' ')?;
+        is_commit_hash(hash).then(|| hash.to_string())
+    })
+}
+
 /// Determine if content array contains tool_use blocks
-fn has_tool_use(content: &serde_json::Value) -> bool {
+fn has_tool_use(content: &Value) -> bool {
     if let Some(arr) = content.as_array() {
         arr.iter().any(|item| {
             item.as_object()
@@ -122,10 +536,24 @@ fn has_tool_use(content: &serde_json::Value) -> bool {
     }
 }
 
-/// Parse a single JSONL line into an optional SessionMessage
-pub fn parse_line(line: &str) -> Option<SessionMessage> {
-    let raw: RawMessage = serde_json::from_str(line).ok()?;
+/// Parse a single JSONL line; `Ok(None)` for valid lines that aren't shown
+pub fn parse_line(line: &str) -> Result<Option<SessionMessage>, serde_json::Error> {
+    parse_raw(line).map(message_from_raw)
+}
+
+/// Both what `scan_line` and what `parse_line` read from a line, parsing
+/// its JSON only once; for lines that are loaded as messages anyway
+pub fn parse_line_full(
+    line: &str,
+) -> Result<(LineScan, Option<SessionMessage>), serde_json::Error> {
+    let value: Value = serde_json::from_str(line)?;
+    let message = message_from_raw(raw_from_value(&value)?);
+    let error = message.as_ref().and_then(|m| m.error.clone());
+    let scan = scan_raw(raw_from_value(&value)?, || error);
+    Ok((scan, message))
+}
 
+fn message_from_raw(raw: RawMessage) -> Option<SessionMessage> {
     let msg_type_str = raw.msg_type.as_str();
 
     // Skip file-history-snapshot and queue-operation
@@ -141,7 +569,7 @@ pub fn parse_line(line: &str) -> Option<SessionMessage> {
         .map(|dt| dt.with_timezone(&Utc))
         .unwrap_or_else(Utc::now);
 
-    let (msg_type, content, tokens_in, tokens_out) = match msg_type_str {
+    let (msg_type, content) = match msg_type_str {
         "user" => {
             let content = raw
                 .message
@@ -149,7 +577,7 @@ pub fn parse_line(line: &str) -> Option<SessionMessage> {
                 .and_then(|m| m.content.as_ref())
                 .map(extract_text_content)
                 .unwrap_or_default();
-            (MessageType::User, content, None, None)
+            (MessageType::User, content)
         }
         "assistant" => {
             let msg = raw.message.as_ref();
@@ -165,15 +593,6 @@ pub fn parse_line(line: &str) -> Option<SessionMessage> {
                 })
                 .unwrap_or_default();
 
-            let tokens_in = msg.and_then(|m| {
-                m.usage.as_ref().map(|u| {
-                    u.input_tokens.unwrap_or(0)
-                        + u.cache_read_input_tokens.unwrap_or(0)
-                        + u.cache_creation_input_tokens.unwrap_or(0)
-                })
-            });
-            let tokens_out = msg.and_then(|m| m.usage.as_ref().and_then(|u| u.output_tokens));
-
             let actual_type = if msg
                 .and_then(|m| m.content.as_ref())
                 .map(has_tool_use)
@@ -184,15 +603,15 @@ pub fn parse_line(line: &str) -> Option<SessionMessage> {
                 MessageType::Assistant
             };
 
-            (actual_type, content, tokens_in, tokens_out)
+            (actual_type, content)
         }
         "progress" => {
             let content = "[progress]".to_string();
-            (MessageType::Progress, content, None, None)
+            (MessageType::Progress, content)
         }
         _ => {
             let content = format!("[{}]", msg_type_str);
-            (MessageType::Other, content, None, None)
+            (MessageType::Other, content)
         }
     };
 
@@ -201,12 +620,27 @@ pub fn parse_line(line: &str) -> Option<SessionMessage> {
         return None;
     }
 
+    let error = (raw.is_api_error == Some(true) || raw.error.is_some())
+        .then(|| api_error_label(raw.error.as_ref(), &content));
+    let stop_reason = match msg_type {
+        MessageType::Assistant | MessageType::ToolUse => {
+            unusual_stop(raw.message.and_then(|m| m.stop_reason))
+        }
+        _ => None,
+    };
+    let duration = raw
+        .duration_ms
+        .filter(|_| raw.subtype.as_deref() == Some("turn_duration"))
+        .and_then(|ms| TimeDelta::try_milliseconds(ms as i64));
+
     Some(SessionMessage {
+        uuid: raw.uuid,
         msg_type,
         timestamp,
         content,
-        tokens_in,
-        tokens_out,
+        duration,
+        stop_reason,
+        error,
     })
 }
 
@@ -218,40 +652,209 @@ pub struct SessionMeta {
     pub custom_title: Option<String>,
 }
 
-/// Extract metadata from a JSONL line. Works on both regular messages
-/// (with sessionId) and "type": "summary" lines.
-pub fn extract_meta(line: &str) -> Option<SessionMeta> {
-    let raw: RawMessage = serde_json::from_str(line).ok()?;
+/// What a transcript line contributes to session metadata and totals,
+/// without building its message
+pub struct LineScan {
+    pub meta: Option<SessionMeta>,
+    /// Tokens reported by an assistant reply
+    pub usage: Option<TokenUsage>,
+    /// Request and message id of the reply, which Claude Code repeats (with
+    /// its usage) on the line of each content block
+    pub reply_id: Option<String>,
+    pub model: Option<String>,
+    /// Only read for lines that report token usage, an error or a
+    /// compaction
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Whether the line will likely render as a message once parsed
+    pub is_message: bool,
+    /// Unusual `stop_reason` of an assistant reply
+    pub stop_reason: Option<String>,
+    /// API error reported by the line
+    pub error: Option<String>,
+    /// Whether the line marks where Claude Code compacted the conversation
+    pub compaction: bool,
+}
 
-    // "type": "summary" lines have summary but no sessionId
-    if raw.msg_type == "summary" {
-        return Some(SessionMeta {
-            git_branch: None,
-            cwd: None,
-            slug: None,
-            summary: raw.summary,
-            custom_title: None,
-        });
+/// Read a line's metadata and token usage while skipping over its content,
+/// which is far cheaper than `parse_line` for large tool results
+pub fn scan_line(line: &str) -> Result<LineScan, serde_json::Error> {
+    let raw = parse_raw(line)?;
+    // Error entries are rare and small, so naming the error from the
+    // content costs a full parse only for them
+    Ok(scan_raw(raw, || parse_line(line).ok().flatten()?.error))
+}
+
+/// `scan_line` for a parsed line; `error_text` names an API error the
+/// line flags without a payload
+fn scan_raw(
+    mut raw: RawMessage<RawUsageOnly>,
+    error_text: impl FnOnce() -> Option<String>,
+) -> LineScan {
+    let is_message = !matches!(
+        raw.msg_type.as_str(),
+        "file-history-snapshot" | "queue-operation"
+    );
+    let body = match raw.msg_type.as_str() {
+        "assistant" => raw.message.take(),
+        _ => None,
+    };
+    let usage = body
+        .as_ref()
+        .and_then(|b| b.usage.as_ref())
+        .map(TokenUsage::from);
+    let error = if raw.error.is_some() {
+        Some(api_error_label(raw.error.as_ref(), ""))
+    } else if raw.is_api_error == Some(true) {
+        error_text()
+    } else {
+        None
+    };
+    let compaction = raw.subtype.as_deref() == Some("compact_boundary");
+    let timestamp = raw
+        .timestamp
+        .as_deref()
+        .filter(|_| usage.is_some() || error.is_some() || compaction)
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|dt| dt.with_timezone(&Utc));
+    let reply_id = body
+        .as_ref()
+        .filter(|_| usage.is_some())
+        .and_then(|b| b.id.as_deref())
+        .map(|id| format!("{}:{}", raw.request_id.as_deref().unwrap_or(""), id));
+    let (model, stop_reason) = body.map_or((None, None), |b| (b.model, b.stop_reason));
+    LineScan {
+        meta: raw.into_meta(),
+        usage,
+        reply_id,
+        model,
+        timestamp,
+        is_message,
+        stop_reason: unusual_stop(stop_reason),
+        error,
+        compaction,
     }
+}
 
-    // "type": "custom-title" lines carry the renamed title
-    if raw.msg_type == "custom-title" {
-        return Some(SessionMeta {
-            git_branch: None,
-            cwd: None,
-            slug: None,
-            summary: None,
-            custom_title: raw.custom_title,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLY: &str = r#"{"type":"assistant","sessionId":"s","requestId":"req_1","timestamp":"2026-10-16T09:01:00Z","message":{"id":"msg_1","model":"claude-sonnet-4-5","content":[{"type":"text","text":"hi"}],"usage":{"input_tokens":10,"output_tokens":5}}}"#;
+
+    #[test]
+    fn search_folds_case_beyond_ascii() {
+        assert_eq!(find_ignore_case("Grüße aus KÖLN", "köln", 0), Some(12..17));
+        assert_eq!(find_ignore_case("ΣΟΦΊΑ σοφία", "σοφία", 0), Some(0..10));
+        assert_eq!(find_ignore_case("ΣΟΦΊΑ σοφία", "σοφία", 2), Some(11..21));
+        assert_eq!(find_ignore_case("abc", "", 0), None);
+        assert_eq!(find_ignore_case("abc", "abcd", 0), None);
+    }
+
+    #[test]
+    fn scan_names_the_reply_its_usage_belongs_to() {
+        let scan = scan_line(REPLY).unwrap();
+        assert_eq!(scan.reply_id.as_deref(), Some("req_1:msg_1"));
+        assert_eq!(scan.usage.map(|u| u.total()), Some(15));
+    }
+
+    #[test]
+    fn lines_without_usage_have_no_reply_id() {
+        let prompt = r#"{"type":"user","sessionId":"s","message":{"id":"x","content":"hello"}}"#;
+        assert!(scan_line(prompt).unwrap().reply_id.is_none());
+    }
+
+    #[test]
+    fn parsing_once_matches_scanning_and_parsing_separately() {
+        let (scan, message) = parse_line_full(REPLY).unwrap();
+        let separate = scan_line(REPLY).unwrap();
+        assert_eq!(scan.reply_id, separate.reply_id);
+        assert_eq!(scan.usage, separate.usage);
+        assert_eq!(scan.model, separate.model);
+        assert_eq!(scan.timestamp, separate.timestamp);
+        assert!(scan.meta.is_some());
+        let message = message.unwrap();
+        assert_eq!(message.content, parse_line(REPLY).unwrap().unwrap().content);
+        assert_eq!(message.msg_type, MessageType::Assistant);
+    }
+
+    #[test]
+    fn parsing_once_rejects_what_parse_line_rejects() {
+        assert!(parse_line_full("{not json").is_err());
+        assert!(parse_line_full("[1, 2]").is_err());
+    }
+
+    fn entry(msg_type: MessageType, content: &str) -> SessionMessage {
+        SessionMessage {
+            uuid: None,
+            msg_type,
+            timestamp: Utc::now(),
+            content: content.to_string(),
+            duration: None,
+            stop_reason: None,
+            error: None,
+        }
+    }
+
+    fn tool_result(output: &str) -> String {
+        let line = serde_json::json!({
+            "type": "user",
+            "message": { "content": [{ "type": "tool_result", "content": output }] },
         });
+        parse_line(&line.to_string()).unwrap().unwrap().content
     }
 
-    // Regular messages need sessionId
-    raw.session_id?;
-    Some(SessionMeta {
-        git_branch: raw.git_branch,
-        cwd: raw.cwd,
-        slug: raw.slug,
-        summary: None,
-        custom_title: None,
-    })
+    #[test]
+    fn commit_hash_comes_from_git_commit_output() {
+        let result = tool_result("[main 1a2b3c4] Fix the login check\n 1 file changed");
+        assert_eq!(result, "[tool result]\n[commit: 1a2b3c4]");
+        let messages = [
+            entry(
+                MessageType::ToolUse,
+                "[tool: Bash]\n$ git add -A && git commit -m 'fix'",
+            ),
+            entry(MessageType::User, &result),
+        ];
+        assert_eq!(created_commit_at(&messages, 1), Some("1a2b3c4"));
+    }
+
+    #[test]
+    fn commit_markers_elsewhere_are_ignored() {
+        let result = tool_result("[main 1a2b3c4] Fix the login check");
+        // Not after a git commit call
+        let messages = [
+            entry(MessageType::ToolUse, "[tool: Bash]\n$ cat notes.txt"),
+            entry(MessageType::User, &result),
+        ];
+        assert_eq!(created_commit_at(&messages, 1), None);
+        // Typed into a prompt
+        let messages = [
+            entry(MessageType::ToolUse, "[tool: Bash]\n$ git commit -m x"),
+            entry(MessageType::User, "look at\n[commit: 1a2b3c4]"),
+        ];
+        assert_eq!(created_commit_at(&messages, 1), None);
+    }
+
+    #[test]
+    fn only_hex_hashes_are_commit_hashes() {
+        assert!(is_commit_hash("1a2b3c4"));
+        assert!(is_commit_hash(&"f".repeat(40)));
+        assert!(!is_commit_hash("1a2b3c"));
+        assert!(!is_commit_hash(&"f".repeat(41)));
+        assert!(!is_commit_hash("--output=/tmp/x"));
+        assert!(!is_commit_hash("1A2B3C4"));
+        assert_eq!(commit_line("[commit: --output=/etc/passwd]"), None);
+        assert_eq!(tool_result("[main --output=/x] subject"), "[tool result]");
+    }
+
+    #[test]
+    fn git_commit_is_found_among_shell_commands() {
+        assert!(is_git_commit("git commit -m x"));
+        assert!(is_git_commit("cd repo && git -C sub commit --amend"));
+        assert!(is_git_commit(
+            "GIT_AUTHOR_NAME=a git -c user.email=b commit -qm x"
+        ));
+        assert!(!is_git_commit("git log --grep commit"));
+        assert!(!is_git_commit("echo git commit"));
+        assert!(!is_git_commit("git status"));
+    }
 }
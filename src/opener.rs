@@ -0,0 +1,20 @@
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+
+/// Open a path or URL with the platform's default handler
+/// (`open` on macOS, `xdg-open` elsewhere). Does not wait for it to exit.
+pub fn open(target: &str) -> Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program)
+        .arg(target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
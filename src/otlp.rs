@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::http;
+use crate::message::{SessionMessage, TokenUsage};
+use crate::session::{Session, UsagePoint};
+
+/// Upper bounds (seconds) of the turn duration histogram buckets
+const TURN_BUCKETS: [f64; 8] = [1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+/// OTLP export settings (`[otlp]` in config.toml; the table's presence
+/// turns export on)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OtlpConfig {
+    /// Collector base URL; `/v1/metrics` and `/v1/traces` are appended
+    pub endpoint: String,
+    pub service_name: String,
+    /// Extra HTTP headers, e.g. for collector authentication
+    pub headers: HashMap<String, String>,
+    /// How often batched data is sent
+    pub interval_secs: u64,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://localhost:4318".to_string(),
+            service_name: "claudy".to_string(),
+            headers: HashMap::new(),
+            interval_secs: 10,
+        }
+    }
+}
+
+/// Where a signal came from, attached to every data point and span
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Origin {
+    session: String,
+    project: String,
+}
+
+impl Origin {
+    fn attributes(&self) -> Vec<Value> {
+        vec![
+            attribute("session.id", &self.session),
+            attribute("project", &self.project),
+        ]
+    }
+}
+
+/// One prompt and everything Claude did in response
+#[derive(Debug, Clone)]
+struct Turn {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    model: Option<String>,
+    usage: TokenUsage,
+    tool_calls: u64,
+}
+
+enum Signal {
+    Tokens {
+        origin: Origin,
+        model: Option<String>,
+        usage: TokenUsage,
+    },
+    ToolCall {
+        origin: Origin,
+        tool: String,
+    },
+    Turn {
+        origin: Origin,
+        turn: Turn,
+    },
+}
+
+/// Turns live session activity into OTLP metrics and spans, sent from a
+/// background thread so a slow collector never stalls the UI. Only
+/// activity seen while claudy runs is exported, not transcript history.
+pub struct Exporter {
+    pub config: OtlpConfig,
+    tx: Option<mpsc::Sender<Signal>>,
+    /// Activity from before this is history and not exported
+    started: DateTime<Utc>,
+    /// Open turn per session id
+    turns: HashMap<String, (Origin, Turn)>,
+}
+
+impl Exporter {
+    pub fn start(config: OtlpConfig) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let worker_config = config.clone();
+        std::thread::spawn(move || run_worker(&worker_config, &rx));
+        Self {
+            config,
+            tx: Some(tx),
+            started: Utc::now(),
+            turns: HashMap::new(),
+        }
+    }
+
+    /// Record messages and usage points just read from `session`; anything
+    /// older than the exporter itself is skipped
+    pub fn record(
+        &mut self,
+        session: &Session,
+        messages: &[SessionMessage],
+        points: &[UsagePoint],
+    ) {
+        let started = self.started;
+        let points = points.iter().filter(|p| p.timestamp >= started);
+        let messages = messages.iter().filter(|m| m.timestamp >= started);
+        let origin = Origin {
+            session: session.id.clone(),
+            project: session.project_name().to_string(),
+        };
+        // Replies answer the latest prompt before them, so each prompt first
+        // takes the points that came earlier into the turn it closes
+        let mut points = points.peekable();
+        for message in messages {
+            if message.is_prompt() {
+                while let Some(point) = points.next_if(|p| p.timestamp < message.timestamp) {
+                    self.record_point(&origin, &session.id, point);
+                }
+                self.finish_turn(&session.id);
+                let turn = Turn {
+                    start: message.timestamp,
+                    end: message.timestamp,
+                    model: None,
+                    usage: TokenUsage::default(),
+                    tool_calls: 0,
+                };
+                self.turns
+                    .insert(session.id.clone(), (origin.clone(), turn));
+                continue;
+            }
+            for tool in message.tool_names() {
+                self.send(Signal::ToolCall {
+                    origin: origin.clone(),
+                    tool: tool.to_string(),
+                });
+            }
+            if let Some((_, turn)) = self.turns.get_mut(&session.id) {
+                turn.end = turn.end.max(message.timestamp);
+                turn.tool_calls += message.tool_names().count() as u64;
+            }
+        }
+        for point in points {
+            self.record_point(&origin, &session.id, point);
+        }
+    }
+
+    /// Export `point`'s tokens and add them to the session's open turn
+    fn record_point(&mut self, origin: &Origin, session_id: &str, point: &UsagePoint) {
+        self.send(Signal::Tokens {
+            origin: origin.clone(),
+            model: point.model.as_deref().map(str::to_string),
+            usage: point.usage,
+        });
+        if let Some((_, turn)) = self.turns.get_mut(session_id) {
+            turn.usage.input += point.usage.input;
+            turn.usage.cache_creation += point.usage.cache_creation;
+            turn.usage.cache_read += point.usage.cache_read;
+            turn.usage.output += point.usage.output;
+            if point.model.is_some() {
+                turn.model = point.model.as_deref().map(str::to_string);
+            }
+        }
+    }
+
+    /// Close turns whose session is now waiting for the user, or gone
+    pub fn tick(&mut self, sessions: &HashMap<String, Session>) {
+        let done: Vec<String> = self
+            .turns
+            .keys()
+            .filter(|id| {
+                sessions
+                    .get(*id)
+                    .is_none_or(|s| s.awaiting_user() || !s.is_active())
+            })
+            .cloned()
+            .collect();
+        for id in done {
+            self.finish_turn(&id);
+        }
+    }
+
+    fn finish_turn(&mut self, session_id: &str) {
+        if let Some((origin, turn)) = self.turns.remove(session_id)
+            && turn.end > turn.start
+        {
+            self.send(Signal::Turn { origin, turn });
+        }
+    }
+
+    fn send(&self, signal: Signal) {
+        if let Some(ref tx) = self.tx {
+            let _ = tx.send(signal);
+        }
+    }
+}
+
+impl Drop for Exporter {
+    /// Close open turns and hang up: the worker flushes what is batched and
+    /// exits on its own. It isn't waited for, since a post to a slow
+    /// collector would freeze a config reload or quit for as long as curl
+    /// takes.
+    fn drop(&mut self) {
+        let ids: Vec<String> = self.turns.keys().cloned().collect();
+        for id in ids {
+            self.finish_turn(&id);
+        }
+        self.tx.take();
+    }
+}
+
+/// Data batched between exports
+#[derive(Default)]
+struct Batch {
+    start: Option<DateTime<Utc>>,
+    /// (origin, model, token type) -> tokens
+    tokens: HashMap<(Origin, Option<String>, &'static str), u64>,
+    /// (origin, tool) -> calls
+    tool_calls: HashMap<(Origin, String), u64>,
+    turns: Vec<(Origin, Turn)>,
+}
+
+impl Batch {
+    fn add(&mut self, signal: Signal) {
+        self.start.get_or_insert_with(Utc::now);
+        match signal {
+            Signal::Tokens {
+                origin,
+                model,
+                usage,
+            } => {
+                for (kind, count) in [
+                    ("input", usage.input),
+                    ("cache_creation", usage.cache_creation),
+                    ("cache_read", usage.cache_read),
+                    ("output", usage.output),
+                ] {
+                    if count > 0 {
+                        *self
+                            .tokens
+                            .entry((origin.clone(), model.clone(), kind))
+                            .or_default() += count;
+                    }
+                }
+            }
+            Signal::ToolCall { origin, tool } => {
+                *self.tool_calls.entry((origin, tool)).or_default() += 1;
+            }
+            Signal::Turn { origin, turn } => self.turns.push((origin, turn)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start.is_none()
+    }
+
+    /// Delta sums and the turn duration histogram as an OTLP metrics request
+    fn metrics(&self, resource: &Value, now: DateTime<Utc>) -> Value {
+        let start = nanos(self.start.unwrap_or(now));
+        let end = nanos(now);
+        let sum = |name: &str, unit: &str, points: Vec<Value>| {
+            json!({
+                "name": name,
+                "unit": unit,
+                "sum": {
+                    "aggregationTemporality": 1,
+                    "isMonotonic": true,
+                    "dataPoints": points,
+                },
+            })
+        };
+        let token_points: Vec<Value> = self
+            .tokens
+            .iter()
+            .map(|((origin, model, kind), count)| {
+                let mut attributes = origin.attributes();
+                attributes.push(attribute("model", model.as_deref().unwrap_or("unknown")));
+                attributes.push(attribute("type", kind));
+                json!({
+                    "attributes": attributes,
+                    "startTimeUnixNano": start,
+                    "timeUnixNano": end,
+                    "asInt": count.to_string(),
+                })
+            })
+            .collect();
+        let tool_points: Vec<Value> = self
+            .tool_calls
+            .iter()
+            .map(|((origin, tool), count)| {
+                let mut attributes = origin.attributes();
+                attributes.push(attribute("tool", tool));
+                json!({
+                    "attributes": attributes,
+                    "startTimeUnixNano": start,
+                    "timeUnixNano": end,
+                    "asInt": count.to_string(),
+                })
+            })
+            .collect();
+
+        // One histogram point per origin
+        let mut histograms: HashMap<&Origin, (Vec<u64>, f64)> = HashMap::new();
+        for (origin, turn) in &self.turns {
+            let seconds = (turn.end - turn.start).num_milliseconds() as f64 / 1000.0;
+            let (counts, total) = histograms
+                .entry(origin)
+                .or_insert_with(|| (vec![0; TURN_BUCKETS.len() + 1], 0.0));
+            let bucket = TURN_BUCKETS
+                .iter()
+                .position(|bound| seconds <= *bound)
+                .unwrap_or(TURN_BUCKETS.len());
+            counts[bucket] += 1;
+            *total += seconds;
+        }
+        let turn_points: Vec<Value> = histograms
+            .into_iter()
+            .map(|(origin, (counts, total))| {
+                json!({
+                    "attributes": origin.attributes(),
+                    "startTimeUnixNano": start,
+                    "timeUnixNano": end,
+                    "count": counts.iter().sum::<u64>().to_string(),
+                    "sum": total,
+                    "bucketCounts": counts.iter().map(u64::to_string).collect::<Vec<_>>(),
+                    "explicitBounds": TURN_BUCKETS,
+                })
+            })
+            .collect();
+
+        let mut metrics = Vec::new();
+        if !token_points.is_empty() {
+            metrics.push(sum("claude.tokens", "{token}", token_points));
+        }
+        if !tool_points.is_empty() {
+            metrics.push(sum("claude.tool_calls", "{call}", tool_points));
+        }
+        if !turn_points.is_empty() {
+            metrics.push(json!({
+                "name": "claude.turn.duration",
+                "unit": "s",
+                "histogram": {
+                    "aggregationTemporality": 1,
+                    "dataPoints": turn_points,
+                },
+            }));
+        }
+        json!({
+            "resourceMetrics": [{
+                "resource": resource,
+                "scopeMetrics": [{
+                    "scope": { "name": "claudy" },
+                    "metrics": metrics,
+                }],
+            }],
+        })
+    }
+
+    /// One span per turn, all turns of a session sharing a trace
+    fn traces(&self, resource: &Value) -> Option<Value> {
+        if self.turns.is_empty() {
+            return None;
+        }
+        let spans: Vec<Value> = self
+            .turns
+            .iter()
+            .map(|(origin, turn)| {
+                let mut attributes = origin.attributes();
+                if let Some(ref model) = turn.model {
+                    attributes.push(attribute("model", model));
+                }
+                for (key, value) in [
+                    ("tokens.input", turn.usage.tokens_in()),
+                    ("tokens.output", turn.usage.output),
+                    ("tool_calls", turn.tool_calls),
+                ] {
+                    attributes.push(json!({ "key": key, "value": { "intValue": value.to_string() } }));
+                }
+                json!({
+                    "traceId": format!("{:016x}{:016x}", fnv1a(&origin.session), fnv1a(&format!("{}/trace", origin.session))),
+                    "spanId": format!("{:016x}", fnv1a(&format!("{}/{}", origin.session, nanos(turn.start)))),
+                    "name": "claude.turn",
+                    "kind": 1,
+                    "startTimeUnixNano": nanos(turn.start),
+                    "endTimeUnixNano": nanos(turn.end),
+                    "attributes": attributes,
+                })
+            })
+            .collect();
+        Some(json!({
+            "resourceSpans": [{
+                "resource": resource,
+                "scopeSpans": [{
+                    "scope": { "name": "claudy" },
+                    "spans": spans,
+                }],
+            }],
+        }))
+    }
+}
+
+/// Batch signals and post them every `interval_secs` until the exporter
+/// is dropped, then send what is left
+fn run_worker(config: &OtlpConfig, rx: &mpsc::Receiver<Signal>) {
+    let interval = Duration::from_secs(config.interval_secs.max(1));
+    let resource = json!({ "attributes": [attribute("service.name", &config.service_name)] });
+    let mut batch = Batch::default();
+    let mut next_flush = std::time::Instant::now() + interval;
+    loop {
+        let timeout = next_flush.saturating_duration_since(std::time::Instant::now());
+        let disconnected = match rx.recv_timeout(timeout) {
+            Ok(signal) => {
+                batch.add(signal);
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => false,
+            Err(mpsc::RecvTimeoutError::Disconnected) => true,
+        };
+        if !batch.is_empty() {
+            let batch = std::mem::take(&mut batch);
+            let metrics = batch.metrics(&resource, Utc::now());
+            if let Err(e) = post(config, "/v1/metrics", &metrics) {
+                tracing::warn!(error = format!("{:#}", e), "OTLP metrics export failed");
+            }
+            if let Some(traces) = batch.traces(&resource)
+                && let Err(e) = post(config, "/v1/traces", &traces)
+            {
+                tracing::warn!(error = format!("{:#}", e), "OTLP trace export failed");
+            }
+        }
+        if disconnected {
+            return;
+        }
+        next_flush = std::time::Instant::now() + interval;
+    }
+}
+
+/// POST an OTLP/HTTP JSON request
+fn post(config: &OtlpConfig, path: &str, body: &Value) -> Result<()> {
+    let url = format!("{}{}", config.endpoint.trim_end_matches('/'), path);
+    let headers = config
+        .headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()));
+    http::post_json(&url, headers, &body.to_string())
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// OTLP timestamps are nanosecond strings
+fn nanos(t: DateTime<Utc>) -> String {
+    t.timestamp_nanos_opt().unwrap_or_default().to_string()
+}
+
+/// 64-bit FNV-1a of `text`, for ids derived from the session: unlike
+/// `DefaultHasher` it is fixed, so ids line up across claudy builds
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_the_reference_values() {
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a("foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::message::TokenUsage;
+use crate::session::UsagePoint;
+
+/// Maintained model price list fetched by `claudy pricing update`
+pub const LITELLM_PRICES_URL: &str =
+    "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json";
+
+/// USD per million tokens for one model
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// Writing to the prompt cache (5-minute TTL)
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64) -> Self {
+        // Cache writes cost 1.25x input and reads 0.1x on every Claude model
+        Self {
+            input,
+            output,
+            cache_write: input * 1.25,
+            cache_read: input * 0.1,
+        }
+    }
+
+    /// Cost in USD of one reply's usage
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input as f64 * self.input
+            + usage.output as f64 * self.output
+            + usage.cache_creation as f64 * self.cache_write
+            + usage.cache_read as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Built-in list prices, matched against model ids by substring; the
+/// first match wins, so more specific patterns come first
+const BUILTIN_PRICES: &[(&str, ModelPrice)] = &[
+    ("opus-4-5", ModelPrice::new(5.0, 25.0)),
+    ("opus-4-1", ModelPrice::new(15.0, 75.0)),
+    // claude-opus-4-20250514
+    ("opus-4-2025", ModelPrice::new(15.0, 75.0)),
+    ("3-opus", ModelPrice::new(15.0, 75.0)),
+    ("sonnet-4", ModelPrice::new(3.0, 15.0)),
+    ("3-7-sonnet", ModelPrice::new(3.0, 15.0)),
+    ("3-5-sonnet", ModelPrice::new(3.0, 15.0)),
+    ("haiku-4-5", ModelPrice::new(1.0, 5.0)),
+    ("3-5-haiku", ModelPrice::new(0.8, 4.0)),
+    ("3-haiku", ModelPrice::new(0.25, 1.25)),
+];
+
+/// Guesses for models newer than every list, at the latest prices of
+/// their family. Live estimates use them; billing reports count those
+/// tokens as unpriced instead.
+const FAMILY_PRICES: &[(&str, ModelPrice)] = &[
+    ("opus", ModelPrice::new(5.0, 25.0)),
+    ("sonnet", ModelPrice::new(3.0, 15.0)),
+    ("haiku", ModelPrice::new(1.0, 5.0)),
+];
+
+/// Currency estimated costs are shown in (`[currency]` in config.toml)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Currency {
+    /// ISO 4217 code, e.g. "EUR"
+    pub code: String,
+    /// Units of this currency per US dollar
+    pub usd_rate: f64,
+    /// BCP 47 tag such as "de-DE"; picks separators and symbol placement
+    pub locale: String,
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Self {
+            code: "USD".to_string(),
+            usd_rate: 1.0,
+            locale: "en-US".to_string(),
+        }
+    }
+}
+
+impl Currency {
+    fn symbol(&self) -> &str {
+        match self.code.as_str() {
+            "USD" => "$",
+            "EUR" => "€",
+            "GBP" => "£",
+            "JPY" | "CNY" => "¥",
+            "INR" => "₹",
+            "KRW" => "₩",
+            code => code,
+        }
+    }
+
+    /// Minor units shown; yen and won have none
+    fn decimals(&self) -> usize {
+        match self.code.as_str() {
+            "JPY" | "KRW" => 0,
+            _ => 2,
+        }
+    }
+
+    /// (thousands, decimal) separators and whether the symbol trails the
+    /// amount, by the locale's language and region
+    fn conventions(&self) -> (&'static str, char, bool) {
+        let mut parts = self.locale.split(['-', '_']);
+        let language = parts.next().unwrap_or("").to_ascii_lowercase();
+        let region = parts.next().unwrap_or("").to_ascii_uppercase();
+        match (language.as_str(), region.as_str()) {
+            (_, "CH") => ("'", '.', false),
+            ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr", _) => (".", ',', true),
+            ("fr" | "sv" | "nb" | "no" | "fi" | "pl" | "cs" | "ru" | "uk", _) => {
+                ("\u{a0}", ',', true)
+            }
+            _ => (",", '.', false),
+        }
+    }
+
+    /// A USD amount converted and formatted for display, e.g. "€1,234.56"
+    /// or "1.234,56 €"
+    pub fn format(&self, usd: f64) -> String {
+        let amount = usd * self.usd_rate;
+        let (thousands, decimal, trailing) = self.conventions();
+        let fixed = format!("{:.*}", self.decimals(), amount.abs());
+        let (whole, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+        let mut number = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                number.push_str(thousands);
+            }
+            number.push(digit);
+        }
+        if !fraction.is_empty() {
+            number.push(decimal);
+            number.push_str(fraction);
+        }
+        let sign = if amount < 0.0 { "-" } else { "" };
+        let symbol = self.symbol();
+        if trailing {
+            format!("{}{}\u{a0}{}", sign, number, symbol)
+        } else if symbol.len() > 1 && symbol.is_ascii() {
+            // Plain codes read better apart: "CHF 12.00"
+            format!("{}{}\u{a0}{}", sign, symbol, number)
+        } else {
+            format!("{}{}{}", sign, symbol, number)
+        }
+    }
+}
+
+/// Where a price came from, in order of precedence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceSource {
+    /// ~/.config/claudy/pricing.toml
+    User,
+    /// Cached copy of the LiteLLM price list
+    Fetched,
+    Builtin,
+    /// A family-wide guess from `FAMILY_PRICES`
+    Family,
+}
+
+impl PriceSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            PriceSource::User => "pricing.toml",
+            PriceSource::Fetched => "fetched",
+            PriceSource::Builtin => "built-in",
+            PriceSource::Family => "family guess",
+        }
+    }
+}
+
+/// An entry of the user's pricing.toml; cache prices default to the
+/// usual multiples of the input price
+#[derive(Debug, Deserialize)]
+struct UserPrice {
+    input: f64,
+    output: f64,
+    cache_write: Option<f64>,
+    cache_read: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct UserPricing {
+    /// Keyed by model id or a substring of it (e.g. "opus-4-5")
+    models: HashMap<String, UserPrice>,
+}
+
+/// An entry of LiteLLM's price list (USD per token)
+#[derive(Debug, Deserialize)]
+struct LiteLlmPrice {
+    litellm_provider: Option<String>,
+    input_cost_per_token: Option<f64>,
+    output_cost_per_token: Option<f64>,
+    cache_creation_input_token_cost: Option<f64>,
+    cache_read_input_token_cost: Option<f64>,
+}
+
+/// Per-model prices used to estimate costs
+#[derive(Debug, Clone)]
+pub struct PriceTable {
+    /// Matched in order by model-id substring
+    entries: Vec<(String, ModelPrice, PriceSource)>,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        Self {
+            entries: BUILTIN_PRICES
+                .iter()
+                .map(|(pattern, price)| (pattern.to_string(), *price, PriceSource::Builtin))
+                .chain(
+                    FAMILY_PRICES
+                        .iter()
+                        .map(|(pattern, price)| (pattern.to_string(), *price, PriceSource::Family)),
+                )
+                .collect(),
+        }
+    }
+}
+
+impl PriceTable {
+    /// User-maintained overrides (~/.config/claudy/pricing.toml)
+    pub fn user_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("claudy").join("pricing.toml"))
+    }
+
+    /// Cached LiteLLM price list written by `claudy pricing update`
+    pub fn cache_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|d| d.join("claudy").join("model_prices.json"))
+    }
+
+    /// Built-in prices, overridden by the fetched list, overridden by the
+    /// user's pricing.toml. A broken user file is an error; a broken cache
+    /// is only logged, since `claudy pricing update` can replace it.
+    pub fn load() -> Result<Self> {
+        let mut table = Self::default();
+        if let Some(path) = Self::cache_path()
+            && path.exists()
+        {
+            match fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|data| parse_litellm(&data))
+            {
+                Ok(prices) => table.prepend(prices, PriceSource::Fetched),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "ignoring price cache")
+                }
+            }
+        }
+        if let Some(path) = Self::user_path()
+            && path.exists()
+        {
+            let data = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let user: UserPricing = toml::from_str(&data)
+                .with_context(|| format!("invalid pricing {}", path.display()))?;
+            let prices = user.models.into_iter().map(|(pattern, p)| {
+                let defaults = ModelPrice::new(p.input, p.output);
+                let price = ModelPrice {
+                    cache_write: p.cache_write.unwrap_or(defaults.cache_write),
+                    cache_read: p.cache_read.unwrap_or(defaults.cache_read),
+                    ..defaults
+                };
+                (pattern, price)
+            });
+            table.prepend(prices.collect(), PriceSource::User);
+        }
+        Ok(table)
+    }
+
+    /// Put `prices` ahead of the current entries, longest pattern first so
+    /// that the most specific one matches
+    fn prepend(&mut self, mut prices: Vec<(String, ModelPrice)>, source: PriceSource) {
+        prices.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        let rest = std::mem::take(&mut self.entries);
+        self.entries = prices
+            .into_iter()
+            .map(|(pattern, price)| (pattern, price, source))
+            .chain(rest)
+            .collect();
+    }
+
+    /// Every entry in match order
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &ModelPrice, PriceSource)> {
+        self.entries
+            .iter()
+            .map(|(pattern, price, source)| (pattern.as_str(), price, *source))
+    }
+
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.entries
+            .iter()
+            .find(|(pattern, _, _)| model.contains(pattern.as_str()))
+            .map(|(_, price, _)| price)
+    }
+
+    /// `price`, unless only a family guess matches
+    fn listed_price(&self, model: &str) -> Option<&ModelPrice> {
+        self.entries
+            .iter()
+            .find(|(pattern, _, _)| model.contains(pattern.as_str()))
+            .filter(|(_, _, source)| *source != PriceSource::Family)
+            .map(|(_, price, _)| price)
+    }
+
+    /// Estimated USD cost of `points` and the tokens left out of it for
+    /// lack of a price
+    pub fn total_cost<'a>(&self, points: impl IntoIterator<Item = &'a UsagePoint>) -> (f64, u64) {
+        points
+            .into_iter()
+            .fold((0.0, 0), |(cost, unpriced), point| {
+                match self.cost(point.model.as_deref(), &point.usage) {
+                    Some(c) => (cost + c, unpriced),
+                    None => (cost, unpriced + point.usage.total()),
+                }
+            })
+    }
+
+    /// Estimated cost in USD, or `None` for models without a known price
+    /// (including the `<synthetic>` placeholder Claude Code writes)
+    pub fn cost(&self, model: Option<&str>, usage: &TokenUsage) -> Option<f64> {
+        self.price(model?).map(|p| p.cost(usage))
+    }
+
+    /// `cost` for billing reports, which leave out family guesses: a model
+    /// without a listed price is unpriced rather than estimated
+    pub fn billed_cost(&self, model: Option<&str>, usage: &TokenUsage) -> Option<f64> {
+        self.listed_price(model?).map(|p| p.cost(usage))
+    }
+}
+
+/// Anthropic models from LiteLLM's price list, converted to USD per million
+/// tokens; entries routed through other providers (Bedrock, Vertex) are skipped
+fn parse_litellm(data: &str) -> Result<Vec<(String, ModelPrice)>> {
+    // Some entries (e.g. "sample_spec") have other shapes: parse loosely
+    let raw: HashMap<String, serde_json::Value> = serde_json::from_str(data)?;
+    let prices = raw
+        .into_iter()
+        .filter_map(|(model, value)| {
+            let entry: LiteLlmPrice = serde_json::from_value(value).ok()?;
+            if entry.litellm_provider.as_deref() != Some("anthropic") || !model.contains("claude") {
+                return None;
+            }
+            let per_million = |cost: Option<f64>| cost.map(|c| c * 1_000_000.0);
+            let defaults = ModelPrice::new(
+                per_million(entry.input_cost_per_token)?,
+                per_million(entry.output_cost_per_token)?,
+            );
+            let price = ModelPrice {
+                cache_write: per_million(entry.cache_creation_input_token_cost)
+                    .unwrap_or(defaults.cache_write),
+                cache_read: per_million(entry.cache_read_input_token_cost)
+                    .unwrap_or(defaults.cache_read),
+                ..defaults
+            };
+            Some((model, price))
+        })
+        .collect();
+    Ok(prices)
+}
+
+/// Download the LiteLLM price list with `curl` and cache it for offline
+/// use. Returns the cache path and the number of Claude models it prices.
+pub fn update(url: &str) -> Result<(PathBuf, usize)> {
+    let path = PriceTable::cache_path().context("no cache directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    let status = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--output",
+        ])
+        .arg(&tmp)
+        .arg(url)
+        .status()
+        .context("failed to run curl")?;
+    if !status.success() {
+        let _ = fs::remove_file(&tmp);
+        bail!("curl exited with {}", status);
+    }
+    // Only replace the cache with a list that actually parses
+    let data = fs::read_to_string(&tmp)?;
+    let count = match parse_litellm(&data) {
+        Ok(prices) if !prices.is_empty() => prices.len(),
+        Ok(_) => {
+            let _ = fs::remove_file(&tmp);
+            bail!("no Claude models in {}", url);
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            return Err(e.context(format!("invalid price list from {}", url)));
+        }
+    };
+    fs::rename(&tmp, &path)?;
+    Ok((path, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage() -> TokenUsage {
+        TokenUsage {
+            input: 1_000_000,
+            output: 0,
+            cache_creation: 0,
+            cache_read: 0,
+        }
+    }
+
+    #[test]
+    fn listed_opus_models_get_their_own_price() {
+        let prices = PriceTable::default();
+        let input = |model: &str| prices.billed_cost(Some(model), &usage());
+        assert_eq!(input("claude-opus-4-5-20251101"), Some(5.0));
+        assert_eq!(input("claude-opus-4-1-20250805"), Some(15.0));
+        assert_eq!(input("claude-opus-4-20250514"), Some(15.0));
+        assert_eq!(input("claude-3-opus-20240229"), Some(15.0));
+        assert_eq!(input("claude-3-5-haiku-20241022"), Some(0.8));
+    }
+
+    #[test]
+    fn family_guesses_stay_out_of_billing() {
+        let prices = PriceTable::default();
+        assert_eq!(prices.cost(Some("claude-opus-9"), &usage()), Some(5.0));
+        assert_eq!(prices.billed_cost(Some("claude-opus-9"), &usage()), None);
+        assert_eq!(prices.billed_cost(Some("<synthetic>"), &usage()), None);
+    }
+}
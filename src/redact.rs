@@ -0,0 +1,101 @@
+use std::borrow::Cow;
+
+use regex::{Captures, Regex};
+use serde::Deserialize;
+
+/// Shown in place of a masked secret
+const MASK: &str = "████";
+
+/// Common credentials: the capture group is masked, the rest (a
+/// recognizable prefix) stays
+const BUILTIN_PATTERNS: &[&str] = &[
+    // Anthropic, OpenAI and similar `sk-` keys
+    r"\bsk-(?:ant-)?([A-Za-z0-9_-]{16,})",
+    // GitHub tokens
+    r"\bgh[pousr]_([A-Za-z0-9]{20,})",
+    r"\bgithub_pat_([A-Za-z0-9_]{20,})",
+    // AWS access key ids
+    r"\bAKIA([0-9A-Z]{16})\b",
+    // Slack tokens
+    r"\bxox[abprs]-([A-Za-z0-9-]{10,})",
+    // Google API keys
+    r"\bAIza([0-9A-Za-z_-]{35})",
+    // Authorization headers
+    r"(?i)\bbearer\s+([A-Za-z0-9._~+/-]{16,}=*)",
+    // key = value assignments of anything that looks secret
+    r#"(?i)\b[a-z_]*(?:api_?key|secret|token|password|passwd)["']?\s*[:=]\s*["']?([^\s"',;]{8,})"#,
+];
+
+/// Secret redaction settings (`[redaction]` in config.toml)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    /// Mask the common credential patterns built into claudy
+    pub builtin: bool,
+    /// Extra regexes; the first capture group is masked, or the whole
+    /// match if there is none
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            builtin: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// Privacy mode placeholder for `text`: every visible character becomes
+/// `░`, keeping the shape of words and lines but none of their content
+pub fn blur(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_whitespace() { c } else { '░' })
+        .collect()
+}
+
+/// Masks secrets in text before it is displayed or copied
+pub struct Redactor {
+    pub config: RedactionConfig,
+    rules: Vec<Regex>,
+}
+
+impl Redactor {
+    pub fn new(config: RedactionConfig) -> Self {
+        let mut rules = Vec::new();
+        if config.enabled {
+            let builtin = BUILTIN_PATTERNS.iter().copied().filter(|_| config.builtin);
+            for pattern in builtin.chain(config.patterns.iter().map(String::as_str)) {
+                match Regex::new(pattern) {
+                    Ok(re) => rules.push(re),
+                    Err(e) => tracing::warn!(pattern, error = %e, "redaction pattern skipped"),
+                }
+            }
+        }
+        Self { config, rules }
+    }
+
+    /// `text` with every secret masked; borrowed when there was nothing to mask
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for rule in &self.rules {
+            if !rule.is_match(&text) {
+                continue;
+            }
+            let masked = rule.replace_all(&text, |caps: &Captures| {
+                let whole = caps.get(0).expect("group 0 is the match");
+                let secret = caps.get(1).unwrap_or(whole);
+                format!(
+                    "{}{}{}",
+                    &whole.as_str()[..secret.start() - whole.start()],
+                    MASK,
+                    &whole.as_str()[secret.end() - whole.start()..]
+                )
+            });
+            text = Cow::Owned(masked.into_owned());
+        }
+        text
+    }
+}
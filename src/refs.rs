@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::message::{MessageType, SessionMessage};
+
+/// A `path/to/file.rs:123` (or `:123:45`) mention in a message
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileRef {
+    pub path: String,
+    pub line: u32,
+    pub column: Option<u32>,
+}
+
+impl FileRef {
+    /// Where the file is: absolute paths as they are, `~/` under the home
+    /// directory, anything else relative to the session's `cwd`
+    pub fn resolve(&self, cwd: Option<&str>) -> PathBuf {
+        if let Some(rest) = self.path.strip_prefix("~/")
+            && let Some(home) = dirs::home_dir()
+        {
+            return home.join(rest);
+        }
+        let path = Path::new(self.path.strip_prefix("./").unwrap_or(&self.path));
+        match cwd {
+            Some(cwd) if path.is_relative() => Path::new(cwd).join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+}
+
+impl std::fmt::Display for FileRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.path, self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        Ok(())
+    }
+}
+
+/// Characters quoting or punctuating a reference in prose or markdown
+const WRAPPERS: &[char] = &[
+    '`', '\'', '"', '(', ')', '[', ']', '<', '>', '{', '}', ',', ';', ':', '.', '!', '?', '*',
+];
+
+/// Whitespace-separated words of `text` with their byte ranges, quoting
+/// and punctuation trimmed off
+fn words(text: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    text.split_whitespace().map(move |word| {
+        // A leading dot belongs to the path (`./src`, `.github/`)
+        let trimmed = word
+            .trim_end_matches(WRAPPERS)
+            .trim_start_matches(|c| c != '.' && WRAPPERS.contains(&c));
+        let start = trimmed.as_ptr() as usize - text.as_ptr() as usize;
+        (start..start + trimmed.len(), trimmed)
+    })
+}
+
+/// The http(s) URL in `word`, which may be wrapped as in a markdown link
+/// `[title](https://...)`, and its byte range within the word
+fn url_in(word: &str) -> Option<(Range<usize>, &str)> {
+    let start = word.find("https://").or_else(|| word.find("http://"))?;
+    let url = word[start..].trim_end_matches(WRAPPERS);
+    let host = url.split_once("://")?.1;
+    (!host.is_empty()).then(|| (start..start + url.len(), url))
+}
+
+/// File references in `text`, in order of first mention
+pub fn file_refs(text: &str) -> Vec<FileRef> {
+    let mut refs: Vec<FileRef> = Vec::new();
+    for (_, word) in words(text) {
+        let Some(found) = parse_file_ref(word) else {
+            continue;
+        };
+        if !refs.contains(&found) {
+            refs.push(found);
+        }
+    }
+    refs
+}
+
+/// http(s) URLs in `text`, in order of first mention
+pub fn urls(text: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for (_, word) in words(text) {
+        let Some((_, url)) = url_in(word) else {
+            continue;
+        };
+        if !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+/// Something in a message a terminal can link to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Link {
+    Url(String),
+    File(FileRef),
+}
+
+/// URLs and file references in `text` with their byte ranges, in order
+pub fn links(text: &str) -> Vec<(Range<usize>, Link)> {
+    words(text)
+        .filter_map(|(range, word)| {
+            if let Some((url_range, url)) = url_in(word) {
+                let start = range.start + url_range.start;
+                return Some((start..start + url_range.len(), Link::Url(url.to_string())));
+            }
+            parse_file_ref(word).map(|file_ref| (range, Link::File(file_ref)))
+        })
+        .collect()
+}
+
+/// A link cited in a session and where
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub link: Link,
+    /// Indices of the messages mentioning it, in order
+    pub messages: Vec<usize>,
+}
+
+/// Every link in prompts, replies and tool calls, in order of first
+/// mention. Tool results are left out: search output alone would bury
+/// the rest under hundreds of `file:line` hits.
+pub fn references(messages: &[SessionMessage]) -> Vec<Reference> {
+    let mut references: Vec<Reference> = Vec::new();
+    let mut index: HashMap<Link, usize> = HashMap::new();
+    for (idx, message) in messages.iter().enumerate() {
+        let cited = match message.msg_type {
+            MessageType::User => message.is_prompt(),
+            MessageType::Assistant | MessageType::ToolUse => true,
+            MessageType::Progress | MessageType::Other => false,
+        };
+        if !cited {
+            continue;
+        }
+        for (_, link) in links(&message.content) {
+            let reference = *index.entry(link.clone()).or_insert_with(|| {
+                references.push(Reference {
+                    link,
+                    messages: Vec::new(),
+                });
+                references.len() - 1
+            });
+            let mentions = &mut references[reference].messages;
+            if mentions.last() != Some(&idx) {
+                mentions.push(idx);
+            }
+        }
+    }
+    references
+}
+
+fn parse_file_ref(word: &str) -> Option<FileRef> {
+    let mut parts = word.split(':');
+    let path = parts.next()?;
+    let line = parts.next()?.parse().ok()?;
+    let column = match parts.next() {
+        Some(column) => Some(column.parse().ok()?),
+        None => None,
+    };
+    if parts.next().is_some() || line == 0 {
+        return None;
+    }
+    // A file name with an extension, or at least a directory in the path,
+    // so times like 10:30 and hosts like localhost:8080 don't count
+    let name = path.rsplit('/').next()?;
+    let plausible = path
+        .chars()
+        .all(|c| c.is_alphanumeric() || "_-./~@+".contains(c))
+        && name.chars().any(char::is_alphabetic)
+        && (path.contains('/') || name.contains('.'))
+        && !name.ends_with('.');
+    plausible.then(|| FileRef {
+        path: path.to_string(),
+        line,
+        column,
+    })
+}
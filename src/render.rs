@@ -0,0 +1,194 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use serde::Deserialize;
+use unicode_width::UnicodeWidthStr;
+
+use crate::message::{MessageType, SessionMessage};
+use crate::ui::truncate_width;
+
+/// Takes over how the body of certain messages is drawn in the chat view.
+/// Compiled-in renderers implement this and are added with
+/// `RendererRegistry::register`; config rules map onto the built-in ones.
+pub trait MessageRenderer {
+    /// Whether this renderer wants `message`
+    fn matches(&self, message: &SessionMessage) -> bool;
+    /// Body lines for `message` (the header is drawn by the chat view),
+    /// at most `width` columns wide before wrapping
+    fn render(&self, message: &SessionMessage, width: usize) -> Vec<Line<'static>>;
+}
+
+/// Built-in renderings a config rule can pick
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderStyle {
+    /// Rows of `|`- or tab-separated cells, aligned into columns
+    Table,
+    /// Verbatim with a gutter, for code and command output
+    Code,
+    /// Only the first line, dimmed
+    Collapsed,
+}
+
+/// A configured renderer (`[[renderers]]` in config.toml); a message must
+/// satisfy every condition given
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RenderRule {
+    /// Tool called by the message, e.g. "mcp__postgres__query"
+    pub tool: Option<String>,
+    /// Text the content must contain
+    pub contains: Option<String>,
+    /// Message types the rule applies to; empty means all
+    #[serde(default)]
+    pub types: Vec<MessageType>,
+    pub style: RenderStyle,
+}
+
+impl MessageRenderer for RenderRule {
+    fn matches(&self, message: &SessionMessage) -> bool {
+        (self.types.is_empty() || self.types.contains(&message.msg_type))
+            && self
+                .tool
+                .as_ref()
+                .is_none_or(|tool| message.tool_names().any(|t| t == tool))
+            && self
+                .contains
+                .as_ref()
+                .is_none_or(|needle| message.content.contains(needle.as_str()))
+    }
+
+    fn render(&self, message: &SessionMessage, width: usize) -> Vec<Line<'static>> {
+        match self.style {
+            RenderStyle::Table => render_table(&message.content, width),
+            RenderStyle::Code => message
+                .content
+                .lines()
+                .map(|line| {
+                    Line::from(vec![
+                        Span::styled("  │ ", Style::default().fg(Color::DarkGray)),
+                        Span::styled(line.to_string(), Style::default().fg(Color::LightCyan)),
+                    ])
+                })
+                .collect(),
+            RenderStyle::Collapsed => {
+                let first = message.content.lines().next().unwrap_or("");
+                vec![Line::from(Span::styled(
+                    format!("  {}", truncate_width(first, width.saturating_sub(2))),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                ))]
+            }
+        }
+    }
+}
+
+/// Renderers in the order they are consulted; the first match wins
+#[derive(Default)]
+pub struct RendererRegistry {
+    renderers: Vec<Box<dyn MessageRenderer>>,
+}
+
+impl RendererRegistry {
+    /// Configured rules, in config order
+    pub fn from_rules(rules: &[RenderRule]) -> Self {
+        let mut registry = Self::default();
+        for rule in rules {
+            registry.register(Box::new(rule.clone()));
+        }
+        registry
+    }
+
+    pub fn register(&mut self, renderer: Box<dyn MessageRenderer>) {
+        self.renderers.push(renderer);
+    }
+
+    pub fn find(&self, message: &SessionMessage) -> Option<&dyn MessageRenderer> {
+        self.renderers
+            .iter()
+            .find(|r| r.matches(message))
+            .map(|r| r.as_ref())
+    }
+}
+
+/// Align `|`- or tab-separated rows into columns; other lines (prose
+/// around the table, markdown `---` rules) pass through
+fn render_table(content: &str, width: usize) -> Vec<Line<'static>> {
+    let split = |line: &str| -> Option<Vec<String>> {
+        let trimmed = line.trim();
+        let cells: Vec<&str> = if trimmed.contains('\t') {
+            trimmed.split('\t').collect()
+        } else if trimmed.contains('|') {
+            trimmed.trim_matches('|').split('|').collect()
+        } else {
+            return None;
+        };
+        Some(cells.iter().map(|c| c.trim().to_string()).collect())
+    };
+    let is_rule = |cells: &[String]| {
+        cells
+            .iter()
+            .all(|c| !c.is_empty() && c.chars().all(|ch| matches!(ch, '-' | ':' | '+' | '=')))
+    };
+
+    let rows: Vec<Option<Vec<String>>> = content.lines().map(split).collect();
+    let mut widths: Vec<usize> = Vec::new();
+    for cells in rows.iter().flatten().filter(|c| !is_rule(c)) {
+        if widths.len() < cells.len() {
+            widths.resize(cells.len(), 0);
+        }
+        for (w, cell) in widths.iter_mut().zip(cells) {
+            *w = (*w).max(cell.width());
+        }
+    }
+    // Shrink the widest columns until the table fits
+    let available = width.saturating_sub(2 + 3 * widths.len().saturating_sub(1));
+    while widths.iter().sum::<usize>() > available {
+        let Some(widest) = widths.iter_mut().max() else {
+            break;
+        };
+        if *widest <= 3 {
+            break;
+        }
+        *widest -= 1;
+    }
+
+    let border = Style::default().fg(Color::DarkGray);
+    let mut header_done = false;
+    let mut lines = Vec::new();
+    for (line, cells) in content.lines().zip(rows) {
+        let Some(cells) = cells else {
+            lines.push(Line::from(format!("  {}", line)));
+            continue;
+        };
+        if is_rule(&cells) {
+            let rule: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+            lines.push(Line::from(Span::styled(
+                format!("  {}", rule.join("─┼─")),
+                border,
+            )));
+            continue;
+        }
+        // The first row reads as a header
+        let style = if header_done {
+            Style::default().fg(Color::White)
+        } else {
+            header_done = true;
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        };
+        let mut spans = vec![Span::raw("  ")];
+        for (i, w) in widths.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::styled(" │ ", border));
+            }
+            let cell = truncate_width(cells.get(i).map_or("", |c| c.as_str()), *w);
+            let pad = w.saturating_sub(cell.width());
+            spans.push(Span::styled(format!("{}{}", cell, " ".repeat(pad)), style));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
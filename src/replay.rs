@@ -0,0 +1,166 @@
+use std::time::{Duration, Instant};
+
+use crate::message::SessionMessage;
+
+/// Longest wait between two messages in real time; idle stretches of the
+/// transcript are cut down to this so a long session plays in minutes
+const MAX_GAP: Duration = Duration::from_secs(2);
+
+/// Fraction of the session a seek step moves
+const SEEK_STEP_PERCENT: usize = 5;
+
+/// Playback rate relative to the transcript's own timing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    X1,
+    X2,
+    X5,
+    /// One message per tick, ignoring timestamps
+    Max,
+}
+
+impl ReplaySpeed {
+    pub fn label(self) -> &'static str {
+        match self {
+            ReplaySpeed::X1 => "1x",
+            ReplaySpeed::X2 => "2x",
+            ReplaySpeed::X5 => "5x",
+            ReplaySpeed::Max => "max",
+        }
+    }
+
+    pub fn faster(self) -> Self {
+        match self {
+            ReplaySpeed::X1 => ReplaySpeed::X2,
+            ReplaySpeed::X2 => ReplaySpeed::X5,
+            ReplaySpeed::X5 | ReplaySpeed::Max => ReplaySpeed::Max,
+        }
+    }
+
+    pub fn slower(self) -> Self {
+        match self {
+            ReplaySpeed::X1 | ReplaySpeed::X2 => ReplaySpeed::X1,
+            ReplaySpeed::X5 => ReplaySpeed::X2,
+            ReplaySpeed::Max => ReplaySpeed::X5,
+        }
+    }
+
+    fn factor(self) -> Option<u32> {
+        match self {
+            ReplaySpeed::X1 => Some(1),
+            ReplaySpeed::X2 => Some(2),
+            ReplaySpeed::X5 => Some(5),
+            ReplaySpeed::Max => None,
+        }
+    }
+}
+
+/// Playback of one session's messages in their original rhythm (`v`)
+#[derive(Debug)]
+pub struct Replay {
+    pub session_id: String,
+    /// Messages revealed so far
+    pub position: usize,
+    pub speed: ReplaySpeed,
+    pub playing: bool,
+    /// Transcript time played since the last message was revealed
+    waited: Duration,
+    last_tick: Instant,
+}
+
+impl Replay {
+    pub fn new(session_id: String) -> Self {
+        Self {
+            session_id,
+            position: 1,
+            speed: ReplaySpeed::X1,
+            playing: true,
+            waited: Duration::ZERO,
+            last_tick: Instant::now(),
+        }
+    }
+
+    /// Reveal the messages whose time has come
+    pub fn advance(&mut self, messages: &[SessionMessage]) {
+        let elapsed = self.last_tick.elapsed();
+        self.last_tick = Instant::now();
+        if !self.playing {
+            return;
+        }
+        match self.speed.factor() {
+            None => self.position += 1,
+            Some(factor) => {
+                self.waited += elapsed * factor;
+                while let Some(next) = messages.get(self.position) {
+                    let gap = self
+                        .position
+                        .checked_sub(1)
+                        .and_then(|prev| (next.timestamp - messages[prev].timestamp).to_std().ok())
+                        .unwrap_or_default()
+                        .min(MAX_GAP * factor);
+                    if self.waited < gap {
+                        break;
+                    }
+                    self.waited -= gap;
+                    self.position += 1;
+                }
+            }
+        }
+        if self.position >= messages.len() {
+            self.position = messages.len();
+            self.playing = false;
+        }
+    }
+
+    pub fn toggle_playing(&mut self, len: usize) {
+        if !self.playing && self.position >= len {
+            // Start over once the end was reached
+            self.seek(1, len);
+        }
+        self.playing = !self.playing;
+        self.last_tick = Instant::now();
+    }
+
+    pub fn seek(&mut self, position: usize, len: usize) {
+        self.position = position.clamp(1.min(len), len);
+        self.waited = Duration::ZERO;
+    }
+
+    /// Move a few percent of the session forward (or back)
+    pub fn step(&mut self, forward: bool, len: usize) {
+        let step = (len * SEEK_STEP_PERCENT / 100).max(1);
+        let position = if forward {
+            self.position + step
+        } else {
+            self.position.saturating_sub(step)
+        };
+        self.seek(position, len);
+    }
+
+    /// Show everything up to and including the next prompt
+    pub fn next_turn(&mut self, messages: &[SessionMessage]) {
+        let next = messages[self.position.min(messages.len())..]
+            .iter()
+            .position(|m| m.is_prompt())
+            .map_or(messages.len(), |i| self.position + i + 1);
+        self.seek(next, messages.len());
+    }
+
+    /// Go back to the prompt before the last one shown
+    pub fn previous_turn(&mut self, messages: &[SessionMessage]) {
+        let shown = &messages[..self.position.saturating_sub(1).min(messages.len())];
+        let previous = shown
+            .iter()
+            .rposition(|m| m.is_prompt())
+            .map_or(1, |i| i + 1);
+        self.seek(previous, messages.len());
+    }
+
+    /// Share of the session revealed, 0.0 to 1.0
+    pub fn progress(&self, len: usize) -> f64 {
+        if len == 0 {
+            return 1.0;
+        }
+        self.position as f64 / len as f64
+    }
+}
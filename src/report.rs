@@ -0,0 +1,960 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, TimeDelta, Utc};
+use clap::ValueEnum;
+
+use crate::config::Config;
+use crate::pricing::{Currency, PriceTable};
+use crate::session::{self, IgnoreList, Session, SlugNames};
+use crate::state::ClaudyState;
+use crate::timezone::DisplayZone;
+use crate::ui::{format_bytes, format_tokens, truncate_width};
+use crate::usage::UsageSnapshot;
+
+/// Width of the model/project column in billing reports
+const GROUP_WIDTH: usize = 32;
+
+/// Metric used to rank sessions in the largest-sessions report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeMetric {
+    FileSize,
+    Tokens,
+}
+
+impl SizeMetric {
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeMetric::FileSize => "file size",
+            SizeMetric::Tokens => "tokens",
+        }
+    }
+
+    pub fn value(self, session: &Session) -> u64 {
+        match self {
+            SizeMetric::FileSize => session.file_size(),
+            SizeMetric::Tokens => session.total_tokens(),
+        }
+    }
+
+    pub fn format(self, value: u64) -> String {
+        match self {
+            SizeMetric::FileSize => format_bytes(value),
+            SizeMetric::Tokens => format_tokens(value),
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            SizeMetric::FileSize => SizeMetric::Tokens,
+            SizeMetric::Tokens => SizeMetric::FileSize,
+        }
+    }
+}
+
+/// Session ids ranked by the given metric, largest first
+pub fn largest_session_ids(
+    sessions: &HashMap<String, Session>,
+    metric: SizeMetric,
+    top: usize,
+) -> Vec<String> {
+    let mut ranked: Vec<&Session> = sessions.values().collect();
+    ranked.sort_by(|a, b| {
+        metric
+            .value(b)
+            .cmp(&metric.value(a))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    ranked.into_iter().take(top).map(|s| s.id.clone()).collect()
+}
+
+/// Print the `claudy du` report: top sessions by file size and by tokens
+pub fn print_du(
+    base_path: &Path,
+    top: usize,
+    aliases: &HashMap<String, String>,
+    ignore: &IgnoreList,
+) -> Result<()> {
+    let mut sessions = session::discover_sessions(base_path, ignore)?;
+    let slug_names = SlugNames::decode_now(sessions.values());
+    sessions
+        .values_mut()
+        .for_each(|s| s.apply_aliases(aliases, &slug_names));
+    let total_size: u64 = sessions.values().map(|s| s.file_size()).sum();
+    println!(
+        "{} sessions, {} on disk in {}",
+        sessions.len(),
+        format_bytes(total_size),
+        base_path.display()
+    );
+
+    for metric in [SizeMetric::FileSize, SizeMetric::Tokens] {
+        println!();
+        println!("Top {} by {}:", top, metric.label());
+        for (rank, id) in largest_session_ids(&sessions, metric, top)
+            .iter()
+            .enumerate()
+        {
+            let s = &sessions[id];
+            println!(
+                "{:>3}. {:>9}  {}  [{}]",
+                rank + 1,
+                metric.format(metric.value(s)),
+                s.display_name(),
+                s.project_name()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Calendar bucket used by billing-period reports
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Month,
+    Week,
+}
+
+impl Period {
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Month => "month",
+            Period::Week => "week",
+        }
+    }
+
+    /// Bucket for a timestamp in `zone`, e.g. `2026-10` or `2026-W42`
+    pub fn key(self, timestamp: DateTime<Utc>, zone: &DisplayZone) -> String {
+        let local = zone.convert(timestamp);
+        match self {
+            Period::Month => local.format("%Y-%m").to_string(),
+            Period::Week => {
+                let week = local.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+        }
+    }
+
+    /// Bucket of the period before the one `now` falls in
+    pub fn previous_key(self, now: DateTime<Utc>, zone: &DisplayZone) -> String {
+        match self {
+            Period::Month => {
+                let local = zone.day(now);
+                let last_month = local
+                    .with_day(1)
+                    .and_then(|first| first.pred_opt())
+                    .unwrap_or(local);
+                last_month.format("%Y-%m").to_string()
+            }
+            Period::Week => self.key(now - TimeDelta::weeks(1), zone),
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            Period::Month => Period::Week,
+            Period::Week => Period::Month,
+        }
+    }
+}
+
+/// What each billing period is further split by
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Breakdown {
+    /// One row per period
+    Total,
+    Model,
+    Project,
+}
+
+impl Breakdown {
+    pub fn label(self) -> &'static str {
+        match self {
+            Breakdown::Total => "total",
+            Breakdown::Model => "model",
+            Breakdown::Project => "project",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Breakdown::Total => Breakdown::Model,
+            Breakdown::Model => Breakdown::Project,
+            Breakdown::Project => Breakdown::Total,
+        }
+    }
+}
+
+/// Usage in one period, or one model/project within it
+#[derive(Debug, Clone, Default)]
+pub struct BillingRow {
+    pub period: String,
+    /// Model or project name when broken down
+    pub group: Option<String>,
+    pub tokens_in: u64,
+    pub tokens_out: u64,
+    /// Estimated USD cost of the priced tokens
+    pub cost: f64,
+    /// Tokens from models without a known price, left out of `cost`
+    pub unpriced_tokens: u64,
+}
+
+impl BillingRow {
+    /// Fixed-width table header matching `format`
+    pub fn header(period: Period, breakdown: Breakdown) -> String {
+        let group = match breakdown {
+            Breakdown::Total => String::new(),
+            b => format!("{:<width$}  ", capitalize(b.label()), width = GROUP_WIDTH),
+        };
+        format!(
+            "{:<8}  {}{:>10}  {:>10}  {:>11}",
+            capitalize(period.label()),
+            group,
+            "Tokens in",
+            "Tokens out",
+            "Cost"
+        )
+    }
+
+    /// The row as a fixed-width table line; `*` marks partly unpriced costs
+    pub fn format(&self, currency: &Currency) -> String {
+        let group = self.group.as_deref().map_or(String::new(), |g| {
+            format!(
+                "{:<width$}  ",
+                truncate_width(g, GROUP_WIDTH),
+                width = GROUP_WIDTH
+            )
+        });
+        let marker = if self.unpriced_tokens > 0 { "*" } else { " " };
+        format!(
+            "{:<8}  {}{:>10}  {:>10}  {:>10}{}",
+            self.period,
+            group,
+            format_tokens(self.tokens_in),
+            format_tokens(self.tokens_out),
+            currency.format(self.cost),
+            marker
+        )
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+}
+
+/// Token usage and estimated cost bucketed by period in `zone`, most
+/// recent period first and the costliest groups first within a period
+pub fn billing_rows(
+    sessions: &HashMap<String, Session>,
+    prices: &PriceTable,
+    zone: &DisplayZone,
+    period: Period,
+    breakdown: Breakdown,
+) -> Vec<BillingRow> {
+    let mut rows: HashMap<(String, Option<String>), BillingRow> = HashMap::new();
+    for session in sessions.values() {
+        for point in &session.usage {
+            let group = match breakdown {
+                Breakdown::Total => None,
+                Breakdown::Model => Some(point.model.as_deref().unwrap_or("unknown").to_string()),
+                Breakdown::Project => Some(session.project_name().to_string()),
+            };
+            let key = (period.key(point.timestamp, zone), group);
+            let row = rows.entry(key.clone()).or_insert_with(|| BillingRow {
+                period: key.0,
+                group: key.1,
+                ..BillingRow::default()
+            });
+            row.tokens_in += point.usage.tokens_in();
+            row.tokens_out += point.usage.output;
+            match prices.billed_cost(point.model.as_deref(), &point.usage) {
+                Some(cost) => row.cost += cost,
+                None => row.unpriced_tokens += point.usage.total(),
+            }
+        }
+    }
+    let mut rows: Vec<BillingRow> = rows.into_values().collect();
+    rows.sort_by(|a, b| {
+        b.period
+            .cmp(&a.period)
+            .then(b.cost.total_cmp(&a.cost))
+            .then(b.tokens_in.cmp(&a.tokens_in))
+            .then_with(|| a.group.cmp(&b.group))
+    });
+    rows
+}
+
+/// Print the `claudy stats` report: usage and estimated cost per period
+pub fn print_stats(
+    base_path: &Path,
+    prices: &PriceTable,
+    config: &Config,
+    zone: &DisplayZone,
+    period: Period,
+    breakdown: Breakdown,
+) -> Result<()> {
+    let currency = &config.currency;
+    let mut sessions = session::discover_sessions(base_path, &config.ignore_projects)?;
+    let slug_names = SlugNames::decode_now(sessions.values());
+    sessions
+        .values_mut()
+        .for_each(|s| s.apply_aliases(&config.project_aliases, &slug_names));
+    let rows = billing_rows(&sessions, prices, zone, period, breakdown);
+    println!(
+        "Usage by {} (estimated cost in {})",
+        period.label(),
+        currency.code
+    );
+    println!();
+    println!("{}", BillingRow::header(period, breakdown));
+    for row in &rows {
+        println!("{}", row.format(currency));
+    }
+    if rows.iter().any(|r| r.unpriced_tokens > 0) {
+        println!();
+        println!("* includes tokens from models without a known price");
+    }
+    Ok(())
+}
+
+/// Span the project leaderboard ranks usage over; Tab cycles through them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RankWindow {
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl RankWindow {
+    pub fn label(self) -> &'static str {
+        match self {
+            RankWindow::Day => "24h",
+            RankWindow::Week => "7 days",
+            RankWindow::Month => "30 days",
+            RankWindow::All => "all time",
+        }
+    }
+
+    /// Start of the window ending at `now`; None for all time
+    pub fn since(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            RankWindow::Day => Some(now - TimeDelta::days(1)),
+            RankWindow::Week => Some(now - TimeDelta::days(7)),
+            RankWindow::Month => Some(now - TimeDelta::days(30)),
+            RankWindow::All => None,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            RankWindow::Day => RankWindow::Week,
+            RankWindow::Week => RankWindow::Month,
+            RankWindow::Month => RankWindow::All,
+            RankWindow::All => RankWindow::Day,
+        }
+    }
+}
+
+/// Tokens and estimated cost of one session, or a project's sessions,
+/// within a leaderboard window
+#[derive(Debug, Clone, Default)]
+pub struct Spend {
+    pub tokens: u64,
+    /// Estimated USD cost of the priced tokens
+    pub cost: f64,
+    /// Tokens from models without a known price, left out of `cost`
+    pub unpriced_tokens: u64,
+}
+
+impl Spend {
+    /// Usage of `session`'s replies since `since` (all of them for None);
+    /// None when it has none in the window
+    fn of(session: &Session, prices: &PriceTable, since: Option<DateTime<Utc>>) -> Option<Self> {
+        let points: Vec<_> = session
+            .usage
+            .iter()
+            .filter(|p| since.is_none_or(|t| p.timestamp >= t))
+            .collect();
+        if points.is_empty() {
+            return None;
+        }
+        let (cost, unpriced_tokens) = prices.total_cost(points.iter().copied());
+        Some(Spend {
+            tokens: points.iter().map(|p| p.tokens()).sum(),
+            cost,
+            unpriced_tokens,
+        })
+    }
+
+    fn add(&mut self, other: &Spend) {
+        self.tokens += other.tokens;
+        self.cost += other.cost;
+        self.unpriced_tokens += other.unpriced_tokens;
+    }
+
+    /// Costliest first, then most tokens
+    fn rank(a: &Spend, b: &Spend) -> std::cmp::Ordering {
+        b.cost.total_cmp(&a.cost).then(b.tokens.cmp(&a.tokens))
+    }
+
+    /// Tokens and cost columns, with `share` of the total cost in percent;
+    /// `*` marks partly unpriced costs
+    pub fn format(&self, currency: &Currency, share: f64) -> String {
+        let marker = if self.unpriced_tokens > 0 { "*" } else { " " };
+        format!(
+            "{:>10}  {:>10}{}  {:>5.1}%",
+            format_tokens(self.tokens),
+            currency.format(self.cost),
+            marker,
+            share
+        )
+    }
+}
+
+/// One project's sessions and usage on the leaderboard
+#[derive(Debug, Clone)]
+pub struct ProjectSummary {
+    pub name: String,
+    /// Sessions with replies in the window
+    pub sessions: usize,
+    pub spend: Spend,
+    pub last_active: DateTime<Utc>,
+}
+
+impl ProjectSummary {
+    /// Fixed-width table header matching `format`
+    pub fn header() -> String {
+        format!(
+            "{:>3}  {:<width$}  {:>8}  {:>10}  {:>11}  {:>6}",
+            "#",
+            "Project",
+            "Sessions",
+            "Tokens",
+            "Cost",
+            "Share",
+            width = GROUP_WIDTH
+        )
+    }
+
+    /// The row as a fixed-width table line, ranked `rank` with `share` of
+    /// the total cost
+    pub fn format(&self, rank: usize, currency: &Currency, share: f64) -> String {
+        format!(
+            "{:>3}  {:<width$}  {:>8}  {}",
+            rank,
+            truncate_width(&self.name, GROUP_WIDTH),
+            self.sessions,
+            self.spend.format(currency, share),
+            width = GROUP_WIDTH
+        )
+    }
+}
+
+/// Projects by their sessions' usage since `since`, the costliest first;
+/// projects without replies in the window are left out
+pub fn project_summaries(
+    sessions: &HashMap<String, Session>,
+    prices: &PriceTable,
+    since: Option<DateTime<Utc>>,
+) -> Vec<ProjectSummary> {
+    let mut projects: HashMap<&str, ProjectSummary> = HashMap::new();
+    for session in sessions.values() {
+        let Some(spend) = Spend::of(session, prices, since) else {
+            continue;
+        };
+        let project = projects
+            .entry(session.project_name())
+            .or_insert_with(|| ProjectSummary {
+                name: session.project_name().to_string(),
+                sessions: 0,
+                spend: Spend::default(),
+                last_active: session.last_activity,
+            });
+        project.sessions += 1;
+        project.spend.add(&spend);
+        project.last_active = project.last_active.max(session.last_activity);
+    }
+    let mut projects: Vec<ProjectSummary> = projects.into_values().collect();
+    projects.sort_by(|a, b| Spend::rank(&a.spend, &b.spend).then_with(|| a.name.cmp(&b.name)));
+    projects
+}
+
+/// Ids of `project`'s sessions with their usage since `since`, the
+/// costliest first
+pub fn project_sessions(
+    sessions: &HashMap<String, Session>,
+    prices: &PriceTable,
+    project: &str,
+    since: Option<DateTime<Utc>>,
+) -> Vec<(String, Spend)> {
+    let mut ranked: Vec<(String, Spend)> = sessions
+        .values()
+        .filter(|s| s.project_name() == project)
+        .filter_map(|s| Some((s.id.clone(), Spend::of(s, prices, since)?)))
+        .collect();
+    ranked.sort_by(|a, b| Spend::rank(&a.1, &b.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+/// The two sets of usage a delta report compares, "A" and "B"
+#[derive(Debug, Clone)]
+pub enum DeltaSides {
+    /// The previous period against the current one
+    Periods(Period),
+    /// Sessions with the first tag against sessions with the second
+    Tags(String, String),
+}
+
+impl DeltaSides {
+    /// Names of side A and side B, periods as `zone` has them
+    pub fn labels(&self, now: DateTime<Utc>, zone: &DisplayZone) -> (String, String) {
+        match self {
+            DeltaSides::Periods(period) => (period.previous_key(now, zone), period.key(now, zone)),
+            DeltaSides::Tags(a, b) => (format!("#{}", a), format!("#{}", b)),
+        }
+    }
+}
+
+/// Usage on one side of a delta row
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeltaUsage {
+    pub tokens: u64,
+    pub cost: f64,
+    pub unpriced_tokens: u64,
+}
+
+/// One project or model (or the total) on both sides of a delta report
+#[derive(Debug, Clone, Default)]
+pub struct DeltaRow {
+    pub group: String,
+    pub a: DeltaUsage,
+    pub b: DeltaUsage,
+}
+
+/// Change from `a` to `b` as a percentage, "new" when `a` was zero
+fn percent_change(a: f64, b: f64) -> String {
+    if a == 0.0 {
+        if b == 0.0 { "-" } else { "new" }.to_string()
+    } else {
+        format!("{:+.0}%", (b - a) / a * 100.0)
+    }
+}
+
+impl DeltaRow {
+    /// Fixed-width table header matching `format`
+    pub fn header() -> String {
+        format!(
+            "{:<width$}  {:>9}  {:>9}  {:>9}  {:>6}  {:>10}  {:>10}  {:>11}  {:>6}",
+            "",
+            "A tokens",
+            "B tokens",
+            "Δ tokens",
+            "Δ%",
+            "A cost",
+            "B cost",
+            "Δ cost",
+            "Δ%",
+            width = GROUP_WIDTH
+        )
+    }
+
+    /// The row as a fixed-width table line; `*` marks partly unpriced costs
+    pub fn format(&self, currency: &Currency) -> String {
+        let (a, b) = (self.a, self.b);
+        let tokens_delta = if b.tokens >= a.tokens {
+            format!("+{}", format_tokens(b.tokens - a.tokens))
+        } else {
+            format!("-{}", format_tokens(a.tokens - b.tokens))
+        };
+        let sign = if b.cost >= a.cost { "+" } else { "-" };
+        let marker = if a.unpriced_tokens + b.unpriced_tokens > 0 {
+            "*"
+        } else {
+            " "
+        };
+        format!(
+            "{:<width$}  {:>9}  {:>9}  {:>9}  {:>6}  {:>10}  {:>10}  {:>10}{}  {:>6}",
+            truncate_width(&self.group, GROUP_WIDTH),
+            format_tokens(a.tokens),
+            format_tokens(b.tokens),
+            tokens_delta,
+            percent_change(a.tokens as f64, b.tokens as f64),
+            currency.format(a.cost),
+            currency.format(b.cost),
+            format!("{}{}", sign, currency.format((b.cost - a.cost).abs())),
+            marker,
+            percent_change(a.cost, b.cost),
+            width = GROUP_WIDTH
+        )
+    }
+}
+
+/// Usage on both sides of `sides` per project or model (the biggest cost
+/// change first), followed by the total
+pub fn delta_rows(
+    sessions: &HashMap<String, Session>,
+    prices: &PriceTable,
+    zone: &DisplayZone,
+    sides: &DeltaSides,
+    breakdown: Breakdown,
+    now: DateTime<Utc>,
+) -> Vec<DeltaRow> {
+    let (key_a, key_b) = sides.labels(now, zone);
+    let mut rows: HashMap<String, DeltaRow> = HashMap::new();
+    let mut total = DeltaRow {
+        group: "Total".to_string(),
+        ..DeltaRow::default()
+    };
+    for session in sessions.values() {
+        let has_tag = |tag: &str| session.tags.iter().any(|t| t == tag);
+        for point in &session.usage {
+            let (in_a, in_b) = match sides {
+                DeltaSides::Periods(period) => {
+                    let key = period.key(point.timestamp, zone);
+                    (key == key_a, key == key_b)
+                }
+                DeltaSides::Tags(a, b) => (has_tag(a), has_tag(b)),
+            };
+            if !in_a && !in_b {
+                continue;
+            }
+            let group = match breakdown {
+                Breakdown::Total => None,
+                Breakdown::Model => Some(point.model.as_deref().unwrap_or("unknown").to_string()),
+                Breakdown::Project => Some(session.project_name().to_string()),
+            };
+            let cost = prices.billed_cost(point.model.as_deref(), &point.usage);
+            let add = |usage: &mut DeltaUsage| {
+                usage.tokens += point.usage.total();
+                match cost {
+                    Some(cost) => usage.cost += cost,
+                    None => usage.unpriced_tokens += point.usage.total(),
+                }
+            };
+            let record = |row: &mut DeltaRow| {
+                if in_a {
+                    add(&mut row.a);
+                }
+                if in_b {
+                    add(&mut row.b);
+                }
+            };
+            record(&mut total);
+            if let Some(group) = group {
+                record(rows.entry(group.clone()).or_insert_with(|| DeltaRow {
+                    group,
+                    ..DeltaRow::default()
+                }));
+            }
+        }
+    }
+    let mut rows: Vec<DeltaRow> = rows.into_values().collect();
+    rows.sort_by(|x, y| {
+        let change = |r: &DeltaRow| (r.b.cost - r.a.cost).abs();
+        change(y)
+            .total_cmp(&change(x))
+            .then_with(|| x.group.cmp(&y.group))
+    });
+    rows.push(total);
+    rows
+}
+
+/// Print the `claudy delta` report: usage of two periods or two tagged
+/// session sets side by side
+pub fn print_delta(
+    base_path: &Path,
+    prices: &PriceTable,
+    config: &Config,
+    zone: &DisplayZone,
+    sides: &DeltaSides,
+    breakdown: Breakdown,
+) -> Result<()> {
+    let currency = &config.currency;
+    let mut sessions = session::discover_sessions(base_path, &config.ignore_projects)?;
+    let state = ClaudyState::load();
+    let slug_names = SlugNames::decode_now(sessions.values());
+    for session in sessions.values_mut() {
+        state.apply(session);
+        session.apply_aliases(&config.project_aliases, &slug_names);
+    }
+    let now = Utc::now();
+    let (a, b) = sides.labels(now, zone);
+    let rows = delta_rows(&sessions, prices, zone, sides, breakdown, now);
+    let note = match sides {
+        DeltaSides::Periods(_) => " (so far)",
+        DeltaSides::Tags(..) => "",
+    };
+    println!(
+        "Usage delta, A: {}  B: {}{} (estimated cost in {})",
+        a, b, note, currency.code
+    );
+    println!();
+    println!("{}", DeltaRow::header());
+    for row in &rows {
+        println!("{}", row.format(currency));
+    }
+    if rows
+        .iter()
+        .any(|r| r.a.unpriced_tokens + r.b.unpriced_tokens > 0)
+    {
+        println!();
+        println!("* includes tokens from models without a known price");
+    }
+    Ok(())
+}
+
+/// Output style of `claudy status`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum StatusFormat {
+    /// `●3 ◌12 42K tok`
+    Plain,
+    /// The same with tmux `#[fg=...]` colors
+    Tmux,
+    /// JSON for a Waybar custom module, with per-session tooltip lines
+    Waybar,
+}
+
+/// Session counts and today's tokens, cheap enough to run from a prompt
+pub struct StatusSummary {
+    pub active: usize,
+    pub waiting: usize,
+    pub total: usize,
+    pub tokens_today: u64,
+    /// Sessions written today, most recent first
+    pub today: Vec<Session>,
+    /// Zone "today" and the tooltip's times are in
+    zone: DisplayZone,
+}
+
+impl StatusSummary {
+    /// Count every transcript but parse only the ones written today in
+    /// `zone`
+    pub fn collect(
+        base_path: &Path,
+        prices: &PriceTable,
+        ignore: &IgnoreList,
+        zone: &DisplayZone,
+    ) -> Result<Self> {
+        let files = session::transcript_files(base_path, ignore)?;
+        let now = Utc::now();
+        let midnight = SystemTime::from(zone.day_start(zone.day(now)));
+        let mut today = HashMap::new();
+        for (path, _) in files.iter().filter(|(_, mtime)| *mtime >= midnight) {
+            match session::discover_single_session(path) {
+                Ok(Some(s)) => {
+                    today.insert(s.id.clone(), s);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "failed to read session")
+                }
+            }
+        }
+        let usage = UsageSnapshot::compute(&today, prices, zone, now);
+        let mut today: Vec<Session> = today.into_values().collect();
+        today.sort_by_key(|s| std::cmp::Reverse(s.last_activity));
+        Ok(Self {
+            active: today.iter().filter(|s| s.is_active()).count(),
+            waiting: today.iter().filter(|s| s.awaiting_user()).count(),
+            total: files.len(),
+            tokens_today: usage.tokens_today,
+            today,
+            zone: zone.clone(),
+        })
+    }
+
+    /// `{"text", "tooltip", "class"}` as Waybar custom modules read it; the
+    /// class (`waiting`, `active` or `idle`) is there for CSS
+    fn waybar(&self) -> String {
+        let class = if self.waiting > 0 {
+            "waiting"
+        } else if self.active > 0 {
+            "active"
+        } else {
+            "idle"
+        };
+        let mut tooltip = vec![format!(
+            "{} active, {} waiting, {} sessions · {} tokens today",
+            self.active,
+            self.waiting,
+            self.total,
+            format_tokens(self.tokens_today)
+        )];
+        for session in &self.today {
+            let marker = if session.awaiting_user() {
+                "⚑"
+            } else if session.is_active() {
+                "●"
+            } else {
+                "○"
+            };
+            tooltip.push(format!(
+                "{} {}  {}  {} tok  {}",
+                marker,
+                truncate_width(&session.display_name(), 40),
+                session.project_name(),
+                format_tokens(session.total_tokens()),
+                self.zone.convert(session.last_activity).format("%H:%M")
+            ));
+        }
+        // Waybar renders tooltips as Pango markup
+        let tooltip = tooltip
+            .join("\n")
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        serde_json::json!({
+            "text": self.format(StatusFormat::Plain),
+            "tooltip": tooltip,
+            "class": class,
+        })
+        .to_string()
+    }
+
+    pub fn format(&self, format: StatusFormat) -> String {
+        let waiting = if self.waiting > 0 {
+            format!("⚑{} ", self.waiting)
+        } else {
+            String::new()
+        };
+        let active = format!("●{}", self.active);
+        let total = format!("◌{}", self.total);
+        let tokens = format!("{} tok", format_tokens(self.tokens_today));
+        match format {
+            StatusFormat::Plain => format!("{}{} {} {}", waiting, active, total, tokens),
+            StatusFormat::Waybar => self.waybar(),
+            StatusFormat::Tmux => format!(
+                "#[fg=yellow,bold]{}#[default]#[fg={}]{}#[default] #[fg=colour244]{}#[default] #[fg=cyan]{}#[default]",
+                waiting,
+                if self.active > 0 {
+                    "green"
+                } else {
+                    "colour244"
+                },
+                active,
+                total,
+                tokens
+            ),
+        }
+    }
+}
+
+/// Print the `claudy status` one-liner
+pub fn print_status(
+    base_path: &Path,
+    prices: &PriceTable,
+    format: StatusFormat,
+    ignore: &IgnoreList,
+    zone: &DisplayZone,
+) -> Result<()> {
+    println!(
+        "{}",
+        StatusSummary::collect(base_path, prices, ignore, zone)?.format(format)
+    );
+    Ok(())
+}
+
+/// Print the effective price table in match order (`claudy pricing show`)
+pub fn print_prices(prices: &PriceTable) {
+    println!(
+        "{:<40}  {:>8}  {:>8}  {:>11}  {:>10}  Source",
+        "Model (substring)", "Input", "Output", "Cache write", "Cache read"
+    );
+    for (pattern, price, source) in prices.entries() {
+        println!(
+            "{:<40}  {:>8.2}  {:>8.2}  {:>11.2}  {:>10.2}  {}",
+            truncate_width(pattern, 40),
+            price.input,
+            price.output,
+            price.cache_write,
+            price.cache_read,
+            source.label()
+        );
+    }
+    println!();
+    println!("USD per million tokens; the first matching row applies");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Session `id` of the project in `cwd`, with one reply of `output`
+    /// tokens per entry of `hours_ago`
+    fn session(base: &Path, id: &str, cwd: &str, replies: &[(i64, u64)]) -> Session {
+        let now = Utc::now();
+        let lines: Vec<String> = replies
+            .iter()
+            .enumerate()
+            .map(|(i, (hours_ago, output))| {
+                format!(
+                    r#"{{"type":"assistant","sessionId":"{}","cwd":"{}","timestamp":"{}","message":{{"id":"m{}","model":"claude-sonnet-4-5","content":[{{"type":"text","text":"ok"}}],"usage":{{"input_tokens":0,"output_tokens":{}}}}}}}"#,
+                    id,
+                    cwd,
+                    (now - TimeDelta::hours(*hours_ago)).to_rfc3339(),
+                    i,
+                    output
+                )
+            })
+            .collect();
+        let project = base.join(cwd.replace('/', "-"));
+        fs::create_dir_all(&project).unwrap();
+        let path = project.join(format!("{}.jsonl", id));
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        session::discover_single_session(&path).unwrap().unwrap()
+    }
+
+    #[test]
+    fn leaderboard_ranks_projects_within_the_window() {
+        let base = tempfile::tempdir().unwrap();
+        let base = base.path();
+        let sessions: HashMap<String, Session> = [
+            session(base, "a1", "/src/app", &[(2, 1000), (24 * 10, 50_000)]),
+            session(base, "a2", "/src/app", &[(24 * 20, 1000)]),
+            session(base, "l1", "/src/lib", &[(3, 4000)]),
+        ]
+        .into_iter()
+        .map(|s| (s.id.clone(), s))
+        .collect();
+        let prices = PriceTable::default();
+        let now = Utc::now();
+
+        let day = project_summaries(&sessions, &prices, RankWindow::Day.since(now));
+        let names: Vec<_> = day.iter().map(|p| (p.name.as_str(), p.sessions)).collect();
+        assert_eq!(names, [("lib", 1), ("app", 1)]);
+        assert_eq!(day[1].spend.tokens, 1000);
+
+        let month = project_summaries(&sessions, &prices, RankWindow::Month.since(now));
+        let names: Vec<_> = month
+            .iter()
+            .map(|p| (p.name.as_str(), p.sessions))
+            .collect();
+        assert_eq!(names, [("app", 2), ("lib", 1)]);
+        assert_eq!(month[0].spend.tokens, 52_000);
+
+        let drill = project_sessions(&sessions, &prices, "app", RankWindow::Week.since(now));
+        let ids: Vec<_> = drill.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["a1"]);
+    }
+
+    #[test]
+    fn rank_windows_cycle_through_all_time() {
+        let now = Utc::now();
+        assert_eq!(RankWindow::All.since(now), None);
+        assert_eq!(RankWindow::Week.since(now), Some(now - TimeDelta::days(7)));
+        let mut window = RankWindow::Day;
+        for _ in 0..4 {
+            window = window.next();
+        }
+        assert_eq!(window, RankWindow::Day);
+    }
+}
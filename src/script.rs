@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use rhai::{AST, Array, Dynamic, Engine, Scope};
+use serde::Deserialize;
+
+use crate::pricing::PriceTable;
+use crate::session::Session;
+
+/// Operations one evaluation may take, so a runaway script can't stall a frame
+const MAX_OPERATIONS: u64 = 50_000;
+
+/// A computed session list column (`[[script_columns]]` in config.toml),
+/// shown by listing its name in `session_columns`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ScriptColumn {
+    pub name: String,
+    /// Rhai expression evaluated against the session context
+    pub expr: String,
+    #[serde(default = "default_column_width")]
+    pub width: usize,
+}
+
+fn default_column_width() -> usize {
+    8
+}
+
+/// Rhai engine for `expr:` filter terms and script columns.
+///
+/// Expressions see one variable per session field:
+///
+/// | name            | type   | value                                        |
+/// |-----------------|--------|----------------------------------------------|
+/// | `id`            | string | session id                                   |
+/// | `name`          | string | display name                                 |
+/// | `project`       | string | project (last component of the cwd)          |
+/// | `branch`        | string | git branch, `""` if unknown                  |
+/// | `cwd`           | string | working directory, `""` if unknown           |
+/// | `summary`       | string | Claude's summary, `""` if none               |
+/// | `tags`          | array  | claudy tags                                  |
+/// | `messages`      | int    | message count, including unloaded history    |
+/// | `tokens_in`     | int    | input tokens, cache included                 |
+/// | `tokens_out`    | int    | output tokens                                |
+/// | `tokens`        | int    | `tokens_in + tokens_out`                     |
+/// | `cost`          | float  | estimated cost in USD                        |
+/// | `active`        | bool   | transcript written to in the last 5 minutes  |
+/// | `waiting`       | bool   | Claude is done and the user is up            |
+/// | `idle_mins`     | int    | minutes since the last activity              |
+/// | `duration_mins` | int    | minutes from the start to the last activity  |
+pub struct Scripts {
+    engine: Engine,
+    pub columns: Vec<ScriptColumn>,
+    /// Compiled column expressions by name; ones that failed are left out
+    compiled: HashMap<String, AST>,
+}
+
+impl Scripts {
+    pub fn new(columns: Vec<ScriptColumn>) -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+        let mut compiled = HashMap::new();
+        for column in &columns {
+            match engine.compile_expression(&column.expr) {
+                Ok(ast) => {
+                    compiled.insert(column.name.clone(), ast);
+                }
+                Err(e) => {
+                    tracing::warn!(column = %column.name, error = %e, "script column disabled")
+                }
+            }
+        }
+        Self {
+            engine,
+            columns,
+            compiled,
+        }
+    }
+
+    pub fn compile(&self, expr: &str) -> Result<AST> {
+        self.engine
+            .compile_expression(expr)
+            .map_err(|e| anyhow!("{}", e))
+    }
+
+    /// Whether `session` passes a filter expression; errors and results
+    /// that aren't a bool count as no match
+    pub fn matches(&self, ast: &AST, session: &Session, prices: &PriceTable) -> bool {
+        self.eval(ast, session, prices)
+            .is_some_and(|v| v.as_bool().unwrap_or(false))
+    }
+
+    /// Cell width of the script column called `name`, if there is one
+    pub fn column_width(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.width)
+    }
+
+    /// Value of the script column `name` for `session`; "err" if the
+    /// expression doesn't compile or fails
+    pub fn column(&self, name: &str, session: &Session, prices: &PriceTable) -> String {
+        let Some(value) = self
+            .compiled
+            .get(name)
+            .and_then(|ast| self.eval(ast, session, prices))
+        else {
+            return "err".to_string();
+        };
+        if let Ok(f) = value.as_float() {
+            format!("{:.2}", f)
+        } else if value.is_unit() {
+            String::new()
+        } else {
+            value.to_string()
+        }
+    }
+
+    fn eval(&self, ast: &AST, session: &Session, prices: &PriceTable) -> Option<Dynamic> {
+        let mut scope = session_scope(session, prices);
+        self.engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, ast)
+            .map_err(|e| tracing::debug!(session = %session.id, error = %e, "script failed"))
+            .ok()
+    }
+}
+
+/// The documented session context, as scope constants
+fn session_scope(session: &Session, prices: &PriceTable) -> Scope<'static> {
+    let (cost, _) = prices.total_cost(&session.usage);
+    let tags: Array = session.tags.iter().cloned().map(Dynamic::from).collect();
+    let idle = Utc::now() - session.last_activity;
+    let mut scope = Scope::new();
+    scope
+        .push_constant("id", session.id.clone())
+        .push_constant("name", session.display_name())
+        .push_constant("project", session.project_name().to_string())
+        .push_constant("branch", session.git_branch.clone().unwrap_or_default())
+        .push_constant("cwd", session.cwd.clone().unwrap_or_default())
+        .push_constant("summary", session.summary.clone().unwrap_or_default())
+        .push_constant("tags", tags)
+        .push_constant("messages", session.message_count() as i64)
+        .push_constant("tokens_in", session.total_tokens_in as i64)
+        .push_constant("tokens_out", session.total_tokens_out as i64)
+        .push_constant("tokens", session.total_tokens() as i64)
+        .push_constant("cost", cost)
+        .push_constant("active", session.is_active())
+        .push_constant("waiting", session.awaiting_user())
+        .push_constant("idle_mins", idle.num_minutes())
+        .push_constant(
+            "duration_mins",
+            session.duration().map_or(0, |d| d.num_minutes()),
+        );
+    scope
+}
@@ -1,42 +1,149 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, mpsc};
 use std::time::SystemTime;
 
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use anyhow::{Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::Deserialize;
 
-use crate::message::{self, SessionMessage};
+use crate::git::{self, Worktree};
+use crate::message::{self, LineScan, MessageType, SessionMessage, TokenUsage};
 
 /// Threshold in seconds for considering a session "active"
 const ACTIVE_THRESHOLD_SECS: u64 = 300; // 5 minutes
+/// Quiet time after Claude's last entry before a session counts as
+/// waiting for the user, so replies mid-turn don't flicker the indicator
+const AWAITING_IDLE_SECS: u64 = 10;
+
+/// Most recent parse failures kept per session; older ones are only counted
+const MAX_PARSE_ERRORS: usize = 100;
+
+/// Messages materialized from the end of a transcript when it is first
+/// read, and per backfill step when scrolling toward the top
+pub const TAIL_MESSAGES: usize = 200;
+
+/// Bytes read per step when walking a transcript backwards
+const BACKWARD_CHUNK: u64 = 64 * 1024;
+
+/// A transcript line that could not be parsed
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    /// 1-based line number in the JSONL file
+    pub line: usize,
+    pub error: String,
+}
+
+/// Parse failures seen while reading a session's transcript
+#[derive(Debug, Clone, Default)]
+pub struct ParseDiagnostics {
+    /// Total failures, including ones dropped from `recent`
+    pub count: usize,
+    /// The failures on the last lines of the file, in line order
+    pub recent: VecDeque<ParseError>,
+}
+
+impl ParseDiagnostics {
+    /// Count a failure and keep it among `recent` unless the ones kept are
+    /// all on later lines; `backfill` reports lines older than those seen
+    fn record(&mut self, session_id: &str, line: usize, error: String) {
+        tracing::debug!(session = %session_id, line, error = %error, "unparseable line");
+        self.count += 1;
+        let at = self.recent.partition_point(|e| e.line < line);
+        if self.recent.len() == MAX_PARSE_ERRORS {
+            if at == 0 {
+                return;
+            }
+            self.recent.pop_front();
+            self.recent.insert(at - 1, ParseError { line, error });
+        } else {
+            self.recent.insert(at, ParseError { line, error });
+        }
+    }
+}
+
+/// Tokens reported by one assistant reply, for time-windowed totals
+#[derive(Debug, Clone)]
+pub struct UsagePoint {
+    pub timestamp: DateTime<Utc>,
+    /// Model that produced the reply; shared between consecutive points
+    pub model: Option<Arc<str>>,
+    pub usage: TokenUsage,
+}
+
+impl UsagePoint {
+    pub fn tokens(&self) -> u64 {
+        self.usage.total()
+    }
+}
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Session {
     pub id: String,
     pub project_slug: String,
+    /// Display name for the project from `project_aliases`, or decoded from
+    /// the slug while `cwd` is unknown (see `apply_aliases`)
+    pub project_alias: Option<String>,
     pub slug: Option<String>,
+    /// Title assigned in claudy itself (see `state.rs`)
+    pub user_title: Option<String>,
+    /// User-defined tags assigned in claudy
+    pub tags: Vec<String>,
     pub custom_title: Option<String>,
     pub summary: Option<String>,
     pub git_branch: Option<String>,
     pub cwd: Option<String>,
+    /// Linked git worktree `cwd` is in, found when `cwd` is first known
+    pub worktree: Option<Worktree>,
     pub messages: Vec<SessionMessage>,
     pub last_activity: DateTime<Utc>,
     pub file_offset: u64,
     pub file_path: PathBuf,
     pub total_tokens_in: u64,
     pub total_tokens_out: u64,
+    /// Token usage of every reply in the transcript (loaded or not), in
+    /// file order
+    pub usage: Vec<UsagePoint>,
+    /// Replies whose usage is counted, so the copies on their other
+    /// content block lines aren't
+    pub counted_replies: HashSet<String>,
+    /// Lines consumed so far, used to number lines read incrementally
+    pub lines_read: usize,
+    pub parse_diagnostics: ParseDiagnostics,
+    /// Length of the transcript prefix whose messages aren't loaded yet
+    /// (see `backfill`); 0 once everything is in `messages`
+    pub backfill_offset: u64,
+    /// Lines in that prefix
+    pub backfill_lines: usize,
+    /// Estimated messages in that prefix
+    pub unloaded_messages: usize,
+    /// API errors anywhere in the transcript, in file order
+    pub api_errors: Vec<ApiError>,
+    /// Replies per unusual `stop_reason` (`max_tokens`, `refusal`, ...)
+    pub stop_reasons: BTreeMap<String, usize>,
+    /// When Claude Code compacted the conversation, in file order
+    pub compactions: Vec<DateTime<Utc>>,
+}
+
+/// A failed request recorded in the transcript
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub timestamp: DateTime<Utc>,
+    /// Short name such as `overloaded_529`
+    pub label: String,
 }
 
 impl Session {
     pub fn display_name(&self) -> String {
-        // Priority: customTitle > slug > summary > short session id
+        // Priority: claudy title > customTitle > slug > summary > short session id
         let name = self
-            .custom_title
+            .user_title
             .as_deref()
+            .or(self.custom_title.as_deref())
             .or(self.slug.as_deref())
             .or(self.summary.as_deref())
             .unwrap_or(self.short_id());
@@ -51,12 +158,155 @@ impl Session {
         &self.id[..8.min(self.id.len())]
     }
 
-    pub fn is_active(&self) -> bool {
+    /// Seconds since the transcript was last written
+    fn idle_secs(&self) -> u64 {
         let mtime = fs::metadata(&self.file_path)
             .and_then(|m| m.modified())
             .unwrap_or(SystemTime::UNIX_EPOCH);
         let elapsed = SystemTime::now().duration_since(mtime).unwrap_or_default();
-        elapsed.as_secs() < ACTIVE_THRESHOLD_SECS
+        elapsed.as_secs()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.idle_secs() < ACTIVE_THRESHOLD_SECS
+    }
+
+    /// Active, quiet for a moment, and the last entry is Claude's: the turn
+    /// is over or a tool call waits for permission. A long-running tool
+    /// looks the same, so this is a hint rather than a certainty.
+    pub fn awaiting_user(&self) -> bool {
+        let last = self
+            .messages
+            .iter()
+            .rev()
+            .find(|m| !matches!(m.msg_type, MessageType::Progress | MessageType::Other));
+        if !last
+            .is_some_and(|m| matches!(m.msg_type, MessageType::Assistant | MessageType::ToolUse))
+        {
+            return false;
+        }
+        (AWAITING_IDLE_SECS..ACTIVE_THRESHOLD_SECS).contains(&self.idle_secs())
+    }
+
+    /// Size of the transcript on disk, as of the last read
+    pub fn file_size(&self) -> u64 {
+        self.file_offset
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.total_tokens_in + self.total_tokens_out
+    }
+
+    /// The project's alias, else the last component of the working
+    /// directory, else the project slug; sessions in linked worktrees count
+    /// towards their primary repository
+    pub fn project_name(&self) -> &str {
+        if let Some(ref alias) = self.project_alias {
+            return alias;
+        }
+        if let Some(ref worktree) = self.worktree {
+            return &worktree.repo;
+        }
+        self.cwd
+            .as_deref()
+            .and_then(|c| Path::new(c).file_name())
+            .and_then(|n| n.to_str())
+            .unwrap_or(&self.project_slug)
+    }
+
+    /// `project_name`, with the worktree as in `repo @ worktree`
+    pub fn project_label(&self) -> String {
+        match self.worktree {
+            Some(ref worktree) => format!("{} @ {}", self.project_name(), worktree.name),
+            None => self.project_name().to_string(),
+        }
+    }
+
+    /// Look up the project's display name in `aliases`, by slug and then by
+    /// name; without one, a session whose `cwd` isn't known yet is named
+    /// after the directory its slug was made from, once `slug_names` has it
+    pub fn apply_aliases(&mut self, aliases: &HashMap<String, String>, slug_names: &SlugNames) {
+        self.project_alias = None;
+        let alias = aliases
+            .get(&self.project_slug)
+            .or_else(|| aliases.get(self.project_name()))
+            .cloned();
+        self.project_alias = alias.or_else(|| {
+            self.cwd
+                .is_none()
+                .then(|| slug_names.get(&self.project_slug))
+                .flatten()
+                .map(str::to_string)
+        });
+    }
+
+    /// Time from the first message (or first reply, while the start of the
+    /// transcript is unloaded) to the last activity
+    pub fn duration(&self) -> Option<TimeDelta> {
+        let first_message = self
+            .messages
+            .first()
+            .filter(|_| !self.is_partial())
+            .map(|m| m.timestamp);
+        let first_reply = self.usage.first().map(|p| p.timestamp);
+        let start = match (first_message, first_reply) {
+            (Some(m), Some(r)) => m.min(r),
+            (m, r) => m.or(r)?,
+        };
+        Some(self.last_activity - start)
+    }
+
+    /// Add a scanned line's token usage to the totals and usage timeline,
+    /// and count its unusual stop, API error or compaction
+    fn record_usage(&mut self, scan: &LineScan) {
+        if scan.compaction {
+            self.compactions
+                .push(scan.timestamp.unwrap_or(self.last_activity));
+        }
+        if let Some(ref reason) = scan.stop_reason {
+            *self.stop_reasons.entry(reason.clone()).or_default() += 1;
+        }
+        if let Some(ref label) = scan.error {
+            self.api_errors.push(ApiError {
+                timestamp: scan.timestamp.unwrap_or(self.last_activity),
+                label: label.clone(),
+            });
+        }
+        let Some(usage) = scan.usage else {
+            return;
+        };
+        if let Some(ref id) = scan.reply_id
+            && !self.counted_replies.insert(id.clone())
+        {
+            return;
+        }
+        self.total_tokens_in += usage.tokens_in();
+        self.total_tokens_out += usage.output;
+        let Some(timestamp) = scan.timestamp else {
+            return;
+        };
+        // Reuse the previous point's model name rather than allocating per reply
+        let model = scan.model.as_deref().map(|name| {
+            match self.usage.last().and_then(|p| p.model.clone()) {
+                Some(prev) if &*prev == name => prev,
+                _ => Arc::from(name),
+            }
+        });
+        self.usage.push(UsagePoint {
+            timestamp,
+            model,
+            usage,
+        });
+    }
+
+    /// Messages in the whole transcript, counting ones not loaded yet
+    pub fn message_count(&self) -> usize {
+        self.messages.len() + self.unloaded_messages
+    }
+
+    /// Whether earlier messages are still waiting to be backfilled
+    pub fn is_partial(&self) -> bool {
+        self.backfill_offset > 0
     }
 }
 
@@ -94,8 +344,203 @@ fn load_sessions_index(project_dir: &Path) -> HashMap<String, IndexEntry> {
     map
 }
 
-/// Discover all sessions from ~/.claude/projects/
-pub fn discover_sessions(base_path: &Path) -> Result<HashMap<String, Session>> {
+/// The directory a project slug was made from. Claude Code replaces every
+/// character but ASCII letters and digits in the path with `-`, so which
+/// dash was which is settled against the filesystem; None once the
+/// directory is gone.
+fn decode_slug(slug: &str) -> Option<PathBuf> {
+    find_dir(Path::new("/"), slug.strip_prefix('-')?)
+}
+
+/// The directory under `base` whose path below it slugs to `rest`, trying
+/// the longest names first
+fn find_dir(base: &Path, rest: &str) -> Option<PathBuf> {
+    if rest.is_empty() {
+        return Some(base.to_path_buf());
+    }
+    let mut children: Vec<(String, PathBuf)> = fs::read_dir(base)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let slug = slug_name(entry.file_name().to_str()?);
+            let path = entry.path();
+            path.is_dir().then_some((slug, path))
+        })
+        .collect();
+    children.sort_by_key(|(slug, _)| Reverse(slug.len()));
+    children.into_iter().find_map(|(slug, dir)| {
+        let below = match rest.strip_prefix(slug.as_str())? {
+            "" => "",
+            below => below.strip_prefix('-').filter(|b| !b.is_empty())?,
+        };
+        find_dir(&dir, below)
+    })
+}
+
+/// A file name as it appears in a project slug
+fn slug_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+type DecodedSlug = (String, Option<String>);
+
+/// Directory names decoded from project slugs (see `decode_slug`), for
+/// sessions whose `cwd` isn't known yet. Decoding probes the filesystem,
+/// so it runs once per slug on a background thread.
+pub struct SlugNames {
+    tx: mpsc::Sender<DecodedSlug>,
+    rx: mpsc::Receiver<DecodedSlug>,
+    /// Name per slug; None while decoding or when the directory is gone
+    names: HashMap<String, Option<String>>,
+}
+
+impl SlugNames {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            tx,
+            rx,
+            names: HashMap::new(),
+        }
+    }
+
+    /// Decode, right away on this thread, the slugs `sessions` need; for
+    /// one-shot commands
+    pub fn decode_now<'a>(sessions: impl IntoIterator<Item = &'a Session>) -> Self {
+        let mut names = Self::new();
+        for slug in names.wanted(sessions) {
+            let name = decoded_name(&slug);
+            names.names.insert(slug, name);
+        }
+        names
+    }
+
+    /// Start decoding the slugs `sessions` need that weren't asked for before
+    pub fn request<'a>(&mut self, sessions: impl IntoIterator<Item = &'a Session>) {
+        let slugs = self.wanted(sessions);
+        if slugs.is_empty() {
+            return;
+        }
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            for slug in slugs {
+                let name = decoded_name(&slug);
+                // The receiver only goes away on exit
+                let _ = tx.send((slug, name));
+            }
+        });
+    }
+
+    /// Take in the names decoded since the last call; true if any were
+    /// found, so aliases need applying again
+    pub fn poll(&mut self) -> bool {
+        let mut any = false;
+        while let Ok((slug, name)) = self.rx.try_recv() {
+            any |= name.is_some();
+            self.names.insert(slug, name);
+        }
+        any
+    }
+
+    /// The name of the directory `slug` was made from, if decoded
+    pub fn get(&self, slug: &str) -> Option<&str> {
+        self.names.get(slug)?.as_deref()
+    }
+
+    /// Slugs of `sessions` without a `cwd` not decoded or under way yet,
+    /// marked as under way
+    fn wanted<'a>(&mut self, sessions: impl IntoIterator<Item = &'a Session>) -> Vec<String> {
+        let mut slugs = Vec::new();
+        for session in sessions.into_iter().filter(|s| s.cwd.is_none()) {
+            if !self.names.contains_key(&session.project_slug) {
+                self.names.insert(session.project_slug.clone(), None);
+                slugs.push(session.project_slug.clone());
+            }
+        }
+        slugs
+    }
+}
+
+fn decoded_name(slug: &str) -> Option<String> {
+    let dir = decode_slug(slug)?;
+    Some(dir.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Projects left out of discovery and watching (`ignore_projects`). Each
+/// entry is a project slug or a path glob; a path is matched as the slug it
+/// would give, so `*` in `~/scratch/*` also spans nested directories, and
+/// `~/scratch/**` means the same.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct IgnoreList(Vec<glob::Pattern>);
+
+impl TryFrom<Vec<String>> for IgnoreList {
+    type Error = glob::PatternError;
+
+    fn try_from(entries: Vec<String>) -> Result<Self, Self::Error> {
+        entries
+            .iter()
+            .map(|entry| {
+                let expanded = match (entry.strip_prefix("~/"), dirs::home_dir()) {
+                    (Some(rest), Some(home)) => home.join(rest).display().to_string(),
+                    _ => entry.clone(),
+                };
+                let slug = if expanded.contains('/') {
+                    slug_pattern(&expanded)
+                } else {
+                    expanded
+                };
+                glob::Pattern::new(&slug)
+            })
+            .collect::<Result<_, _>>()
+            .map(IgnoreList)
+    }
+}
+
+/// The slug glob a path glob gives. Claude Code names a project after its
+/// path with every character but letters and digits turned into `-`; the
+/// glob's own syntax is kept, and `**` becomes `*` since a slug has no
+/// separators left for it to span.
+fn slug_pattern(path: &str) -> String {
+    let mut pattern = String::with_capacity(path.len());
+    for c in path.chars() {
+        let keep = c.is_ascii_alphanumeric()
+            || matches!(c, '*' | '?' | '[' | ']')
+            || (c == '!' && pattern.ends_with('['));
+        pattern.push(if keep { c } else { '-' });
+    }
+    while pattern.contains("**") {
+        pattern = pattern.replace("**", "*");
+    }
+    pattern
+}
+
+impl IgnoreList {
+    /// Whether the project with this slug is ignored
+    pub fn ignores(&self, project_slug: &str) -> bool {
+        self.0.iter().any(|pattern| pattern.matches(project_slug))
+    }
+
+    /// Whether `path`, somewhere under one of `base_paths`, is in an
+    /// ignored project directory
+    pub fn ignores_path(&self, path: &Path, base_paths: &[PathBuf]) -> bool {
+        !self.0.is_empty()
+            && base_paths
+                .iter()
+                .filter_map(|base| path.strip_prefix(base).ok()?.components().next())
+                .any(|slug| self.ignores(&slug.as_os_str().to_string_lossy()))
+    }
+}
+
+/// Discover all sessions from ~/.claude/projects/, leaving out the
+/// projects `ignore` matches without reading them
+pub fn discover_sessions(
+    base_path: &Path,
+    ignore: &IgnoreList,
+) -> Result<HashMap<String, Session>> {
+    let started = std::time::Instant::now();
     let mut sessions = HashMap::new();
 
     if !base_path.exists() {
@@ -110,6 +555,10 @@ pub fn discover_sessions(base_path: &Path) -> Result<HashMap<String, Session>> {
         }
 
         let project_slug = project_entry.file_name().to_string_lossy().to_string();
+        if ignore.ignores(&project_slug) {
+            tracing::debug!(project = %project_slug, "project ignored");
+            continue;
+        }
 
         // Load sessions-index.json for this project
         let index = load_sessions_index(&project_path);
@@ -139,23 +588,38 @@ pub fn discover_sessions(base_path: &Path) -> Result<HashMap<String, Session>> {
                 Ok(session) => {
                     sessions.insert(session.id.clone(), session);
                 }
-                Err(_) => {
+                Err(e) => {
+                    tracing::warn!(path = %file_path.display(), error = %e, "failed to read session");
                     sessions.insert(
                         session_id.clone(),
                         Session {
                             id: session_id,
                             project_slug: project_slug.clone(),
+                            project_alias: None,
                             slug: None,
+                            user_title: None,
+                            tags: Vec::new(),
                             custom_title: index_entry.and_then(|e| e.custom_title.clone()),
                             summary: index_entry.and_then(|e| e.summary.clone()),
                             git_branch: index_entry.and_then(|e| e.git_branch.clone()),
                             cwd: index_entry.and_then(|e| e.project_path.clone()),
+                            worktree: None,
                             messages: Vec::new(),
                             last_activity: Utc::now(),
                             file_offset: 0,
                             file_path,
                             total_tokens_in: 0,
                             total_tokens_out: 0,
+                            usage: Vec::new(),
+                            lines_read: 0,
+                            parse_diagnostics: ParseDiagnostics::default(),
+                            backfill_offset: 0,
+                            backfill_lines: 0,
+                            unloaded_messages: 0,
+                            api_errors: Vec::new(),
+                            stop_reasons: BTreeMap::new(),
+                            compactions: Vec::new(),
+                            counted_replies: HashSet::new(),
                         },
                     );
                 }
@@ -163,17 +627,56 @@ pub fn discover_sessions(base_path: &Path) -> Result<HashMap<String, Session>> {
         }
     }
 
+    tracing::info!(
+        count = sessions.len(),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "discovered sessions"
+    );
     Ok(sessions)
 }
 
+/// Metadata gathered while reading a transcript front to back
+#[derive(Default)]
+struct MetaAccumulator {
+    git_branch: Option<String>,
+    cwd: Option<String>,
+    slug: Option<String>,
+    summary: Option<String>,
+    custom_title: Option<String>,
+}
+
+impl MetaAccumulator {
+    fn add(&mut self, meta: message::SessionMeta) {
+        if self.git_branch.is_none() {
+            self.git_branch = meta.git_branch;
+        }
+        if self.cwd.is_none() {
+            self.cwd = meta.cwd;
+        }
+        if self.slug.is_none() {
+            self.slug = meta.slug;
+        }
+        if self.summary.is_none() {
+            self.summary = meta.summary;
+        }
+        // custom_title: take the latest value (rename can happen at any point)
+        if meta.custom_title.is_some() {
+            self.custom_title = meta.custom_title;
+        }
+    }
+}
+
+/// Parse a transcript tail-first: the last `TAIL_MESSAGES` messages are
+/// materialized, while earlier lines are only scanned for metadata and
+/// token totals and left for `backfill`
 fn parse_session_file(
     file_path: &Path,
     project_slug: &str,
     index_entry: Option<&IndexEntry>,
 ) -> Result<Session> {
-    let file = fs::File::open(file_path)?;
+    let mut file = fs::File::open(file_path)?;
     let file_len = file.metadata()?.len();
-    let reader = BufReader::new(file);
+    let (tail_start, tail_lines) = read_lines_backwards(&mut file, file_len, TAIL_MESSAGES)?;
 
     let session_id = file_path
         .file_stem()
@@ -181,75 +684,229 @@ fn parse_session_file(
         .unwrap_or("unknown")
         .to_string();
 
-    let mut messages = Vec::new();
-    let mut git_branch = index_entry.and_then(|e| e.git_branch.clone());
-    let mut cwd = index_entry.and_then(|e| e.project_path.clone());
-    let mut slug: Option<String> = None;
-    let mut inline_summary: Option<String> = None;
-    let mut inline_custom_title: Option<String> = None;
-    let mut last_activity = Utc::now();
-    let mut total_tokens_in: u64 = 0;
-    let mut total_tokens_out: u64 = 0;
-    for line in reader.lines() {
-        let line = match line {
-            Ok(l) => l,
-            Err(_) => continue,
+    let mut session = Session {
+        id: session_id,
+        project_slug: project_slug.to_string(),
+        project_alias: None,
+        slug: None,
+        user_title: None,
+        tags: Vec::new(),
+        custom_title: None,
+        summary: None,
+        git_branch: None,
+        cwd: None,
+        worktree: None,
+        messages: Vec::new(),
+        last_activity: Utc::now(),
+        file_offset: file_len,
+        file_path: file_path.to_path_buf(),
+        total_tokens_in: 0,
+        total_tokens_out: 0,
+        usage: Vec::new(),
+        lines_read: 0,
+        parse_diagnostics: ParseDiagnostics::default(),
+        backfill_offset: tail_start,
+        backfill_lines: 0,
+        unloaded_messages: 0,
+        api_errors: Vec::new(),
+        stop_reasons: BTreeMap::new(),
+        compactions: Vec::new(),
+        counted_replies: HashSet::new(),
+    };
+    let mut meta = MetaAccumulator::default();
+
+    // Everything before the tail only feeds metadata and totals
+    file.seek(SeekFrom::Start(0))?;
+    for line in BufReader::new(file.take(tail_start)).lines() {
+        session.lines_read += 1;
+        let line_no = session.lines_read;
+        // Counted like the tail's; `backfill` leaves out what fails here
+        let scan = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => message::scan_line(&line).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
         };
-        if line.trim().is_empty() {
-            continue;
+        let scan = match scan {
+            Ok(scan) => scan,
+            Err(e) => {
+                session.parse_diagnostics.record(&session.id, line_no, e);
+                continue;
+            }
+        };
+        session.record_usage(&scan);
+        if scan.is_message {
+            session.unloaded_messages += 1;
+        }
+        if let Some(m) = scan.meta {
+            meta.add(m);
         }
+    }
+    session.backfill_lines = session.lines_read;
 
+    for line in tail_lines {
+        session.lines_read += 1;
         // Extract metadata from every line (custom_title can appear anywhere)
-        if let Some(meta) = message::extract_meta(&line) {
-            if git_branch.is_none() {
-                git_branch = meta.git_branch;
-            }
-            if cwd.is_none() {
-                cwd = meta.cwd;
-            }
-            if slug.is_none() {
-                slug = meta.slug;
+        let (scan, msg) = match line.parsed {
+            None => continue,
+            Some(Ok(parsed)) => parsed,
+            Some(Err(e)) => {
+                let line_no = session.lines_read;
+                session
+                    .parse_diagnostics
+                    .record(&session.id, line_no, e.to_string());
+                continue;
             }
-            if inline_summary.is_none() {
-                inline_summary = meta.summary;
+        };
+        session.record_usage(&scan);
+        if let Some(m) = scan.meta {
+            meta.add(m);
+        }
+        if let Some(msg) = msg {
+            session.last_activity = msg.timestamp;
+            session.messages.push(msg);
+        }
+    }
+
+    session.slug = meta.slug;
+    session.custom_title = index_entry
+        .and_then(|e| e.custom_title.clone())
+        .or(meta.custom_title);
+    session.summary = index_entry.and_then(|e| e.summary.clone()).or(meta.summary);
+    session.git_branch = index_entry
+        .and_then(|e| e.git_branch.clone())
+        .or(meta.git_branch);
+    session.cwd = index_entry
+        .and_then(|e| e.project_path.clone())
+        .or(meta.cwd);
+    session.worktree = session.cwd.as_deref().and_then(git::worktree);
+    Ok(session)
+}
+
+/// A whole line collected by `read_lines_backwards`, parsed once
+struct CollectedLine {
+    text: String,
+    /// What `parse_line_full` made of it; None for a blank line
+    parsed: Option<Result<(LineScan, Option<SessionMessage>), serde_json::Error>>,
+}
+
+impl CollectedLine {
+    fn parse(text: String) -> Self {
+        let parsed = (!text.trim().is_empty()).then(|| message::parse_line_full(&text));
+        Self { text, parsed }
+    }
+
+    fn is_message(&self) -> bool {
+        matches!(self.parsed, Some(Ok((_, Some(_)))))
+    }
+}
+
+/// Walk `file` backwards from byte `end`, collecting whole lines until
+/// `want` of them parse into messages (or the start of the file is reached).
+/// Returns the offset of the first collected line and the lines in file
+/// order, parsed, so callers never parse them again.
+fn read_lines_backwards(
+    file: &mut fs::File,
+    end: u64,
+    want: usize,
+) -> io::Result<(u64, Vec<CollectedLine>)> {
+    let mut lines = Vec::new();
+    let mut found = 0;
+    let mut pos = end;
+    // Bytes of the line straddling the current chunk boundary
+    let mut carry: Vec<u8> = Vec::new();
+    while pos > 0 && found < want {
+        // Grow the step with a long line so it isn't re-copied chunk by chunk
+        let start = pos.saturating_sub(BACKWARD_CHUNK.max(carry.len() as u64));
+        let mut buf = vec![0; (pos - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut buf)?;
+        buf.extend_from_slice(&carry);
+        pos = start;
+
+        // Every newline in the buffer completes the line that follows it
+        let mut line_end = buf.len();
+        while found < want {
+            let Some(nl) = buf[..line_end].iter().rposition(|&b| b == b'\n') else {
+                break;
+            };
+            // A trailing newline at EOF doesn't start another line
+            if !(pos + nl as u64 + 1 == end && lines.is_empty()) {
+                let line = CollectedLine::parse(decode_line(&buf[nl + 1..line_end]));
+                if line.is_message() {
+                    found += 1;
+                }
+                lines.push(line);
             }
-            // custom_title: take the latest value (rename can happen at any point)
-            if meta.custom_title.is_some() {
-                inline_custom_title = meta.custom_title;
+            line_end = nl;
+            if found == want {
+                lines.reverse();
+                return Ok((pos + nl as u64 + 1, lines));
             }
         }
+        buf.truncate(line_end);
+        carry = buf;
+    }
+    // Reached the start of the file: the carry is its first line
+    if pos == 0 && (!carry.is_empty() || end > 0) && found < want {
+        lines.push(CollectedLine::parse(decode_line(&carry)));
+    }
+    lines.reverse();
+    Ok((pos, lines))
+}
 
-        if let Some(msg) = message::parse_line(&line) {
-            last_activity = msg.timestamp;
-            if let Some(tin) = msg.tokens_in {
-                total_tokens_in += tin;
-            }
-            if let Some(tout) = msg.tokens_out {
-                total_tokens_out += tout;
+/// A raw line as `BufRead::lines` would yield it, minus the UTF-8 check
+fn decode_line(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Materialize up to `count` more messages from before the loaded ones.
+/// Returns how many messages were prepended to `session.messages`. Fails
+/// if the prefix no longer has the lines it was scanned with (the file was
+/// rewritten), in which case the session needs reading again.
+pub fn backfill(session: &mut Session, count: usize) -> Result<usize> {
+    if session.backfill_offset == 0 {
+        return Ok(0);
+    }
+    let mut file = fs::File::open(&session.file_path)?;
+    let (start, lines) = read_lines_backwards(&mut file, session.backfill_offset, count)?;
+
+    let Some(remaining_lines) = session.backfill_lines.checked_sub(lines.len()) else {
+        bail!("{} changed since it was read", session.file_path.display());
+    };
+    let mut estimated = 0;
+    let mut earlier = Vec::new();
+    for (i, line) in lines.into_iter().enumerate() {
+        // Metadata and token totals were taken when the prefix was scanned
+        match line.parsed {
+            None => {}
+            Some(Ok((scan, msg))) => {
+                if scan.is_message {
+                    estimated += 1;
+                }
+                earlier.extend(msg);
             }
-            messages.push(msg);
+            // Lines that don't even scan were counted with the prefix
+            Some(Err(_)) if message::scan_line(&line.text).is_err() => {}
+            Some(Err(e)) => session.parse_diagnostics.record(
+                &session.id,
+                remaining_lines + i + 1,
+                e.to_string(),
+            ),
         }
     }
 
-    Ok(Session {
-        id: session_id,
-        project_slug: project_slug.to_string(),
-        slug,
-        custom_title: index_entry
-            .and_then(|e| e.custom_title.clone())
-            .or(inline_custom_title),
-        summary: index_entry
-            .and_then(|e| e.summary.clone())
-            .or(inline_summary),
-        git_branch,
-        cwd,
-        messages,
-        last_activity,
-        file_offset: file_len,
-        file_path: file_path.to_path_buf(),
-        total_tokens_in,
-        total_tokens_out,
-    })
+    let added = earlier.len();
+    session.messages.splice(0..0, earlier);
+    session.backfill_offset = start;
+    session.backfill_lines = remaining_lines;
+    session.unloaded_messages = if start == 0 {
+        0
+    } else {
+        session.unloaded_messages.saturating_sub(estimated)
+    };
+    tracing::debug!(session = %session.id, added, remaining = start, "backfilled messages");
+    Ok(added)
 }
 
 /// Read new lines from a session file starting at the given offset
@@ -267,21 +924,39 @@ pub fn read_new_lines(session: &mut Session) -> Result<Vec<SessionMessage>> {
     let mut new_messages = Vec::new();
 
     for line in reader.lines() {
+        session.lines_read += 1;
+        let line_no = session.lines_read;
         let line = match line {
             Ok(l) => l,
-            Err(_) => continue,
+            Err(e) => {
+                session
+                    .parse_diagnostics
+                    .record(&session.id, line_no, e.to_string());
+                continue;
+            }
         };
         if line.trim().is_empty() {
             continue;
         }
 
-        // Update metadata from new lines
-        if let Some(meta) = message::extract_meta(&line) {
+        // Update metadata and token totals from new lines
+        let (scan, msg) = match message::parse_line_full(&line) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                session
+                    .parse_diagnostics
+                    .record(&session.id, line_no, e.to_string());
+                continue;
+            }
+        };
+        session.record_usage(&scan);
+        if let Some(meta) = scan.meta {
             if session.git_branch.is_none() {
                 session.git_branch = meta.git_branch;
             }
             if session.cwd.is_none() {
                 session.cwd = meta.cwd;
+                session.worktree = session.cwd.as_deref().and_then(git::worktree);
             }
             if session.slug.is_none() {
                 session.slug = meta.slug;
@@ -292,19 +967,20 @@ pub fn read_new_lines(session: &mut Session) -> Result<Vec<SessionMessage>> {
             }
         }
 
-        if let Some(msg) = message::parse_line(&line) {
+        if let Some(msg) = msg {
             session.last_activity = msg.timestamp;
-            if let Some(tin) = msg.tokens_in {
-                session.total_tokens_in += tin;
-            }
-            if let Some(tout) = msg.tokens_out {
-                session.total_tokens_out += tout;
-            }
             new_messages.push(msg.clone());
             session.messages.push(msg);
         }
     }
 
+    tracing::debug!(
+        session = %session.id,
+        from = session.file_offset,
+        to = file_len,
+        messages = new_messages.len(),
+        "read new lines"
+    );
     session.file_offset = file_len;
     Ok(new_messages)
 }
@@ -335,6 +1011,52 @@ pub fn discover_single_session(file_path: &Path) -> Result<Option<Session>> {
     Ok(Some(session))
 }
 
+/// Every transcript under `base_path` (subagent files and ignored projects
+/// excluded) with its
+/// modification time, without parsing any of them
+pub fn transcript_files(
+    base_path: &Path,
+    ignore: &IgnoreList,
+) -> Result<Vec<(PathBuf, SystemTime)>> {
+    let mut files = Vec::new();
+    for project_entry in fs::read_dir(base_path)? {
+        let project_entry = project_entry?;
+        let project_path = project_entry.path();
+        if !project_path.is_dir() || ignore.ignores(&project_entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        for file_entry in fs::read_dir(&project_path)? {
+            let file_entry = file_entry?;
+            let file_path = file_entry.path();
+            let is_agent = file_entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("agent-");
+            if is_agent || file_path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let mtime = file_entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((file_path, mtime));
+        }
+    }
+    Ok(files)
+}
+
+/// Sessions under each of `base_paths`, as one set
+pub fn discover_all(
+    base_paths: &[PathBuf],
+    ignore: &IgnoreList,
+) -> Result<HashMap<String, Session>> {
+    let mut sessions = HashMap::new();
+    for base_path in base_paths {
+        sessions.extend(discover_sessions(base_path, ignore)?);
+    }
+    Ok(sessions)
+}
+
 /// Re-read sessions-index.json and update session metadata (names, titles)
 pub fn refresh_index_metadata(base_path: &Path, sessions: &mut HashMap<String, Session>) {
     if !base_path.exists() {
@@ -361,3 +1083,223 @@ pub fn refresh_index_metadata(base_path: &Path, sessions: &mut HashMap<String, S
         }
     }
 }
+
+/// Default location for archived sessions (~/.local/share/claudy/archive)
+pub fn archive_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("claudy").join("archive"))
+}
+
+/// Move a session transcript (and its per-session subagent directory, if any)
+/// to `archive_root/<project_slug>/`. Returns the new transcript path.
+pub fn archive_session(session: &Session, archive_root: &Path) -> Result<PathBuf> {
+    let dest_dir = archive_root.join(&session.project_slug);
+    fs::create_dir_all(&dest_dir)?;
+
+    let file_name = session.file_path.file_name().unwrap_or_default();
+    let dest = dest_dir.join(file_name);
+    move_path(&session.file_path, &dest)?;
+
+    let side_dir = session.file_path.with_extension("");
+    if side_dir.is_dir() {
+        move_path(&side_dir, &dest_dir.join(&session.id))?;
+    }
+    Ok(dest)
+}
+
+/// Rename `from` to `to`, or, when the archive is on another filesystem,
+/// copy it, flush the copy to disk and only then remove the original
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_synced(from, to)?;
+            if let Some(parent) = to.parent() {
+                fs::File::open(parent)?.sync_all()?;
+            }
+            if from.is_dir() {
+                fs::remove_dir_all(from)
+            } else {
+                fs::remove_file(from)
+            }
+        }
+        result => result,
+    }
+}
+
+/// Copy a file or directory tree, fsyncing every file and directory written
+fn copy_synced(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_synced(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::File::open(to)?.sync_all()
+    } else {
+        fs::copy(from, to)?;
+        fs::File::open(to)?.sync_all()
+    }
+}
+
+/// Permanently delete a session transcript and its per-session directory
+pub fn delete_session(session: &Session) -> Result<()> {
+    fs::remove_file(&session.file_path)?;
+    let side_dir = session.file_path.with_extension("");
+    if side_dir.is_dir() {
+        fs::remove_dir_all(&side_dir)?;
+    }
+    Ok(())
+}
+
+/// Transcript lines shared by the tests of other modules
+#[cfg(test)]
+pub(crate) mod fixtures {
+    /// User prompt `i` of session `s`
+    pub(crate) fn prompt(i: usize) -> String {
+        format!(
+            r#"{{"type":"user","sessionId":"s","timestamp":"2026-10-16T09:00:00Z","message":{{"role":"user","content":"prompt {}"}}}}"#,
+            i
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::prompt;
+    use super::*;
+
+    /// A transcript file in a fresh temporary directory
+    fn transcript(lines: &[&str]) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        (dir, path)
+    }
+
+    fn reply(request: &str, id: &str, block: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","sessionId":"s","requestId":"{}","timestamp":"2026-10-16T09:01:00Z","message":{{"id":"{}","model":"claude-sonnet-4-5","content":[{{"type":"text","text":"{}"}}],"usage":{{"input_tokens":100,"output_tokens":10}}}}}}"#,
+            request, id, block
+        )
+    }
+
+    #[test]
+    fn synced_copies_keep_the_whole_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        fs::create_dir_all(dir.join("from/subagents")).unwrap();
+        fs::write(dir.join("from/subagents/agent-1.jsonl"), "{}\n").unwrap();
+        copy_synced(&dir.join("from"), &dir.join("to")).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("to/subagents/agent-1.jsonl")).unwrap(),
+            "{}\n"
+        );
+    }
+
+    #[test]
+    fn slugs_decode_against_the_filesystem() {
+        let base = tempfile::tempdir().unwrap();
+        let base = base.path();
+        fs::create_dir_all(base.join("my_app v2/.config/x-y")).unwrap();
+        fs::create_dir_all(base.join("a_b")).unwrap();
+        fs::create_dir_all(base.join("a/b/c")).unwrap();
+        assert_eq!(
+            find_dir(base, "my-app-v2--config-x-y"),
+            Some(base.join("my_app v2/.config/x-y"))
+        );
+        // `a_b` is tried first but is a dead end
+        assert_eq!(find_dir(base, "a-b-c"), Some(base.join("a/b/c")));
+        assert_eq!(find_dir(base, "my-app-v3"), None);
+    }
+
+    #[test]
+    fn diagnostics_keep_the_last_lines_in_order() {
+        let mut diagnostics = ParseDiagnostics::default();
+        for line in (1..=MAX_PARSE_ERRORS).map(|i| i * 10) {
+            diagnostics.record("s", line, String::new());
+        }
+        // Older than everything kept: only counted
+        diagnostics.record("s", 5, String::new());
+        // Between kept lines: the oldest makes room
+        diagnostics.record("s", 25, String::new());
+        assert_eq!(diagnostics.count, MAX_PARSE_ERRORS + 2);
+        assert_eq!(diagnostics.recent.len(), MAX_PARSE_ERRORS);
+        let lines: Vec<usize> = diagnostics.recent.iter().take(3).map(|e| e.line).collect();
+        assert_eq!(lines, [20, 25, 30]);
+    }
+
+    #[test]
+    fn path_globs_become_slug_globs() {
+        assert_eq!(slug_pattern("/home/u/scratch/**"), "-home-u-scratch-*");
+        assert_eq!(slug_pattern("/home/u/my_app v2/*"), "-home-u-my-app-v2-*");
+        assert_eq!(slug_pattern("/srv/[!.]*"), "-srv-[!-]*");
+    }
+
+    #[test]
+    fn ignore_list_accepts_recursive_path_globs() {
+        let ignore = IgnoreList::try_from(vec![
+            "/home/u/scratch/**".to_string(),
+            "-tmp-keep".to_string(),
+        ])
+        .unwrap();
+        assert!(ignore.ignores("-home-u-scratch-a"));
+        assert!(ignore.ignores("-home-u-scratch-a-b"));
+        assert!(ignore.ignores("-tmp-keep"));
+        assert!(!ignore.ignores("-home-u-work"));
+    }
+
+    #[test]
+    fn reply_usage_counts_once_across_content_blocks() {
+        let lines = [
+            reply("req_1", "msg_1", "first block"),
+            reply("req_1", "msg_1", "second block"),
+            reply("req_1", "msg_1", "third block"),
+            reply("req_2", "msg_2", "next reply"),
+        ];
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let (_dir, path) = transcript(&lines);
+        let session = parse_session_file(&path, "p", None).unwrap();
+        assert_eq!(session.usage.len(), 2);
+        assert_eq!(session.total_tokens_in, 200);
+        assert_eq!(session.total_tokens_out, 20);
+        assert_eq!(session.messages.len(), 4);
+    }
+
+    #[test]
+    fn appended_copies_of_a_reply_count_once() {
+        let first = reply("req_1", "msg_1", "first block");
+        let (_dir, path) = transcript(&[&first]);
+        let mut session = parse_session_file(&path, "p", None).unwrap();
+        let more = format!("{}\n{}\n", reply("req_1", "msg_1", "second block"), first);
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut file, more.as_bytes()).unwrap();
+        let new = read_new_lines(&mut session).unwrap();
+        assert_eq!(new.len(), 2);
+        assert_eq!(session.usage.len(), 1);
+        assert_eq!(session.total_tokens(), 110);
+    }
+
+    #[test]
+    fn prefix_errors_count_once() {
+        let mut lines: Vec<String> = (0..TAIL_MESSAGES + 10).map(prompt).collect();
+        lines[2] = "{not json".to_string();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let (_dir, path) = transcript(&lines);
+        let mut session = parse_session_file(&path, "p", None).unwrap();
+        assert_eq!(session.parse_diagnostics.count, 1);
+        assert_eq!(session.parse_diagnostics.recent[0].line, 3);
+        assert_eq!(backfill(&mut session, usize::MAX).unwrap(), 9);
+        assert_eq!(session.parse_diagnostics.count, 1);
+        assert_eq!(session.unloaded_messages, 0);
+    }
+
+    #[test]
+    fn backfill_fails_once_the_prefix_was_rewritten() {
+        let lines: Vec<String> = (0..TAIL_MESSAGES + 10).map(prompt).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let (_dir, path) = transcript(&lines);
+        let mut session = parse_session_file(&path, "p", None).unwrap();
+        // Now with more lines in front of the loaded ones than were scanned
+        fs::write(&path, lines.join("\n\n")).unwrap();
+        assert!(backfill(&mut session, usize::MAX).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::config::Config;
+
+/// Folders listed at most when browsing one
+const MAX_LISTED: usize = 20;
+
+/// Ask where Claude Code keeps its projects, offering the usual places
+/// that exist, and save the answer as `projects_dir` in the config. None
+/// if the user gives up (an empty answer or end of input).
+pub fn run(missing: &Path) -> Result<Option<PathBuf>> {
+    println!("Claude projects directory not found: {}", missing.display());
+    let mut choices = candidates();
+    if choices.is_empty() {
+        println!("None of the usual places exist either.");
+    } else {
+        println!("Found:");
+    }
+    // The folder being browsed, which "." picks
+    let mut browsing: Option<PathBuf> = None;
+    let stdin = std::io::stdin();
+    loop {
+        for (i, dir) in choices.iter().enumerate() {
+            println!("  {}) {}", i + 1, describe(dir));
+        }
+        if browsing.is_some() {
+            print!("Pick a number, type a path, \".\" to use this folder, or Enter to quit: ");
+        } else {
+            print!("Pick a number, type a path, or press Enter to quit: ");
+        }
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        let answer = answer.trim();
+        let picked = match answer {
+            "" => return Ok(None),
+            "." if browsing.is_some() => browsing.take(),
+            _ => match answer.parse::<usize>() {
+                Ok(n) if (1..=choices.len()).contains(&n) => Some(choices[n - 1].clone()),
+                _ => Some(expand_home(answer)),
+            },
+        };
+        let Some(dir) = picked else {
+            continue;
+        };
+        if !dir.is_dir() {
+            println!("Not a directory: {}", dir.display());
+            continue;
+        }
+        if project_count(&dir) == 0 && browsing.as_ref() != Some(&dir) && answer != "." {
+            // Probably a folder above the right one: show what's in it
+            let folders = subfolders(&dir);
+            if !folders.is_empty() {
+                println!("No sessions directly in {}; its folders:", dir.display());
+                choices = folders;
+                browsing = Some(dir);
+                continue;
+            }
+        }
+        save(&dir)?;
+        return Ok(Some(dir));
+    }
+}
+
+/// Existing directories Claude Code may keep its projects in
+fn candidates() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(config_dir) = std::env::var_os("CLAUDE_CONFIG_DIR") {
+        dirs.push(PathBuf::from(config_dir).join("projects"));
+    }
+    if let Some(config) = dirs::config_dir() {
+        dirs.push(config.join("claude").join("projects"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".claude").join("projects"));
+    }
+    let mut found: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if dir.is_dir() && !found.contains(&dir) {
+            found.push(dir);
+        }
+    }
+    found
+}
+
+/// `dir` and how many project folders with sessions it has
+fn describe(dir: &Path) -> String {
+    match project_count(dir) {
+        0 => dir.display().to_string(),
+        1 => format!("{} (1 project)", dir.display()),
+        n => format!("{} ({} projects)", dir.display(), n),
+    }
+}
+
+/// Folders in `dir` holding at least one `.jsonl` transcript
+fn project_count(dir: &Path) -> usize {
+    subfolders(dir)
+        .iter()
+        .filter(|project| {
+            fs::read_dir(project).is_ok_and(|entries| {
+                entries
+                    .flatten()
+                    .any(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+            })
+        })
+        .count()
+}
+
+/// Visible folders in `dir`, by name, at most `MAX_LISTED`
+fn subfolders(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut folders: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter(|p| {
+            p.file_name()
+                .is_some_and(|n| !n.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    folders.sort();
+    folders.truncate(MAX_LISTED);
+    folders
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Set `projects_dir` in the config file, creating it if need be. The
+/// rest of the file, comments included, is kept as it was.
+fn save(dir: &Path) -> Result<()> {
+    let Some(path) = Config::path() else {
+        return Ok(());
+    };
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let contents = with_projects_dir(&existing, dir)
+        .with_context(|| format!("invalid config {}", path.display()))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+    println!("Saved to {}", path.display());
+    Ok(())
+}
+
+/// `config` with the top-level `projects_dir` set to `dir`
+fn with_projects_dir(config: &str, dir: &Path) -> Result<String> {
+    let mut doc: toml_edit::DocumentMut = config.parse()?;
+    doc["projects_dir"] = toml_edit::value(dir.display().to_string());
+    Ok(doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_dir_replaces_only_the_top_level_key() {
+        let config = "# projects_dir = \"/old\"\nprojects_dir = \"/old\"\ntheme = \"dark\"\n\n[otlp]\nprojects_dir_label = true\n";
+        let saved = with_projects_dir(config, Path::new("/new")).unwrap();
+        assert_eq!(
+            saved,
+            "# projects_dir = \"/old\"\nprojects_dir = \"/new\"\ntheme = \"dark\"\n\n[otlp]\nprojects_dir_label = true\n"
+        );
+    }
+
+    #[test]
+    fn projects_dir_goes_before_tables() {
+        let saved = with_projects_dir("[otlp]\nendpoint = \"x\"\n", Path::new("/new")).unwrap();
+        let parsed: toml::Table = toml::from_str(&saved).unwrap();
+        assert_eq!(parsed["projects_dir"].as_str(), Some("/new"));
+        assert!(parsed["otlp"].get("projects_dir").is_none());
+    }
+}
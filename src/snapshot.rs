@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+};
+use serde::Deserialize;
+use unicode_width::UnicodeWidthStr;
+
+/// What a snapshot captures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotScope {
+    /// The chat pane, without its border
+    Chat,
+    /// Everything on screen
+    Frame,
+}
+
+/// Snapshot settings (`[snapshot]` in config.toml)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    /// Where snapshots are written; the current directory if unset
+    pub dir: Option<PathBuf>,
+    /// Shell command rendering the `.ans` file to PNG, with `{input}` and
+    /// `{output}` replaced by the paths, e.g. "freeze {input} -o {output}"
+    pub png_command: Option<String>,
+}
+
+/// Write `area` of `buffer` as `<name>-<time>.ans` (with colors) and
+/// `.txt`, then start the PNG command if one is configured. Returns the
+/// `.ans` path.
+pub fn write(buffer: &Buffer, area: Rect, name: &str, config: &SnapshotConfig) -> Result<PathBuf> {
+    let dir = config.dir.clone().unwrap_or_else(|| PathBuf::from("."));
+    fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+    let stem = format!("claudy-{}-{}", name, Local::now().format("%Y%m%d-%H%M%S"));
+    let ans = dir.join(format!("{}.ans", stem));
+    fs::write(&ans, to_ansi(buffer, area)).with_context(|| format!("writing {}", ans.display()))?;
+    let txt = ans.with_extension("txt");
+    fs::write(&txt, to_text(buffer, area)).with_context(|| format!("writing {}", txt.display()))?;
+    if let Some(ref command) = config.png_command {
+        render_png(command, &ans);
+    }
+    Ok(ans)
+}
+
+/// The cells of one row, skipping the placeholders after wide characters
+pub fn row_cells(buffer: &Buffer, area: Rect, y: u16) -> impl Iterator<Item = (u16, &str, Style)> {
+    let mut skip = 0;
+    (area.left()..area.right()).filter_map(move |x| {
+        if skip > 0 {
+            skip -= 1;
+            return None;
+        }
+        let cell = &buffer[(x, y)];
+        skip = cell.symbol().width().saturating_sub(1);
+        Some((x, cell.symbol(), cell.style()))
+    })
+}
+
+fn to_text(buffer: &Buffer, area: Rect) -> String {
+    let mut out = String::new();
+    for y in area.top()..area.bottom() {
+        let line: String = row_cells(buffer, area, y).map(|(_, s, _)| s).collect();
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn to_ansi(buffer: &Buffer, area: Rect) -> String {
+    let mut out = String::new();
+    for y in area.top()..area.bottom() {
+        let mut current = None;
+        for (_, symbol, style) in row_cells(buffer, area, y) {
+            if current != Some(style) {
+                out.push_str(&sgr(style));
+                current = Some(style);
+            }
+            out.push_str(symbol);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// Select Graphic Rendition sequence setting exactly `style`
+pub fn sgr(style: Style) -> String {
+    let mut codes = vec!["0".to_string()];
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, code) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.extend(style.fg.and_then(|c| color_code(c, false)));
+    codes.extend(style.bg.and_then(|c| color_code(c, true)));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base = |n: u8| Some((if background { n + 10 } else { n }).to_string());
+    let extended = if background { 48 } else { 38 };
+    match color {
+        Color::Reset => None,
+        Color::Black => base(30),
+        Color::Red => base(31),
+        Color::Green => base(32),
+        Color::Yellow => base(33),
+        Color::Blue => base(34),
+        Color::Magenta => base(35),
+        Color::Cyan => base(36),
+        Color::Gray => base(37),
+        Color::DarkGray => base(90),
+        Color::LightRed => base(91),
+        Color::LightGreen => base(92),
+        Color::LightYellow => base(93),
+        Color::LightBlue => base(94),
+        Color::LightMagenta => base(95),
+        Color::LightCyan => base(96),
+        Color::White => base(97),
+        Color::Indexed(i) => Some(format!("{};5;{}", extended, i)),
+        Color::Rgb(r, g, b) => Some(format!("{};2;{};{};{}", extended, r, g, b)),
+    }
+}
+
+/// Run the PNG command in the background; it may take a moment
+fn render_png(command: &str, ans: &Path) {
+    let png = ans.with_extension("png");
+    let quote = |p: &Path| format!("'{}'", p.display().to_string().replace('\'', r"'\''"));
+    let command = command
+        .replace("{input}", &quote(ans))
+        .replace("{output}", &quote(&png));
+    std::thread::spawn(move || {
+        let status = Command::new("sh")
+            .args(["-c", &command])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        match status {
+            Ok(s) if s.success() => {}
+            Ok(s) => tracing::warn!(command = %command, status = %s, "PNG snapshot failed"),
+            Err(e) => tracing::warn!(command = %command, error = %e, "PNG snapshot failed"),
+        }
+    });
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::session::Session;
+use crate::ui::SessionColumn;
+
+/// Number of recent filters remembered
+const FILTER_HISTORY_LEN: usize = 50;
+
+/// Claudy's own persistent state, independent of Claude Code's files.
+/// Stored as JSON in ~/.local/share/claudy/state.json.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClaudyState {
+    /// User-assigned titles keyed by session id; these override every
+    /// other naming source in `Session::display_name()`
+    pub titles: HashMap<String, String>,
+    /// Free-form notes keyed by session id
+    pub notes: HashMap<String, String>,
+    /// User-defined tags keyed by session id
+    pub tags: HashMap<String, Vec<String>>,
+    /// Starred message keys (`SessionMessage::key`) keyed by session id
+    pub starred: HashMap<String, Vec<String>>,
+    /// Recently applied filter strings, most recent first
+    pub filter_history: Vec<String>,
+    /// View state saved on exit and restored on the next launch
+    pub ui: UiState,
+}
+
+/// Where the user left off, so a restart doesn't reset the view
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub selected_session: Option<String>,
+    /// Key (`SessionMessage::key`) of the message at the top of the chat
+    /// view and the line offset into it; `None` follows the bottom
+    pub chat_top: Option<(String, usize)>,
+    pub filter: Option<String>,
+    pub active_only: bool,
+    pub sort_ascending: bool,
+    /// Session list columns picked in the chooser; None keeps the config's
+    pub session_columns: Option<Vec<SessionColumn>>,
+}
+
+impl ClaudyState {
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("claudy").join("state.json"))
+    }
+
+    /// Load state from disk, falling back to empty state if missing or invalid
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("no data directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write to a temp file first so a crash never leaves truncated state
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Copy claudy-managed metadata onto a freshly parsed session
+    pub fn apply(&self, session: &mut Session) {
+        session.user_title = self.titles.get(&session.id).cloned();
+        session.tags = self.tags.get(&session.id).cloned().unwrap_or_default();
+    }
+
+    pub fn set_note(&mut self, session_id: &str, note: Option<String>) {
+        match note {
+            Some(n) => self.notes.insert(session_id.to_string(), n),
+            None => self.notes.remove(session_id),
+        };
+    }
+
+    pub fn set_tags(&mut self, session_id: &str, tags: Vec<String>) {
+        if tags.is_empty() {
+            self.tags.remove(session_id);
+        } else {
+            self.tags.insert(session_id.to_string(), tags);
+        }
+    }
+
+    pub fn is_starred(&self, session_id: &str, message_key: &str) -> bool {
+        self.starred
+            .get(session_id)
+            .is_some_and(|keys| keys.iter().any(|k| k == message_key))
+    }
+
+    /// Flip the star on a message; returns whether it is now starred
+    pub fn toggle_star(&mut self, session_id: &str, message_key: &str) -> bool {
+        let keys = self.starred.entry(session_id.to_string()).or_default();
+        let starred = if let Some(pos) = keys.iter().position(|k| k == message_key) {
+            keys.remove(pos);
+            false
+        } else {
+            keys.push(message_key.to_string());
+            true
+        };
+        if keys.is_empty() {
+            self.starred.remove(session_id);
+        }
+        starred
+    }
+
+    /// Record an applied filter, keeping the list short and duplicate-free;
+    /// false, with nothing to save, for a blank filter or the latest one
+    pub fn push_filter_history(&mut self, filter: &str) -> bool {
+        if filter.trim().is_empty() || self.filter_history.first().is_some_and(|f| f == filter) {
+            return false;
+        }
+        self.filter_history.retain(|f| f != filter);
+        self.filter_history.insert(0, filter.to_string());
+        self.filter_history.truncate(FILTER_HISTORY_LEN);
+        true
+    }
+
+    pub fn set_title(&mut self, session_id: &str, title: Option<String>) {
+        match title {
+            Some(t) => self.titles.insert(session_id.to_string(), t),
+            None => self.titles.remove(session_id),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_history_skips_blanks_and_repeats() {
+        let mut state = ClaudyState::default();
+        assert!(state.push_filter_history("repo:app"));
+        assert!(!state.push_filter_history("repo:app"));
+        assert!(!state.push_filter_history("  "));
+        assert!(state.push_filter_history("tag:x"));
+        assert!(state.push_filter_history("repo:app"));
+        assert_eq!(state.filter_history, ["repo:app", "tag:x"]);
+    }
+}
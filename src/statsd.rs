@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::net::UdpSocket;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::app::SessionCounters;
+use crate::message::SessionMessage;
+use crate::session::{Session, UsagePoint};
+
+/// Keep packets under a typical MTU so they never fragment
+const MAX_PACKET_BYTES: usize = 1432;
+
+/// StatsD export settings (`[statsd]` in config.toml; the table's presence
+/// turns it on)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StatsdConfig {
+    /// host:port of the StatsD daemon
+    pub address: String,
+    /// Prepended to every metric name
+    pub prefix: String,
+    pub tag_format: TagFormat,
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:8125".to_string(),
+            prefix: "claudy".to_string(),
+            tag_format: TagFormat::Dogstatsd,
+        }
+    }
+}
+
+/// How project and model reach the daemon
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagFormat {
+    /// `name:1|c|#project:x,model:y` (DogStatsD, Telegraf, StatsD exporters)
+    Dogstatsd,
+    /// Tags folded into the name, `name.x.y:1|c`, for plain StatsD/Graphite
+    Graphite,
+}
+
+/// Counter key: metric name, project and model (if the metric has one)
+type CounterKey = (&'static str, String, Option<String>);
+
+/// Accumulates counters as transcripts grow and sends them, with session
+/// gauges, over UDP once per tick
+pub struct Emitter {
+    pub config: StatsdConfig,
+    socket: Option<UdpSocket>,
+    /// Activity from before this is history and not counted
+    started: DateTime<Utc>,
+    counters: HashMap<CounterKey, u64>,
+}
+
+impl Emitter {
+    pub fn start(config: StatsdConfig) -> Self {
+        let socket = UdpSocket::bind(if config.address.starts_with('[') {
+            "[::]:0"
+        } else {
+            "0.0.0.0:0"
+        })
+        .and_then(|s| {
+            s.connect(&config.address)?;
+            s.set_nonblocking(true)?;
+            Ok(s)
+        });
+        let socket = match socket {
+            Ok(s) => Some(s),
+            Err(e) => {
+                tracing::warn!(address = %config.address, error = %e, "StatsD disabled");
+                None
+            }
+        };
+        Self {
+            config,
+            socket,
+            started: Utc::now(),
+            counters: HashMap::new(),
+        }
+    }
+
+    /// Count messages, tokens and newly skipped lines just read from
+    /// `session`; anything older than the emitter itself is skipped
+    pub fn record(
+        &mut self,
+        session: &Session,
+        messages: &[SessionMessage],
+        points: &[UsagePoint],
+        new_errors: usize,
+    ) {
+        let project = session.project_name();
+        let started = self.started;
+        let mut count = |name: &'static str, model: Option<&str>, value: u64| {
+            if value > 0 {
+                *self
+                    .counters
+                    .entry((name, project.to_string(), model.map(str::to_string)))
+                    .or_default() += value;
+            }
+        };
+        let new_messages = messages.iter().filter(|m| m.timestamp >= started).count();
+        count("messages", None, new_messages as u64);
+        for point in points.iter().filter(|p| p.timestamp >= started) {
+            let model = point.model.as_deref();
+            count("tokens.input", model, point.usage.tokens_in());
+            count("tokens.output", model, point.usage.output);
+        }
+        count("errors", None, new_errors as u64);
+    }
+
+    /// Send the counters gathered since the last tick and the session gauges
+    pub fn tick(&mut self, sessions: SessionCounters) {
+        let Some(ref socket) = self.socket else {
+            return;
+        };
+        let mut lines: Vec<String> = self
+            .counters
+            .drain()
+            .map(|((name, project, model), value)| {
+                self.config
+                    .format(name, value, "c", Some(&project), model.as_deref())
+            })
+            .collect();
+        lines.push(
+            self.config
+                .format("sessions.active", sessions.active as u64, "g", None, None),
+        );
+        lines.push(self.config.format(
+            "sessions.waiting",
+            sessions.awaiting_user as u64,
+            "g",
+            None,
+            None,
+        ));
+
+        let mut packet = String::new();
+        for line in lines {
+            if !packet.is_empty() && packet.len() + line.len() + 1 > MAX_PACKET_BYTES {
+                send(socket, &packet);
+                packet.clear();
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(&line);
+        }
+        send(socket, &packet);
+    }
+}
+
+impl StatsdConfig {
+    /// One metric line in the configured tag format
+    fn format(
+        &self,
+        name: &str,
+        value: u64,
+        kind: &str,
+        project: Option<&str>,
+        model: Option<&str>,
+    ) -> String {
+        match self.tag_format {
+            TagFormat::Dogstatsd => {
+                let tags: Vec<String> = [("project", project), ("model", model)]
+                    .into_iter()
+                    .filter_map(|(key, value)| Some(format!("{}:{}", key, sanitize(value?))))
+                    .collect();
+                let tags = if tags.is_empty() {
+                    String::new()
+                } else {
+                    format!("|#{}", tags.join(","))
+                };
+                format!("{}.{}:{}|{}{}", self.prefix, name, value, kind, tags)
+            }
+            TagFormat::Graphite => {
+                let mut path = format!("{}.{}", self.prefix, name);
+                for segment in [project, model].into_iter().flatten() {
+                    path.push('.');
+                    path.push_str(&sanitize(segment));
+                }
+                format!("{}:{}|{}", path, value, kind)
+            }
+        }
+    }
+}
+
+/// Replace characters StatsD and Graphite treat as separators
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Fire and forget: a missing daemon must not disturb the UI
+fn send(socket: &UdpSocket, packet: &str) {
+    if let Err(e) = socket.send(packet.as_bytes()) {
+        tracing::debug!(error = %e, "StatsD send failed");
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::Write;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+/// Notification escape sequence understood by the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OscProtocol {
+    /// `OSC 9 ; body` (iTerm2, WezTerm, kitty, Windows Terminal)
+    #[default]
+    Osc9,
+    /// `OSC 777 ; notify ; title ; body` (foot, WezTerm, rxvt-unicode)
+    Osc777,
+}
+
+/// Whether the terminal says it takes 24-bit colors (`COLORTERM`); there's
+/// no query for it, and terminals that can all set this
+pub fn supports_truecolor() -> bool {
+    std::env::var("COLORTERM").is_ok_and(|v| v == "truecolor" || v == "24bit")
+}
+
+/// Ring the terminal bell (BEL), which most terminals turn into a sound,
+/// a visual bell or an urgency hint on the window
+pub fn bell() -> Result<()> {
+    let mut out = std::io::stdout();
+    out.write_all(b"\x07")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Show a desktop notification through the terminal emulator. Inside tmux
+/// the sequence is wrapped for passthrough (needs `allow-passthrough on`).
+pub fn notify(protocol: OscProtocol, title: &str, body: &str) -> Result<()> {
+    // Control characters would end the sequence early; `;` splits OSC 777
+    let clean = |s: &str| -> String {
+        s.chars()
+            .map(|c| if c.is_control() || c == ';' { ' ' } else { c })
+            .collect()
+    };
+    let sequence = match protocol {
+        OscProtocol::Osc9 => format!("\x1b]9;{}: {}\x07", clean(title), clean(body)),
+        OscProtocol::Osc777 => format!("\x1b]777;notify;{};{}\x07", clean(title), clean(body)),
+    };
+    let sequence = if std::env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    };
+    let mut out = std::io::stdout();
+    out.write_all(sequence.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveTime, TimeDelta, Utc};
+use serde::Deserialize;
+use tz::TimeZone;
+
+/// Clock the TUI shows times on (`time_format`, toggled with `Z`)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum TimeFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+    #[serde(rename = "12h")]
+    H12,
+}
+
+impl TimeFormat {
+    pub fn toggle(self) -> Self {
+        match self {
+            TimeFormat::H24 => TimeFormat::H12,
+            TimeFormat::H12 => TimeFormat::H24,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeFormat::H24 => "24-hour",
+            TimeFormat::H12 => "12-hour",
+        }
+    }
+}
+
+/// Heading for sessions last active on `day`: today, yesterday, earlier
+/// this week, last week (weeks start on Monday), earlier this month, then
+/// the month, with the year unless it is this year
+pub fn day_heading(day: NaiveDate, today: NaiveDate) -> String {
+    let week_start = today - TimeDelta::days(today.weekday().num_days_from_monday() as i64);
+    if day >= today {
+        "Today".to_string()
+    } else if day == today - TimeDelta::days(1) {
+        "Yesterday".to_string()
+    } else if day >= week_start {
+        "Earlier this week".to_string()
+    } else if day >= week_start - TimeDelta::days(7) {
+        "Last week".to_string()
+    } else if (day.year(), day.month()) == (today.year(), today.month()) {
+        "Earlier this month".to_string()
+    } else if day.year() == today.year() {
+        day.format("%B").to_string()
+    } else {
+        day.format("%B %Y").to_string()
+    }
+}
+
+/// Zone the TUI shows times in (`timezone` in config.toml)
+#[derive(Clone)]
+pub enum DisplayZone {
+    /// The system's, as everywhere else
+    Local,
+    /// UTC or an IANA zone, read from the system's zoneinfo
+    Named(TimeZone),
+}
+
+impl DisplayZone {
+    /// "" or "local" for the system zone, "UTC", or an IANA name such as
+    /// "America/New_York"
+    pub fn parse(name: &str) -> Result<Self> {
+        let name = name.trim();
+        if name.is_empty() || name.eq_ignore_ascii_case("local") {
+            return Ok(DisplayZone::Local);
+        }
+        if name.eq_ignore_ascii_case("utc") {
+            return Ok(DisplayZone::Named(TimeZone::utc()));
+        }
+        TimeZone::from_posix_tz(name)
+            .map(DisplayZone::Named)
+            .with_context(|| format!("Unknown timezone {:?} in config", name))
+    }
+
+    /// `parse`, falling back to the system zone for a name it rejects;
+    /// the warning says so
+    pub fn parse_or_local(name: &str) -> (Self, Option<String>) {
+        match Self::parse(name) {
+            Ok(zone) => (zone, None),
+            Err(e) => {
+                tracing::warn!(error = format!("{:#}", e), "falling back to local time");
+                (
+                    DisplayZone::Local,
+                    Some(format!("{:#}; showing local times", e)),
+                )
+            }
+        }
+    }
+
+    /// The day `time` falls on in this zone
+    pub fn day(&self, time: DateTime<Utc>) -> NaiveDate {
+        self.convert(time).date_naive()
+    }
+
+    /// When `day` begins in this zone
+    pub fn day_start(&self, day: NaiveDate) -> DateTime<Utc> {
+        let midnight = day.and_time(NaiveTime::MIN);
+        match self {
+            DisplayZone::Local => midnight
+                .and_local_timezone(Local)
+                .earliest()
+                .map_or_else(|| midnight.and_utc(), |t| t.with_timezone(&Utc)),
+            DisplayZone::Named(_) => {
+                // The offset at midnight may differ from the one at a first
+                // guess of it (across a DST change); the second one settles it
+                let offset_at =
+                    |time| TimeDelta::seconds(self.convert(time).offset().local_minus_utc() as i64);
+                let guess = midnight.and_utc() - offset_at(midnight.and_utc());
+                midnight.and_utc() - offset_at(guess)
+            }
+        }
+    }
+
+    /// `time` on a `format` clock in this zone, with the date in front
+    /// unless it is today (and the year unless it is this year)
+    pub fn clock(&self, time: DateTime<Utc>, format: TimeFormat) -> String {
+        let time = self.convert(time);
+        let today = self.convert(Utc::now()).date_naive();
+        let hours = match format {
+            TimeFormat::H24 => "%H:%M",
+            TimeFormat::H12 => "%-I:%M%P",
+        };
+        let date = if time.date_naive() == today {
+            ""
+        } else if time.year() == today.year() {
+            "%m-%d "
+        } else {
+            "%Y-%m-%d "
+        };
+        time.format(&format!("{}{}", date, hours)).to_string()
+    }
+
+    /// `time` as a clock in this zone would show it
+    pub fn convert(&self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            DisplayZone::Local => time.with_timezone(&Local).fixed_offset(),
+            DisplayZone::Named(zone) => zone
+                .find_local_time_type(time.timestamp())
+                .ok()
+                .and_then(|t| FixedOffset::east_opt(t.ut_offset()))
+                .map_or(time.fixed_offset(), |offset| time.with_timezone(&offset)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_start_at_the_zone_midnight() {
+        let utc = DisplayZone::parse("UTC").unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        assert_eq!(utc.day_start(day).to_rfc3339(), "2026-10-16T00:00:00+00:00");
+        // A fixed POSIX zone five hours behind UTC
+        let est = DisplayZone::parse("EST5").unwrap();
+        assert_eq!(est.day_start(day).to_rfc3339(), "2026-10-16T05:00:00+00:00");
+        let late = "2026-10-17T03:00:00Z".parse().unwrap();
+        assert_eq!(est.day(late), day);
+    }
+
+    #[test]
+    fn unknown_zones_fall_back_to_local() {
+        let (zone, warning) = DisplayZone::parse_or_local("Not/AZone");
+        assert!(matches!(zone, DisplayZone::Local));
+        assert!(warning.is_some_and(|w| w.contains("Not/AZone")));
+    }
+}
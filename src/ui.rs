@@ -1,50 +1,2146 @@
-use chrono::Local;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Datelike, TimeDelta, Utc};
 use ratatui::{
     Frame,
+    buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{
+        Axis, Block, Borders, Chart, Clear, Dataset, GraphType, LineGauge, List, ListItem,
+        ListState, Paragraph, Tabs, Wrap,
+    },
+};
+use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::app::{
+    App, FocusPanel, InfoAction, LARGEST_SESSIONS_TOP, ListRow, Popup, SidePanel, View,
 };
+use crate::background::Background;
+use crate::classes::classify;
+use crate::compare::{Comparison, Stats, Turn, TurnRow};
+use crate::context::ContextLevel;
+use crate::filter::SessionFilter;
+use crate::git;
+use crate::health::{self, HealthState};
+use crate::heatmap::{self, HeatMetric};
+use crate::latency::{self, ChartWindow, LatencyStats};
+use crate::message::{self, MessageType, SessionMessage};
+use crate::redact;
+use crate::refs::Link;
+use crate::replay::Replay;
+use crate::report::{self, BillingRow, Breakdown, ProjectSummary, SizeMetric};
+use crate::session::{ParseError, Session};
+
+/// Rows built past the bottom of the session list viewport
+const LIST_OVERSCAN: usize = 5;
+
+/// Lines of each side's answer shown per turn in the compare popup
+const COMPARE_RESPONSE_LINES: usize = 8;
+
+/// Narrowest the session name may get before extra columns are dropped
+const MIN_NAME_WIDTH: usize = 16;
+
+/// Optional session list column (`session_columns` in config.toml, or
+/// chosen with `l`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionColumn {
+    Tokens,
+    Cost,
+    Project,
+    Branch,
+    Duration,
+    /// Any other name refers to a `[[script_columns]]` entry
+    #[serde(untagged)]
+    Script(String),
+}
+
+/// Where chat text narrower than the pane sits (`content_align`)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentAlign {
+    #[default]
+    Left,
+    Center,
+}
+
+/// How much room the chat stream gives each message (`density`)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+impl Density {
+    /// Lead-in of a message's body lines under its header
+    fn indent(self) -> &'static str {
+        match self {
+            Density::Comfortable => "  ",
+            Density::Compact => " ",
+        }
+    }
+}
+
+/// How the chat pane lays out messages (`chat_style`, cycled with `V`)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatStyle {
+    /// Role-prefixed messages one after another
+    #[default]
+    Stream,
+    /// Prompts and replies in bordered bubbles, prompts on the right
+    Bubbles,
+    /// One truncated line per message, for skimming; Enter expands
+    Oneline,
+}
+
+impl ChatStyle {
+    pub fn next(self) -> Self {
+        match self {
+            ChatStyle::Stream => ChatStyle::Bubbles,
+            ChatStyle::Bubbles => ChatStyle::Oneline,
+            ChatStyle::Oneline => ChatStyle::Stream,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChatStyle::Stream => "stream",
+            ChatStyle::Bubbles => "bubbles",
+            ChatStyle::Oneline => "one line",
+        }
+    }
+}
+
+impl SessionColumn {
+    /// The built-in columns, in the order the chooser offers them
+    pub const BUILT_IN: [SessionColumn; 5] = [
+        SessionColumn::Tokens,
+        SessionColumn::Cost,
+        SessionColumn::Project,
+        SessionColumn::Branch,
+        SessionColumn::Duration,
+    ];
+
+    /// Name as written in `session_columns`
+    pub fn name(&self) -> &str {
+        match self {
+            SessionColumn::Tokens => "tokens",
+            SessionColumn::Cost => "cost",
+            SessionColumn::Project => "project",
+            SessionColumn::Branch => "branch",
+            SessionColumn::Duration => "duration",
+            SessionColumn::Script(name) => name,
+        }
+    }
+
+    /// Cell width, excluding the separating space; `None` for a script
+    /// column that isn't defined
+    fn width(&self, app: &App) -> Option<usize> {
+        Some(match self {
+            SessionColumn::Tokens => 6,
+            SessionColumn::Cost => 9,
+            SessionColumn::Project | SessionColumn::Branch => 14,
+            SessionColumn::Duration => 6,
+            SessionColumn::Script(name) => app.scripts.column_width(name)?,
+        })
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            SessionColumn::Tokens | SessionColumn::Cost => Color::Cyan,
+            SessionColumn::Project => Color::Blue,
+            SessionColumn::Branch => Color::Green,
+            SessionColumn::Duration => Color::DarkGray,
+            SessionColumn::Script(_) => Color::Magenta,
+        }
+    }
+
+    /// The right-aligned cell for `session`, `width` wide
+    fn cell(&self, app: &App, session: &Session, width: usize) -> String {
+        let value = match self {
+            SessionColumn::Tokens => format_tokens(session.total_tokens()),
+            SessionColumn::Cost => {
+                let (cost, _) = app.prices.total_cost(&session.usage);
+                app.config.currency.format(cost)
+            }
+            SessionColumn::Project => session.project_label(),
+            SessionColumn::Branch => session.git_branch.clone().unwrap_or_default(),
+            SessionColumn::Duration => session
+                .duration()
+                .map_or_else(|| "-".to_string(), format_duration),
+            SessionColumn::Script(name) => app.scripts.column(name, session, &app.prices),
+        };
+        let value = truncate_width(&value, width);
+        format!(
+            " {}{}",
+            " ".repeat(width.saturating_sub(value.width())),
+            value
+        )
+    }
+}
+
+pub fn draw(f: &mut Frame, app: &mut App) {
+    // Main layout: the view + bottom status bar
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(f.area());
+
+    if app.view == View::Monitor {
+        draw_monitor(f, app, main_chunks[0]);
+    } else {
+        // Other views get a tab bar naming their keys
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(main_chunks[0]);
+        draw_view_tabs(f, app, chunks[0]);
+        app.view_area = chunks[1];
+        match app.view {
+            View::Monitor => {}
+            View::Dashboard => draw_dashboard(f, app, chunks[1]),
+            View::Stats => draw_billing(f, app, chunks[1]),
+            View::Projects => draw_projects(f, app, chunks[1]),
+            View::Help => draw_help(f, app, chunks[1]),
+        }
+    }
+    draw_status_bar(f, app, main_chunks[1]);
+
+    if let Some(popup) = app.popup.clone() {
+        draw_popup(f, app, &popup);
+    }
+    if app.config.no_color {
+        monochrome(f.buffer_mut());
+    } else if app.config.background == Background::Light {
+        light_theme(f.buffer_mut());
+    }
+    if app.config.ascii {
+        ascii(f.buffer_mut());
+    }
+}
+
+/// Sessions, chat and session info, the layout mouse hits are tested on
+fn draw_monitor(f: &mut Frame, app: &mut App, area: Rect) {
+    // Left panel + right chat panel
+    let h_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    // Left panel: session list + session info
+    let left_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(h_chunks[0]);
+
+    // Right panel: chat, with the side panel beside it when open
+    let (chat, side) = if app.side_panel.is_some() {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+            .split(h_chunks[1]);
+        (chunks[0], chunks[1])
+    } else {
+        (h_chunks[1], Rect::default())
+    };
+
+    // Store rects for mouse hit testing
+    app.session_list_area = left_chunks[0];
+    app.chat_area = chat;
+    app.side_area = side;
+
+    draw_session_list(f, app, left_chunks[0]);
+    draw_session_info(f, app, left_chunks[1]);
+    draw_chat_stream(f, app, chat);
+    if let Some(panel) = app.side_panel {
+        draw_side_panel(f, app, side, panel);
+    }
+    if app.config.truecolor {
+        let focused = match app.focus {
+            FocusPanel::Sessions => app.session_list_area,
+            FocusPanel::Chat => app.chat_area,
+            FocusPanel::Side => app.side_area,
+        };
+        shade_border(f.buffer_mut(), focused, app.config.background);
+    }
+}
+
+fn draw_view_tabs(f: &mut Frame, app: &App, area: Rect) {
+    let titles: Vec<String> = View::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, view)| format!("F{} {}", i + 1, view.label()))
+        .collect();
+    let selected = View::ALL.iter().position(|v| *v == app.view);
+    let tabs = Tabs::new(titles)
+        .select(selected)
+        .style(Style::default().fg(Color::DarkGray))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        );
+    f.render_widget(tabs, area);
+}
+
+/// Today's sessions above the activity heatmap
+fn draw_dashboard(f: &mut Frame, app: &App, area: Rect) {
+    // Month labels, seven weekdays, a gap, the legend and the summary
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(13)])
+        .split(area);
+    let sessions = app.todays_sessions();
+    let reader = app.config.screen_reader;
+    let name_width = (chunks[0].width as usize)
+        .saturating_sub(2 + 2 + 8 + 24 + 22)
+        .max(1);
+    let items: Vec<ListItem> = sessions
+        .iter()
+        .map(|session| {
+            let (cost, _) = app.prices.total_cost(&session.usage);
+            let (prefix, color) = match session.is_active() {
+                true if reader => ("active ", Color::Green),
+                true => ("● ", Color::Green),
+                false => ("  ", Color::DarkGray),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(prefix, Style::default().fg(color)),
+                Span::styled(
+                    format!("{:<8}", app.clock(session.last_activity)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!(
+                        "{:<width$}",
+                        truncate_width(&app.private(&session.display_name()), name_width),
+                        width = name_width
+                    ),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    format!(
+                        "  {:<22}",
+                        truncate_width(&app.private(session.project_name()), 22)
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!(
+                        "{:>10}  {:>10}",
+                        format_tokens(session.total_tokens()),
+                        app.config.currency.format(cost)
+                    ),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    " Today: {} sessions, {} tokens, {} ",
+                    sessions.len(),
+                    format_tokens(app.usage.tokens_today),
+                    app.config.currency.format(app.usage.cost_today)
+                ))
+                .title_bottom(Line::from(Span::styled(
+                    " j/k:move  Enter:show in monitor ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !sessions.is_empty() {
+        state.select(Some(app.views.dashboard_selected.min(sessions.len() - 1)));
+    }
+    f.render_stateful_widget(list, chunks[0], &mut state);
+    draw_heatmap(
+        f,
+        app,
+        chunks[1],
+        app.views.heat_metric,
+        " m:tokens/messages ",
+    );
+}
+
+/// Projects ranked by estimated cost over the chosen window, or the
+/// sessions of the one drilled down into
+fn draw_projects(f: &mut Frame, app: &App, area: Rect) {
+    let window = app.views.projects_window;
+    let since = window.since(Utc::now());
+    let currency = &app.config.currency;
+    // Each row's part of the total cost, as a percentage and a bar
+    let share = |cost: f64, total: f64| {
+        let percent = if total > 0.0 {
+            cost * 100.0 / total
+        } else {
+            0.0
+        };
+        let bar = if app.config.screen_reader {
+            String::new()
+        } else {
+            format!("  {}", gauge(percent.round() as u64, 10))
+        };
+        (percent, bar)
+    };
+
+    let (title, hint, header, items, selected) = if let Some(ref project) = app.views.project_open {
+        let sessions = report::project_sessions(&app.sessions, &app.prices, project, since);
+        let total: f64 = sessions.iter().map(|(_, spend)| spend.cost).sum();
+        let name_width = (area.width as usize)
+            .saturating_sub(2 + 8 + 33 + 12)
+            .clamp(1, 48);
+        let items: Vec<ListItem> = sessions
+            .iter()
+            .map(|(id, spend)| {
+                let session = &app.sessions[id];
+                let (percent, bar) = share(spend.cost, total);
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "{:<width$}",
+                            truncate_width(&app.private(&session.display_name()), name_width),
+                            width = name_width
+                        ),
+                        Style::default().fg(Color::White),
+                    ),
+                    Span::styled(
+                        format!("  {:<8}", app.clock(session.last_activity)),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        spend.format(currency, percent),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::styled(bar, Style::default().fg(Color::Cyan)),
+                ]))
+            })
+            .collect();
+        let header = format!(
+            "{:<name_width$}  {:<8}{:>12}  {:>11}  {:>6}",
+            "Session", "Last", "Tokens", "Cost", "Share"
+        );
+        (
+            format!(
+                " {} - {} sessions, {} over {} ",
+                app.private(project),
+                sessions.len(),
+                currency.format(total),
+                window.label()
+            ),
+            " j/k:move  Enter:show in monitor  Tab:span  Esc:projects ",
+            header,
+            items,
+            app.views.project_session_selected,
+        )
+    } else {
+        let mut projects = report::project_summaries(&app.sessions, &app.prices, since);
+        for project in &mut projects {
+            project.name = app.private(&project.name).into_owned();
+        }
+        let total: f64 = projects.iter().map(|p| p.spend.cost).sum();
+        let items: Vec<ListItem> = projects
+            .iter()
+            .enumerate()
+            .map(|(i, project)| {
+                let (percent, bar) = share(project.spend.cost, total);
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        project.format(i + 1, currency, percent),
+                        Style::default().fg(Color::White),
+                    ),
+                    Span::styled(bar, Style::default().fg(Color::Cyan)),
+                    Span::styled(
+                        format!("  {}", app.clock(project.last_active)),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]))
+            })
+            .collect();
+        (
+            format!(
+                " Projects by cost - {} over {}, estimated {} ",
+                currency.format(total),
+                window.label(),
+                currency.code
+            ),
+            " j/k:move  Enter:sessions  f:filter monitor  Tab:span ",
+            ProjectSummary::header(),
+            items,
+            app.views.projects_selected,
+        )
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(Line::from(Span::styled(
+            hint,
+            Style::default().fg(Color::DarkGray),
+        )))
+        .border_style(Style::default().fg(Color::Cyan));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(block.inner(area));
+    f.render_widget(block, area);
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            header,
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )),
+        chunks[0],
+    );
+    let len = items.len();
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    if len > 0 {
+        state.select(Some(selected.min(len - 1)));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+/// Keys of every view, for the help view
+const VIEW_KEYS: [(View, &[(&str, &str)]); 5] = [
+    (
+        View::Monitor,
+        &[
+            ("Tab", "switch focus between sessions, chat and side panel"),
+            (
+                "j/k, gg/G, Home/End",
+                "move, jump to top/end (counts such as 5j, 5gg work)",
+            ),
+            (
+                "^D/^U, ^F/^B, PgDn/PgUp",
+                "scroll the chat half or a whole page",
+            ),
+            ("Enter", "open the selected session"),
+            ("/", "filter sessions (Up/Down: history)"),
+            ("F", "saved filters"),
+            ("a", "only active sessions"),
+            ("I", "newest or oldest first"),
+            ("l", "choose session list columns"),
+            ("R / N / t", "rename, note, tags"),
+            ("s / S", "star message, starred messages"),
+            ("J/K", "next/previous message"),
+            ("o / T", "fold message, show long messages in full"),
+            ("]u / [u", "next/previous prompt"),
+            ("f", "find in chat"),
+            ("y", "copy"),
+            ("V / M", "chat style, minimap"),
+            ("O / W", "outline, references"),
+            ("e / w", "file references, URLs"),
+            ("H / i", "commits, show commit"),
+            ("D / v", "compare, replay"),
+            ("x / X", "snapshot"),
+            ("C / L / h", "message classes, latency, heatmap"),
+            ("E", "parse errors"),
+            ("U / B", "largest sessions, usage (the stats view)"),
+            ("^W", "workspaces"),
+            ("p / Space", "pause"),
+            ("P / Z / m", "privacy, 12/24h clock, mouse"),
+            ("r", "refresh"),
+            ("q, ^C", "quit"),
+        ],
+    ),
+    (
+        View::Dashboard,
+        &[
+            ("j/k", "move through today's sessions"),
+            ("Enter", "show the session in the monitor"),
+            ("m", "shade the heatmap by tokens or messages"),
+        ],
+    ),
+    (
+        View::Stats,
+        &[
+            ("Tab", "months or weeks"),
+            ("b", "split by model or project"),
+            ("j/k", "scroll"),
+        ],
+    ),
+    (
+        View::Projects,
+        &[
+            ("j/k", "move"),
+            ("Tab", "rank over 24h, 7 days, 30 days or all time"),
+            ("Enter", "the project's sessions, then one in the monitor"),
+            ("f", "the monitor, filtered to the project"),
+            ("Esc", "back from a project's sessions"),
+        ],
+    ),
+    (View::Help, &[("j/k, PgUp/PgDn, g", "scroll")]),
+];
+
+fn draw_help(f: &mut Frame, app: &mut App, area: Rect) {
+    let key_style = Style::default().fg(Color::Yellow);
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(format!("{:<20}", "F1-F5"), key_style),
+            Span::raw("switch view (1-5 too, outside the monitor)"),
+        ]),
+        Line::from(vec![
+            Span::styled(format!("{:<20}", "Esc"), key_style),
+            Span::raw("back to the monitor"),
+        ]),
+    ];
+    for (view, keys) in VIEW_KEYS {
+        lines.push(Line::raw(""));
+        lines.push(Line::from(Span::styled(
+            view.label(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for (key, action) in keys {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:<20}", key), key_style),
+                Span::raw(*action),
+            ]));
+        }
+    }
+    let visible = area.height.saturating_sub(2) as usize;
+    app.views.help_max_scroll = lines.len().saturating_sub(visible);
+    app.views.help_scroll = app.views.help_scroll.min(app.views.help_max_scroll);
+    let scroll = app.views.help_scroll;
+    let paragraph = Paragraph::new(lines).scroll((scroll as u16, 0)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Keys ")
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    f.render_widget(paragraph, area);
+}
+
+/// Replace the glyphs, borders and bars drawn with ASCII stand-ins, for
+/// terminals whose font or encoding garbles them. This runs over the whole
+/// buffer, so message text holding one of those glyphs (a box-drawn table,
+/// a bullet) changes too; characters without a stand-in are left alone.
+fn ascii(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if cell.symbol().is_ascii() {
+            continue;
+        }
+        if let Some(symbol) = ascii_symbol(cell.symbol()) {
+            cell.set_symbol(symbol);
+        }
+    }
+}
+
+/// ASCII stand-in for a decorative `symbol`
+fn ascii_symbol(symbol: &str) -> Option<&'static str> {
+    let mut chars = symbol.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(match c {
+        '─' | '━' | '═' | '╌' | '┄' | '—' => "-",
+        '│' | '┃' | '║' | '╎' | '┆' => "|",
+        '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' | '╭' | '╮' | '╰' | '╯' | '╔' | '╗'
+        | '╚' | '╝' | '┏' | '┓' | '┗' | '┛' => "+",
+        '●' | '★' => "*",
+        '○' | '◌' => "o",
+        '·' => ".",
+        '…' => "~",
+        '×' | '✗' => "x",
+        '⚑' | '⚠' => "!",
+        '◔' => "%",
+        '◷' | '⏱' => "@",
+        '→' | '›' | '▶' | '▸' | '↳' => ">",
+        '←' | '‹' => "<",
+        '↓' => "v",
+        '↑' => "^",
+        '⇄' => "=",
+        '≠' => "#",
+        '⚙' => "+",
+        '⏸' | '▐' | '▌' => "|",
+        '░' | '▒' => ":",
+        // Bars, gauges and sparklines
+        '\u{2580}'..='\u{259f}' => "#",
+        // Chart dots
+        '\u{2800}' => " ",
+        '\u{2801}'..='\u{28ff}' => ".",
+        _ => return None,
+    })
+}
+
+/// A color `t` (0..=1) of the way from `from` to `to`
+fn blend(from: (u8, u8, u8), to: (u8, u8, u8), t: f64) -> Color {
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+/// Turn the yellow border of the focused panel in `area` into a gradient
+/// from its top left corner to its bottom right
+fn shade_border(buffer: &mut Buffer, area: Rect, background: Background) {
+    let (from, to) = match background {
+        Background::Light => ((175, 125, 0), (195, 70, 20)),
+        Background::Auto | Background::Dark => ((255, 215, 90), (255, 120, 70)),
+    };
+    let span = (area.width + area.height).saturating_sub(2).max(1) as f64;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let edge = y == area.top()
+                || y + 1 == area.bottom()
+                || x == area.left()
+                || x + 1 == area.right();
+            if !edge {
+                continue;
+            }
+            if let Some(cell) = buffer.cell_mut((x, y))
+                && cell.fg == Color::Yellow
+            {
+                let t = (x - area.left() + y - area.top()) as f64 / span;
+                cell.set_fg(blend(from, to, t));
+            }
+        }
+    }
+}
+
+/// Background tint of a message's rows in the truecolor palette
+fn role_tint(msg: &SessionMessage, background: Background) -> Option<Color> {
+    let light = background == Background::Light;
+    let rgb = match msg.msg_type {
+        MessageType::User if !msg.is_prompt() => return None,
+        MessageType::User if light => (232, 245, 233),
+        MessageType::User => (22, 38, 26),
+        MessageType::Assistant if light => (233, 238, 250),
+        MessageType::Assistant => (22, 28, 42),
+        MessageType::ToolUse if light => (243, 236, 247),
+        MessageType::ToolUse => (34, 26, 38),
+        MessageType::Progress | MessageType::Other => return None,
+    };
+    Some(Color::Rgb(rgb.0, rgb.1, rgb.2))
+}
+
+/// Swap the colors picked for a dark background for ones that read on a
+/// light one: white text goes black, gray, yellow and the light hues go
+/// darker, and gray highlights and bubble tints go pale
+fn light_theme(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        let fg = match cell.fg {
+            Color::White => Color::Black,
+            Color::Gray => Color::Indexed(238),
+            Color::DarkGray => Color::Indexed(242),
+            Color::Yellow => Color::Indexed(136),
+            Color::LightYellow => Color::Indexed(130),
+            Color::Green | Color::LightGreen => Color::Indexed(28),
+            Color::Cyan | Color::LightCyan => Color::Indexed(30),
+            Color::LightBlue => Color::Blue,
+            Color::LightMagenta => Color::Magenta,
+            fg => fg,
+        };
+        let bg = match cell.bg {
+            Color::DarkGray => Color::Indexed(252),
+            Color::Indexed(236) => Color::Indexed(254),
+            Color::Indexed(22) => Color::Indexed(194),
+            bg => bg,
+        };
+        cell.set_fg(fg).set_bg(bg);
+    }
+}
+
+/// Strip the colors from everything drawn, keeping what they told apart as
+/// attributes: highlights reverse, warnings and the focused border go bold,
+/// errors are underlined too, and gray text stays dim. Indexed backgrounds
+/// are only tints (bubbles) and are dropped.
+fn monochrome(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        let mut modifier = cell.modifier;
+        if !matches!(cell.bg, Color::Reset | Color::Indexed(_)) {
+            modifier |= Modifier::REVERSED;
+        }
+        modifier |= match cell.fg {
+            Color::Red | Color::LightRed => Modifier::BOLD | Modifier::UNDERLINED,
+            Color::Yellow | Color::LightYellow => Modifier::BOLD,
+            Color::DarkGray => Modifier::DIM,
+            _ => Modifier::empty(),
+        };
+        cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        cell.modifier = modifier;
+    }
+}
+
+fn draw_side_panel(f: &mut Frame, app: &mut App, area: Rect, panel: SidePanel) {
+    let (title, hint, items) = match panel {
+        SidePanel::References => {
+            let references = app.references();
+            let items: Vec<ListItem> = references
+                .iter()
+                .map(|reference| {
+                    let (text, color) = match reference.link {
+                        Link::Url(ref url) => (url.clone(), Color::LightBlue),
+                        Link::File(ref file_ref) => (file_ref.to_string(), Color::Cyan),
+                    };
+                    let mut spans = vec![Span::styled(text, Style::default().fg(color))];
+                    if reference.messages.len() > 1 {
+                        spans.push(Span::styled(
+                            format!(" ×{}", reference.messages.len()),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect();
+            (
+                format!(" References ({}) ", items.len()),
+                " Enter:jump  o:open  W:close ",
+                items,
+            )
+        }
+        SidePanel::Outline => {
+            let session = app.selected();
+            let items: Vec<ListItem> = app
+                .outline()
+                .iter()
+                .enumerate()
+                .filter_map(|(n, &idx)| {
+                    let msg = session?.messages.get(idx)?;
+                    let content = displayed_content(app, msg);
+                    let first = content.lines().find(|l| !l.trim().is_empty());
+                    Some(ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{:>3} ", n + 1),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::styled(
+                            format!("{} ", app.clock(msg.timestamp)),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::raw(first.unwrap_or("").trim().to_string()),
+                    ])))
+                })
+                .collect();
+            (
+                format!(" Outline ({} prompts) ", items.len()),
+                " j/k:go to prompt  O:close ",
+                items,
+            )
+        }
+    };
+    let len = items.len();
+    app.side_selected = app.side_selected.min(len.saturating_sub(1));
+
+    let border_color = if app.focus == FocusPanel::Side {
+        Color::Yellow
+    } else {
+        Color::DarkGray
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    hint,
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(border_color)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default().with_offset(app.side_offset);
+    if len > 0 {
+        state.select(Some(app.side_selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+    // Kept for mapping clicks to rows
+    app.side_offset = state.offset();
+}
+
+/// Centered rect covering the given percentage of `area`
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+fn draw_popup(f: &mut Frame, app: &App, popup: &Popup) {
+    // Two transcripts side by side, or a year of weeks, need the room
+    let area = match popup {
+        Popup::Compare { .. } | Popup::CommitDiff { .. } | Popup::Heatmap { .. } => {
+            centered_rect(94, 90, f.area())
+        }
+        _ => centered_rect(70, 70, f.area()),
+    };
+    f.render_widget(Clear, area);
+    match popup {
+        Popup::LargestSessions {
+            metric,
+            selected,
+            confirm_delete,
+        } => draw_largest_sessions(f, app, area, *metric, *selected, confirm_delete.as_deref()),
+        Popup::Starred { selected } => draw_starred(f, app, area, *selected),
+        Popup::SavedFilters { selected } => draw_saved_filters(f, app, area, *selected),
+        Popup::Workspaces { selected } => draw_workspaces(f, app, area, *selected),
+        Popup::ParseErrors { selected } => draw_parse_errors(f, app, area, *selected),
+        Popup::MessageClasses { selected } => draw_message_classes(f, app, area, *selected),
+        Popup::Columns { selected } => draw_columns(f, app, area, *selected),
+        Popup::ResponseTimes { window, breakdown } => {
+            draw_response_times(f, app, area, *window, *breakdown)
+        }
+        Popup::Heatmap { metric } => {
+            draw_heatmap(f, app, area, *metric, " m:tokens/messages  Esc:close ")
+        }
+        Popup::Compare { row } => {
+            if let Some(ref comparison) = app.comparison {
+                draw_compare(f, app, area, comparison, *row);
+            }
+        }
+        Popup::Commits { selected } => draw_commits(f, app, area, *selected),
+        Popup::CommitDiff { hash, scroll } => draw_commit_diff(f, app, area, hash, *scroll),
+        Popup::FileRefs { selected } => draw_file_refs(f, app, area, *selected),
+        Popup::Urls { selected } => draw_urls(f, app, area, *selected),
+    }
+}
+
+/// Heatmap cells from no activity to the busiest days, shaded so the
+/// levels still read without color
+const HEAT_GLYPHS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+const HEAT_COLORS: [Color; 5] = [
+    Color::DarkGray,
+    Color::Green,
+    Color::Green,
+    Color::LightGreen,
+    Color::LightGreen,
+];
+
+/// Days as a grid of weeks (columns, the current one last) by weekday
+/// (rows), as many weeks as fit, shaded by `metric`
+fn draw_heatmap(f: &mut Frame, app: &App, area: Rect, metric: HeatMetric, hint: &str) {
+    let totals = heatmap::daily_totals(&app.sessions, &app.zone, metric);
+    let today = app.zone.convert(Utc::now()).date_naive();
+    // Weekday labels take 4 cells and each week 2
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let weeks = (inner_width.saturating_sub(4) / 2).clamp(1, 53);
+    let monday = today - TimeDelta::days(today.weekday().num_days_from_monday() as i64);
+    let first = monday - TimeDelta::weeks(weeks as i64 - 1);
+    let value = |day| totals.get(&day).copied().unwrap_or(0);
+    let days: Vec<_> = first.iter_days().take_while(|d| *d <= today).collect();
+    let max = days.iter().map(|&d| value(d)).max().unwrap_or(0);
+    let reader = app.config.screen_reader;
+    let format = |v: u64| match metric {
+        HeatMetric::Tokens => format_tokens(v),
+        HeatMetric::Messages => v.to_string(),
+    };
+
+    // Month names over the first week of each month, where there is room
+    let mut months = " ".repeat(4);
+    for week in 0..weeks {
+        let start = first + TimeDelta::weeks(week as i64);
+        let column = 4 + week * 2;
+        if (week == 0 || start.day() <= 7) && months.len() <= column {
+            months.push_str(&" ".repeat(column - months.len()));
+            months.push_str(&start.format("%b").to_string());
+        }
+    }
+    let mut lines = vec![Line::from(Span::styled(
+        months,
+        Style::default().fg(Color::DarkGray),
+    ))];
+    for weekday in 0..7 {
+        let label = ["Mon", "", "Wed", "", "Fri", "", ""][weekday];
+        let mut spans = vec![Span::styled(
+            format!("{:<4}", label),
+            Style::default().fg(Color::DarkGray),
+        )];
+        for week in 0..weeks {
+            let day = first + TimeDelta::days((week * 7 + weekday) as i64);
+            if day > today {
+                break;
+            }
+            let level = heatmap::level(value(day), max);
+            let cell = if reader {
+                format!("{} ", level)
+            } else {
+                format!("{} ", HEAT_GLYPHS[level])
+            };
+            spans.push(Span::styled(cell, Style::default().fg(HEAT_COLORS[level])));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::raw(""));
+    let mut legend = vec![Span::styled("Less ", Style::default().fg(Color::DarkGray))];
+    for (level, glyph) in HEAT_GLYPHS.iter().enumerate() {
+        let glyph = if reader {
+            level.to_string()
+        } else {
+            glyph.to_string()
+        };
+        legend.push(Span::styled(
+            format!("{} ", glyph),
+            Style::default().fg(HEAT_COLORS[level]),
+        ));
+    }
+    legend.push(Span::styled("More", Style::default().fg(Color::DarkGray)));
+    lines.push(Line::from(legend));
+    let total: u64 = days.iter().map(|&d| value(d)).sum();
+    let active = days.iter().filter(|&&d| value(d) > 0).count();
+    let mut summary = format!(
+        "{} {} on {} of {} days",
+        format(total),
+        metric.label(),
+        active,
+        days.len()
+    );
+    if let Some(&busiest) = days.iter().rev().find(|&&d| max > 0 && value(d) == max) {
+        summary.push_str(&format!(
+            "; busiest {} ({})",
+            busiest.format("%Y-%m-%d"),
+            format(max)
+        ));
+    }
+    lines.push(Line::raw(summary));
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Activity by day: {} ", metric.label()))
+            .title_bottom(Line::from(Span::styled(
+                hint.to_string(),
+                Style::default().fg(Color::DarkGray),
+            )))
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(paragraph, area);
+}
+
+/// Colors of the response time chart series, the biggest group first
+const SERIES_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::LightMagenta,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightRed,
+];
+
+fn draw_response_times(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    window: ChartWindow,
+    breakdown: Breakdown,
+) {
+    let now = Utc::now();
+    let since = now - window.span();
+    let samples = latency::samples(&app.sessions, since, breakdown);
+
+    // Biggest groups get their own series, the rest share "other"
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for sample in &samples {
+        *counts.entry(sample.group.as_str()).or_default() += 1;
+    }
+    let mut groups: Vec<(&str, usize)> = counts.into_iter().collect();
+    groups.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let mut names: Vec<&str> = groups.iter().map(|(g, _)| *g).collect();
+    if names.len() > SERIES_COLORS.len() {
+        names.truncate(SERIES_COLORS.len() - 1);
+        names.push("other");
+    }
+    let mut series: Vec<Vec<(f64, f64)>> = vec![Vec::new(); names.len()];
+    let mut elapsed: Vec<Vec<TimeDelta>> = vec![Vec::new(); names.len()];
+    for sample in &samples {
+        let i = names
+            .iter()
+            .position(|n| *n == sample.group)
+            .unwrap_or(names.len() - 1);
+        series[i].push((
+            sample.at.timestamp() as f64,
+            sample.elapsed.num_seconds() as f64,
+        ));
+        elapsed[i].push(sample.elapsed);
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " Response times - last {} by {} ({} turns) ",
+            window.label(),
+            breakdown.label(),
+            samples.len()
+        ))
+        .title_bottom(Line::from(Span::styled(
+            " Tab:span  b:split by  Esc:close ",
+            Style::default().fg(Color::DarkGray),
+        )))
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if samples.is_empty() {
+        f.render_widget(
+            Paragraph::new(Span::styled(
+                "No answered turns in this span (only loaded messages count)",
+                Style::default().fg(Color::DarkGray),
+            )),
+            inner,
+        );
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(6),
+            Constraint::Length(names.len() as u16 + 1),
+        ])
+        .split(inner);
+
+    let datasets: Vec<Dataset> = names
+        .iter()
+        .zip(&series)
+        .zip(SERIES_COLORS)
+        .map(|((name, points), color)| {
+            Dataset::default()
+                .name(name.to_string())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(color))
+                .data(points)
+        })
+        .collect();
+    let slowest = samples
+        .iter()
+        .map(|s| s.elapsed)
+        .max()
+        .unwrap_or_default()
+        .max(TimeDelta::seconds(1));
+    let time_format = match window {
+        ChartWindow::Day => "%H:%M",
+        ChartWindow::Week | ChartWindow::Month => "%m-%d",
+    };
+    let time_label = |t: DateTime<Utc>| app.zone.convert(t).format(time_format).to_string();
+    let axis_style = Style::default().fg(Color::DarkGray);
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([since.timestamp() as f64, now.timestamp() as f64])
+                .labels([
+                    time_label(since),
+                    time_label(since + window.span() / 2),
+                    "now".to_string(),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([0.0, slowest.num_seconds() as f64])
+                .labels([
+                    "0s".to_string(),
+                    latency::format_latency(slowest / 2),
+                    latency::format_latency(slowest),
+                ]),
+        )
+        .legend_position(None);
+    f.render_widget(chart, chunks[0]);
+
+    let mut table = vec![Line::from(Span::styled(
+        format!(
+            "{:<32}  {:>6}  {:>7}  {:>7}  {:>7}",
+            "", "Turns", "Median", "P90", "Max"
+        ),
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    for ((name, elapsed), color) in names.iter().zip(elapsed).zip(SERIES_COLORS) {
+        let Some(stats) = LatencyStats::new(elapsed) else {
+            continue;
+        };
+        table.push(Line::from(vec![
+            Span::styled(
+                format!("● {:<30}", truncate_width(name, 30)),
+                Style::default().fg(color),
+            ),
+            Span::raw(format!(
+                "  {:>6}  {:>7}  {:>7}  {:>7}",
+                stats.turns,
+                latency::format_latency(stats.median),
+                latency::format_latency(stats.p90),
+                latency::format_latency(stats.max)
+            )),
+        ]));
+    }
+    f.render_widget(Paragraph::new(table), chunks[1]);
+}
+
+fn draw_compare(f: &mut Frame, app: &App, area: Rect, comparison: &Comparison, row: usize) {
+    let name = |id: &str| {
+        app.sessions.get(id).map_or_else(
+            || id.to_string(),
+            |s| app.private(&s.display_name()).into_owned(),
+        )
+    };
+    let differing = comparison.rows.iter().filter(|r| r.differs()).count();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " Compare: A {}  ⇄  B {} - {} turns, {} differ ",
+            name(&comparison.left_id),
+            name(&comparison.right_id),
+            comparison.rows.len(),
+            differing
+        ))
+        .title_bottom(Line::from(Span::styled(
+            " j/k:turn  n/N:next/prev difference  g/G:first/last  Esc:close ",
+            Style::default().fg(Color::DarkGray),
+        )))
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let width = inner.width as usize;
+    let mut lines = compare_stats_lines(app, comparison, width);
+    lines.push(Line::raw(""));
+    for (i, turn) in comparison.rows.iter().enumerate().skip(row) {
+        if lines.len() >= inner.height as usize {
+            break;
+        }
+        lines.extend(compare_turn_lines(app, i, turn, width));
+        lines.push(Line::raw(""));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Signed difference of B over A, formatted by `format`
+fn delta<T: PartialOrd>(a: T, b: T, diff: impl Fn(T, T) -> String) -> String {
+    if b > a {
+        format!("+{}", diff(b, a))
+    } else if b < a {
+        format!("-{}", diff(a, b))
+    } else {
+        "=".to_string()
+    }
+}
+
+fn compare_stats_lines(app: &App, comparison: &Comparison, width: usize) -> Vec<Line<'static>> {
+    let (a, b) = (&comparison.left, &comparison.right);
+    let currency = &app.config.currency;
+    let duration = |s: &Stats| s.duration.map_or("-".to_string(), format_duration);
+    let duration_delta = match (a.duration, b.duration) {
+        (Some(x), Some(y)) => delta(x.num_minutes(), y.num_minutes(), |hi, lo| {
+            format_duration(TimeDelta::minutes(hi - lo))
+        }),
+        _ => String::new(),
+    };
+    let count = |x: usize, y: usize| delta(x, y, |hi, lo| (hi - lo).to_string());
+    let rows = [
+        (
+            "tokens",
+            format_tokens(a.tokens),
+            format_tokens(b.tokens),
+            delta(a.tokens, b.tokens, |hi, lo| format_tokens(hi - lo)),
+        ),
+        (
+            "cost",
+            currency.format(a.cost),
+            currency.format(b.cost),
+            delta(a.cost, b.cost, |hi, lo| currency.format(hi - lo)),
+        ),
+        ("duration", duration(a), duration(b), duration_delta),
+        (
+            "messages",
+            a.messages.to_string(),
+            b.messages.to_string(),
+            count(a.messages, b.messages),
+        ),
+        (
+            "prompts",
+            a.prompts.to_string(),
+            b.prompts.to_string(),
+            count(a.prompts, b.prompts),
+        ),
+        (
+            "tool calls",
+            a.tool_calls().to_string(),
+            b.tool_calls().to_string(),
+            count(a.tool_calls(), b.tool_calls()),
+        ),
+        (
+            "model",
+            a.model.clone().unwrap_or_else(|| "-".to_string()),
+            b.model.clone().unwrap_or_else(|| "-".to_string()),
+            String::new(),
+        ),
+    ];
+    let header_style = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{:<12}{:>22}{:>22}{:>12}", "", "A", "B", "Δ"),
+        header_style,
+    ))];
+    for (label, left, right, change) in rows {
+        let change_style = match change.chars().next() {
+            Some('+') => Style::default().fg(Color::Red),
+            Some('-') => Style::default().fg(Color::Green),
+            _ => Style::default().fg(Color::DarkGray),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<12}", label), Style::default().fg(Color::Cyan)),
+            Span::raw(format!(
+                "{:>22}{:>22}",
+                truncate_width(&left, 21),
+                truncate_width(&right, 21)
+            )),
+            Span::styled(format!("{:>12}", change), change_style),
+        ]));
+    }
+
+    let mut tools = vec![Span::styled(
+        format!("{:<12}", "tools"),
+        Style::default().fg(Color::Cyan),
+    )];
+    let mut used = 12;
+    for (name, left, right) in comparison.tool_counts() {
+        let text = format!("{} {}→{}  ", name, left, right);
+        if used + text.width() > width {
+            tools.push(Span::styled("…", Style::default().fg(Color::DarkGray)));
+            break;
+        }
+        used += text.width();
+        let style = if left == right {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::Yellow)
+        };
+        tools.push(Span::styled(text, style));
+    }
+    lines.push(Line::from(tools));
+    lines
+}
+
+/// Two texts in columns split by a bar, one visual line per text line
+fn side_by_side(
+    left: &[String],
+    right: &[String],
+    style: Style,
+    width: usize,
+) -> Vec<Line<'static>> {
+    let column = width.saturating_sub(3) / 2;
+    let empty = String::new();
+    (0..left.len().max(right.len()))
+        .map(|i| {
+            let l = truncate_width(left.get(i).unwrap_or(&empty), column);
+            let r = truncate_width(right.get(i).unwrap_or(&empty), column);
+            let pad = column.saturating_sub(l.width());
+            Line::from(vec![
+                Span::styled(format!("{}{}", l, " ".repeat(pad)), style),
+                Span::styled(" │ ", Style::default().fg(Color::DarkGray)),
+                Span::styled(r, style),
+            ])
+        })
+        .collect()
+}
+
+fn compare_turn_lines(app: &App, index: usize, row: &TurnRow, width: usize) -> Vec<Line<'static>> {
+    let prompt = |text: &str| {
+        let text = if app.privacy {
+            redact::blur(text)
+        } else {
+            app.redactor.redact(text).into_owned()
+        };
+        text.lines().next().unwrap_or_default().to_string()
+    };
+    let missing = "(no such turn)".to_string();
+    let marker = if row.differs() {
+        Span::styled(
+            format!("#{:<3} ≠ ", index + 1),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+    } else {
+        Span::styled(
+            format!("#{:<3} = ", index + 1),
+            Style::default().fg(Color::Green),
+        )
+    };
+    let prompt_style = Style::default().fg(Color::Cyan);
+
+    let mut lines = Vec::new();
+    match (&row.left, &row.right) {
+        (Some(left), Some(_)) if row.same_prompt() => {
+            let text = prompt(&left.prompt);
+            lines.push(Line::from(vec![
+                marker,
+                Span::styled(truncate_width(&text, width.saturating_sub(7)), prompt_style),
+            ]));
+        }
+        (left, right) => {
+            lines.push(Line::from(marker));
+            let side = |turn: &Option<Turn>| {
+                vec![turn.as_ref().map_or(missing.clone(), |t| prompt(&t.prompt))]
+            };
+            lines.extend(side_by_side(&side(left), &side(right), prompt_style, width));
+        }
+    }
+
+    if !row.differs() {
+        lines.push(Line::from(Span::styled(
+            "  same response",
+            Style::default().fg(Color::DarkGray),
+        )));
+        return lines;
+    }
+    let response = |turn: &Option<Turn>| -> Vec<String> {
+        let Some(turn) = turn else {
+            return Vec::new();
+        };
+        let text = app.redactor.redact(&turn.response);
+        let mut out: Vec<String> = text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .take(COMPARE_RESPONSE_LINES)
+            .map(str::to_string)
+            .collect();
+        if !turn.tools.is_empty() {
+            out.push(format!("[tools: {}]", turn.tools.join(", ")));
+        }
+        out
+    };
+    lines.extend(side_by_side(
+        &response(&row.left),
+        &response(&row.right),
+        Style::default().fg(Color::White),
+        width,
+    ));
+    lines
+}
+
+fn draw_billing(f: &mut Frame, app: &App, area: Rect) {
+    let (period, breakdown) = (app.views.stats_period, app.views.stats_breakdown);
+    let mut rows = report::billing_rows(&app.sessions, &app.prices, &app.zone, period, breakdown);
+    if breakdown == Breakdown::Project {
+        for row in &mut rows {
+            row.group = row.group.as_deref().map(|g| app.private(g).into_owned());
+        }
+    }
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|row| {
+            let style = if row.group.is_some() {
+                Style::default().fg(Color::White)
+            } else {
+                Style::default().fg(Color::Cyan)
+            };
+            ListItem::new(Line::from(Span::styled(
+                row.format(&app.config.currency),
+                style,
+            )))
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " Usage by {} ({}) - estimated {} ",
+            period.label(),
+            breakdown.label(),
+            app.config.currency.code
+        ))
+        .title_bottom(Line::from(Span::styled(
+            " Tab:month/week  b:split by  j/k:scroll ",
+            Style::default().fg(Color::DarkGray),
+        )))
+        .border_style(Style::default().fg(Color::Cyan));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(block.inner(area));
+    f.render_widget(block, area);
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            BillingRow::header(period, breakdown),
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )),
+        chunks[0],
+    );
+
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    if !rows.is_empty() {
+        state.select(Some(app.views.stats_selected));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn draw_workspaces(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let entry = |name: &str, dirs: &[PathBuf], current: bool| {
+        let dirs: Vec<String> = dirs.iter().map(|d| d.display().to_string()).collect();
+        ListItem::new(Line::from(vec![
+            Span::styled(
+                if current { "● " } else { "  " },
+                Style::default().fg(Color::Green),
+            ),
+            Span::styled(name.to_string(), Style::default().fg(Color::White)),
+            Span::styled(
+                format!("  {}", dirs.join(", ")),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]))
+    };
+    let mut items = vec![entry(
+        "default",
+        std::slice::from_ref(&app.base_path),
+        app.workspace.is_none(),
+    )];
+    for workspace in &app.config.workspaces {
+        items.push(entry(
+            &workspace.name,
+            &workspace.dirs(),
+            app.workspace.as_ref() == Some(&workspace.name),
+        ));
+    }
+
+    let title = if app.config.workspaces.is_empty() {
+        " Workspaces (none - add [[workspaces]] to config.toml) ".to_string()
+    } else {
+        format!(" Workspaces ({}) ", app.config.workspaces.len())
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    " Enter:switch  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_saved_filters(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let filters = &app.config.saved_filters;
+    let items: Vec<ListItem> = filters
+        .iter()
+        .map(|saved| {
+            ListItem::new(Line::from(vec![
+                Span::styled(saved.name.clone(), Style::default().fg(Color::White)),
+                Span::styled(
+                    format!("  /{}", saved.filter),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let title = if filters.is_empty() {
+        " Saved filters (none - add [[saved_filters]] to config.toml) ".to_string()
+    } else {
+        format!(" Saved filters ({}) ", filters.len())
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    " Enter:apply  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !filters.is_empty() {
+        state.select(Some(selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_columns(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let items: Vec<ListItem> = app
+        .column_choices()
+        .into_iter()
+        .map(|(column, shown)| {
+            let width = column
+                .width(app)
+                .map_or_else(|| "undefined".to_string(), |w| format!("{} cells", w));
+            ListItem::new(Line::from(vec![
+                Span::raw(if shown { "[x] " } else { "[ ] " }),
+                Span::styled(
+                    column.name().to_string(),
+                    Style::default()
+                        .fg(column.color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("  {}", width), Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Session list columns ")
+                .title_bottom(Line::from(Span::styled(
+                    " Space:show/hide  J/K:move  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_message_classes(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let classes = &app.config.message_classes;
+    let items: Vec<ListItem> = classes
+        .iter()
+        .map(|class| {
+            let shown = !app.hidden_classes.contains(&class.name);
+            let mut conditions = Vec::new();
+            if let Some(ref tool) = class.tool {
+                conditions.push(format!("tool {}", tool));
+            }
+            if let Some(ref needle) = class.contains {
+                conditions.push(format!("contains {:?}", needle));
+            }
+            if !class.types.is_empty() {
+                let types: Vec<&str> = class.types.iter().map(|t| t.as_str()).collect();
+                conditions.push(types.join("/"));
+            }
+            ListItem::new(Line::from(vec![
+                Span::raw(if shown { "[x] " } else { "[ ] " }),
+                Span::styled(
+                    class.name.clone(),
+                    Style::default()
+                        .fg(class.color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("  {}", conditions.join(", ")),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let title = if classes.is_empty() {
+        " Message classes (none - add [[message_classes]] to config.toml) ".to_string()
+    } else {
+        format!(" Message classes ({}) ", classes.len())
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    " Space:show/hide  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !classes.is_empty() {
+        state.select(Some(selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
 
-use crate::app::{App, FocusPanel};
-use crate::message::MessageType;
+fn draw_starred(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let starred = app.starred_message_indices();
+    let messages = app.selected().map(|s| &s.messages[..]).unwrap_or(&[]);
+    let items: Vec<ListItem> = starred
+        .iter()
+        .filter_map(|&idx| messages.get(idx))
+        .map(|msg| {
+            let (prefix, style) = role_style(&msg.msg_type);
+            let time = app.clock(msg.timestamp);
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("[{}] ", time), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{}: ", prefix), style),
+                Span::styled(
+                    displayed_content(app, msg)
+                        .lines()
+                        .next()
+                        .unwrap_or("")
+                        .to_string(),
+                    Style::default().fg(Color::White),
+                ),
+            ]))
+        })
+        .collect();
 
-pub fn draw(f: &mut Frame, app: &mut App) {
-    // Main layout: top area + bottom status bar
-    let main_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(1)])
-        .split(f.area());
+    let title = if items.is_empty() {
+        " Starred messages (none - J/K to pick a message, s to star) ".to_string()
+    } else {
+        format!(" Starred messages ({}) ", items.len())
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    " Enter:jump  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !starred.is_empty() {
+        state.select(Some(selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
 
-    // Top area: left panel + right chat panel
-    let h_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
-        .split(main_chunks[0]);
+fn draw_commits(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let session = app.selected();
+    let commits = app.session_commits().unwrap_or(&[]);
+    let items: Vec<ListItem> = commits
+        .iter()
+        .map(|commit| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("[{}] ", app.clock(commit.time)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{} ", &commit.hash[..7]),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(commit.subject.clone(), Style::default().fg(Color::White)),
+                Span::styled(
+                    format!(" ({})", commit.author),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
 
-    // Left panel: session list + session info
-    let left_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
-        .split(h_chunks[0]);
+    let state = match session {
+        None => "no session selected",
+        Some(s) if s.cwd.is_none() => "no working directory recorded",
+        Some(s) => match git::activity_window(s)
+            .and_then(|(since, _)| app.git.log(s.cwd.as_deref()?, since))
+        {
+            None => "loading…",
+            Some(None) => "not a repository",
+            Some(Some(_)) => "none",
+        },
+    };
+    let title = if items.is_empty() {
+        format!(" Commits during session ({}) ", state)
+    } else {
+        format!(" Commits during session ({}) ", items.len())
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    " Enter:jump to transcript  y:copy hash  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !commits.is_empty() {
+        state.select(Some(selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
 
-    // Store rects for mouse hit testing
-    app.session_list_area = left_chunks[0];
-    app.chat_area = h_chunks[1];
+fn draw_commit_diff(f: &mut Frame, app: &App, area: Rect, hash: &str, scroll: usize) {
+    let lines: Vec<Line> = match app.commit_diff(hash) {
+        Some(diff) => diff
+            .lines()
+            .map(|line| {
+                let color = if line.starts_with("+++") || line.starts_with("---") {
+                    Color::White
+                } else if line.starts_with('+') {
+                    Color::Green
+                } else if line.starts_with('-') {
+                    Color::Red
+                } else if line.starts_with("@@") {
+                    Color::Cyan
+                } else if line.starts_with("commit ") || line.starts_with("diff --git") {
+                    Color::Yellow
+                } else {
+                    Color::Gray
+                };
+                Line::from(Span::styled(line.to_string(), Style::default().fg(color)))
+            })
+            .collect(),
+        None => {
+            let cwd = app.selected().and_then(|s| s.cwd.as_deref()).unwrap_or("");
+            let text = match app.git.shown(cwd, hash) {
+                Some(None) => format!("git show {} failed in {}", hash, app.private(cwd)),
+                _ => "loading…".to_string(),
+            };
+            vec![Line::from(Span::styled(
+                text,
+                Style::default().fg(Color::DarkGray),
+            ))]
+        }
+    };
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Commit {} ", hash))
+                .title_bottom(Line::from(Span::styled(
+                    " j/k/PgUp/PgDn:scroll  g/G:top/end  y:copy hash  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .scroll((scroll.min(u16::MAX as usize) as u16, 0));
+    f.render_widget(paragraph, area);
+}
 
-    draw_session_list(f, app, left_chunks[0]);
-    draw_session_info(f, app, left_chunks[1]);
-    draw_chat_stream(f, app, h_chunks[1]);
-    draw_status_bar(f, app, main_chunks[1]);
+fn draw_file_refs(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let file_refs = app.selected_file_refs();
+    let cwd = app.selected().and_then(|s| s.cwd.as_deref());
+    let items: Vec<ListItem> = file_refs
+        .iter()
+        .map(|file_ref| {
+            let mut spans = vec![Span::styled(
+                file_ref.to_string(),
+                Style::default().fg(Color::LightBlue),
+            )];
+            if !file_ref.resolve(cwd).exists() {
+                spans.push(Span::styled(
+                    "  (not found)",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let title = if items.is_empty() {
+        " File references (none in the selected message) ".to_string()
+    } else {
+        format!(" File references ({}) ", items.len())
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    " Enter:open in $EDITOR  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !file_refs.is_empty() {
+        state.select(Some(selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_urls(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let urls = app.selected_urls();
+    let items: Vec<ListItem> = urls
+        .iter()
+        .map(|url| {
+            ListItem::new(Line::from(Span::styled(
+                url.as_str(),
+                Style::default().fg(Color::LightBlue),
+            )))
+        })
+        .collect();
+
+    let title = if items.is_empty() {
+        " URLs (none in the selected message) ".to_string()
+    } else {
+        format!(" URLs ({}) ", items.len())
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    " Enter:open in browser  y:copy  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !urls.is_empty() {
+        state.select(Some(selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_parse_errors(f: &mut Frame, app: &App, area: Rect, selected: usize) {
+    let diagnostics = app.selected().map(|s| &s.parse_diagnostics);
+    let errors: Vec<&ParseError> = diagnostics.map_or(Vec::new(), |d| d.recent.iter().collect());
+    let items: Vec<ListItem> = errors
+        .iter()
+        .map(|err| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("line {:>6}  ", err.line),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(err.error.as_str(), Style::default().fg(Color::Red)),
+            ]))
+        })
+        .collect();
+
+    let count = diagnostics.map_or(0, |d| d.count);
+    let title = if count == 0 {
+        " Parse errors (none) ".to_string()
+    } else if count > errors.len() {
+        format!(" Parse errors ({}, last {} shown) ", count, errors.len())
+    } else {
+        format!(" Parse errors ({}) ", count)
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom(Line::from(Span::styled(
+                    " j/k:move  Esc:close ",
+                    Style::default().fg(Color::DarkGray),
+                )))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !errors.is_empty() {
+        state.select(Some(selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_largest_sessions(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    metric: SizeMetric,
+    selected: usize,
+    confirm_delete: Option<&str>,
+) {
+    let ids = report::largest_session_ids(&app.sessions, metric, LARGEST_SESSIONS_TOP);
+    // Leave room for the size column and part of the project slug
+    let name_width = (area.width as usize).saturating_sub(2 + 11).max(1) * 2 / 3;
+    let items: Vec<ListItem> = ids
+        .iter()
+        .map(|id| {
+            let session = &app.sessions[id];
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:>9}  ", metric.format(metric.value(session))),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::styled(
+                    truncate_width(&app.private(&session.display_name()), name_width),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    format!("  [{}]", app.private(session.project_name())),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let footer = if let Some(id) = confirm_delete {
+        let name = app
+            .sessions
+            .get(id)
+            .map(|s| app.private(&s.display_name()).into_owned())
+            .unwrap_or_default();
+        Line::from(Span::styled(
+            format!(" Delete {} permanently? y:confirm  any key:cancel ", name),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        Line::from(Span::styled(
+            " Tab:metric  Enter:open  A:archive  X:delete  Esc:close ",
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Largest sessions by {} ", metric.label()))
+                .title_bottom(footer)
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !ids.is_empty() {
+        state.select(Some(selected));
+    }
+    f.render_stateful_widget(list, area, &mut state);
 }
 
 fn draw_session_list(f: &mut Frame, app: &mut App, area: Rect) {
+    let rows = app.list_rows();
     let sessions = &app.sorted_session_ids;
-    let items: Vec<ListItem> = sessions
+    let inner_width = area.width.saturating_sub(2) as usize;
+
+    // Only the visible window gets built: scroll it to keep the selection in
+    // view the way `List` would, and record the offset (in rows, headings
+    // included) for click hit testing
+    let height = (area.height.saturating_sub(2) as usize).max(1);
+    let selected = app
+        .list_state
+        .selected()
+        .and_then(|sel| rows.iter().position(|row| *row == ListRow::Session(sel)));
+    let mut offset = app
+        .list_state
+        .offset()
+        .min(rows.len().saturating_sub(height));
+    if let Some(sel) = selected {
+        if sel < offset {
+            // Bring the heading into view along with the first of its sessions
+            offset = match sel.checked_sub(1).map(|above| &rows[above]) {
+                Some(ListRow::Heading(_)) => sel - 1,
+                _ => sel,
+            };
+        } else if sel >= offset + height {
+            offset = sel + 1 - height;
+        }
+    }
+    *app.list_state.offset_mut() = offset;
+    let end = (offset + height + LIST_OVERSCAN).min(rows.len());
+
+    // Keep the configured columns that leave the name room, dropping from
+    // the right; the prefix, health glyph and " [n] HH:MM" suffix take
+    // about 16 cells
+    let mut columns: Vec<(&SessionColumn, usize)> = app
+        .session_columns
         .iter()
-        .enumerate()
-        .map(|(i, id)| {
+        .filter_map(|c| Some((c, c.width(app)?)))
+        .collect();
+    while columns.iter().map(|(_, w)| w + 1).sum::<usize>() + 16 + MIN_NAME_WIDTH > inner_width
+        && columns.pop().is_some()
+    {}
+    let columns_width: usize = columns.iter().map(|(_, w)| w + 1).sum();
+
+    let items: Vec<ListItem> = rows[offset.min(end)..end]
+        .iter()
+        .map(|row| {
+            let i = match row {
+                ListRow::Heading(label) => {
+                    let text = if app.config.screen_reader {
+                        label.clone()
+                    } else {
+                        format!("— {} —", label)
+                    };
+                    return ListItem::new(
+                        Line::from(Span::styled(text, Style::default().fg(Color::DarkGray)))
+                            .centered(),
+                    );
+                }
+                ListRow::Session(i) => *i,
+            };
+            let id = &sessions[i];
             let session = &app.sessions[id];
             let is_selected = app
                 .selected_session
@@ -52,7 +2148,10 @@ fn draw_session_list(f: &mut Frame, app: &mut App, area: Rect) {
                 .map(|s| s == id)
                 .unwrap_or(false);
             let is_active = session.is_active();
-            let prefix = if is_active {
+            let reader = app.config.screen_reader;
+            let prefix = if reader {
+                if is_active { "active " } else { "" }
+            } else if is_active {
                 "● "
             } else if is_selected {
                 "○ "
@@ -64,30 +2163,103 @@ fn draw_session_list(f: &mut Frame, app: &mut App, area: Rect) {
             } else {
                 Color::DarkGray
             };
-            let name = session.display_name();
-            let time = session
-                .last_activity
-                .with_timezone(&Local)
-                .format("%H:%M")
-                .to_string();
-            let msg_count = session.messages.len();
-
-            let style = if Some(i) == app.list_state.selected() {
+            let health = health::health(session, &app.config).state;
+            let glyph = match health {
+                _ if !reader => format!("{} ", health.glyph()),
+                HealthState::Ok => String::new(),
+                _ => format!("{}: ", health.label()),
+            };
+            let name = app.private(&session.display_name()).into_owned();
+            let time = app.clock(session.last_activity);
+            let msg_count = session.message_count();
+            let suffix = if reader {
+                format!(", {} messages, {}", msg_count, time)
+            } else {
+                format!(" [{}] {}", msg_count, time)
+            };
+            let tags_width: usize = session.tags.iter().map(|t| t.width() + 2).sum();
+            let errors = app.config.error_burst.burst(session).map(|b| {
+                if reader {
+                    format!(" {} errors ", b.count)
+                } else {
+                    format!(" ⚠{} ", b.count)
+                }
+            });
+            let errors_width = errors.as_ref().map_or(0, |e| e.width() + 1);
+            let context = app.config.context.usage(session).and_then(|usage| {
+                let level = app.config.context.level(&usage)?;
+                let label = if reader {
+                    format!(" context {}% full ", usage.percent())
+                } else {
+                    format!(" ◔{}% ", usage.percent())
+                };
+                Some((label, context_style(level)))
+            });
+            let context_width = context.as_ref().map_or(0, |(c, _)| c.width() + 1);
+            let name = truncate_width(
+                &name,
+                inner_width.saturating_sub(
+                    prefix.width()
+                        + glyph.width()
+                        + tags_width
+                        + errors_width
+                        + context_width
+                        + suffix.width()
+                        + columns_width,
+                ),
+            );
+
+            let mut style = if Some(i) == app.list_state.selected() {
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD)
             } else {
                 Style::default().fg(Color::White)
             };
+            if app.flashing.contains_key(id) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
 
-            ListItem::new(Line::from(vec![
+            let mut spans = vec![
                 Span::styled(prefix, Style::default().fg(prefix_color)),
+                Span::styled(glyph, Style::default().fg(health_color(health))),
                 Span::styled(name, style),
-                Span::styled(
-                    format!(" [{}] {}", msg_count, time),
-                    Style::default().fg(Color::DarkGray),
-                ),
-            ]))
+            ];
+            for tag in &session.tags {
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(
+                    format!("#{}", tag),
+                    Style::default().fg(Color::Black).bg(tag_color(tag)),
+                ));
+            }
+            if let Some(errors) = errors {
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(
+                    errors,
+                    Style::default()
+                        .fg(Color::White)
+                        .bg(Color::Red)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            if let Some((context, style)) = context {
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(context, style));
+            }
+            // Pad so the columns line up at the right edge
+            let used: usize = spans.iter().map(|s| s.width()).sum::<usize>() + suffix.width();
+            let pad = inner_width.saturating_sub(used + columns_width);
+            if !columns.is_empty() && pad > 0 {
+                spans.push(Span::raw(" ".repeat(pad)));
+            }
+            spans.push(Span::styled(suffix, Style::default().fg(Color::DarkGray)));
+            for &(column, width) in &columns {
+                spans.push(Span::styled(
+                    column.cell(app, session, width),
+                    Style::default().fg(column.color()),
+                ));
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -96,10 +2268,30 @@ fn draw_session_list(f: &mut Frame, app: &mut App, area: Rect) {
     } else {
         ""
     };
+    let workspace = app
+        .workspace
+        .as_ref()
+        .map(|name| format!(" @{}", name))
+        .unwrap_or_default();
+    let direction = match (app.config.screen_reader, app.sort_ascending) {
+        (false, false) => " ↓",
+        (false, true) => " ↑",
+        (true, false) => ", newest first",
+        (true, true) => ", oldest first",
+    };
     let title = if let Some(ref filter) = app.filter_text {
-        format!(" Sessions{} (/{}) ", active_label, filter)
+        format!(
+            " Sessions{}{}{} (/{}) ",
+            direction, workspace, active_label, filter
+        )
     } else {
-        format!(" Sessions{} ({}) ", active_label, sessions.len())
+        format!(
+            " Sessions{}{}{} ({}) ",
+            direction,
+            workspace,
+            active_label,
+            sessions.len()
+        )
     };
 
     let border_color = if app.focus == FocusPanel::Sessions {
@@ -120,10 +2312,18 @@ fn draw_session_list(f: &mut Frame, app: &mut App, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         );
 
-    f.render_stateful_widget(list, area, &mut app.list_state);
+    let mut window_state = ListState::default().with_selected(selected.map(|sel| sel - offset));
+    f.render_stateful_widget(list, area, &mut window_state);
 }
 
-fn draw_session_info(f: &mut Frame, app: &App, area: Rect) {
+fn draw_session_info(f: &mut Frame, app: &mut App, area: Rect) {
+    // (line index, action) for lines that react to clicks
+    let mut actions: Vec<(usize, InfoAction)> = Vec::new();
+    let clickable = Modifier::UNDERLINED;
+    let inner_width = area.width.saturating_sub(2) as usize;
+    // Truncate a value so it fits on one row after its label
+    let fit =
+        |label: &str, value: &str| truncate_width(value, inner_width.saturating_sub(label.width()));
     let content = if let Some(ref id) = app.selected_session {
         if let Some(session) = app.sessions.get(id) {
             let branch = session.git_branch.as_deref().unwrap_or("n/a");
@@ -131,6 +2331,9 @@ fn draw_session_info(f: &mut Frame, app: &App, area: Rect) {
                 .cwd
                 .as_deref()
                 .map(|c| {
+                    if app.privacy {
+                        return "‹hidden›".to_string();
+                    }
                     // Abbreviate home dir
                     if let Some(home) = dirs::home_dir()
                         && let Some(rest) = c.strip_prefix(home.to_str().unwrap_or(""))
@@ -143,31 +2346,86 @@ fn draw_session_info(f: &mut Frame, app: &App, area: Rect) {
 
             let tokens_in = format_tokens(session.total_tokens_in);
             let tokens_out = format_tokens(session.total_tokens_out);
+            let (cost, unpriced) = app.prices.total_cost(&session.usage);
+            let latency_stats = LatencyStats::new(
+                latency::turn_latencies(
+                    &session.messages,
+                    !session.is_active() || session.awaiting_user(),
+                )
+                .iter()
+                .map(|t| t.elapsed),
+            );
 
             let mut info_lines = vec![];
-            if let Some(ref title) = session.custom_title {
+            if let Some(title) = session
+                .user_title
+                .as_ref()
+                .or(session.custom_title.as_ref())
+            {
                 info_lines.push(Line::from(vec![
                     Span::styled("Title: ", Style::default().fg(Color::DarkGray)),
                     Span::styled(
-                        title.as_str(),
+                        fit("Title: ", &app.private(title)),
                         Style::default()
                             .fg(Color::Yellow)
                             .add_modifier(Modifier::BOLD),
                     ),
                 ]));
             }
+            actions.push((info_lines.len(), InfoAction::CopyId(session.id.clone())));
+            if let Some(ref b) = session.git_branch {
+                actions.push((info_lines.len() + 1, InfoAction::FilterBranch(b.clone())));
+            }
+            if let Some(ref c) = session.cwd {
+                actions.push((info_lines.len() + 2, InfoAction::OpenCwd(c.clone())));
+            }
+            if let Some(ref worktree) = session.worktree {
+                actions.push((
+                    info_lines.len() + 3,
+                    InfoAction::FilterRepo(worktree.repo.clone()),
+                ));
+            }
             info_lines.extend([
                 Line::from(vec![
                     Span::styled("ID: ", Style::default().fg(Color::DarkGray)),
-                    Span::styled(&session.id, Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        fit("ID: ", &session.id),
+                        Style::default().fg(Color::DarkGray).add_modifier(clickable),
+                    ),
                 ]),
                 Line::from(vec![
                     Span::styled("Branch: ", Style::default().fg(Color::DarkGray)),
-                    Span::styled(branch, Style::default().fg(Color::Green)),
+                    Span::styled(
+                        fit("Branch: ", branch),
+                        if session.git_branch.is_some() {
+                            Style::default().fg(Color::Green).add_modifier(clickable)
+                        } else {
+                            Style::default().fg(Color::Green)
+                        },
+                    ),
                 ]),
                 Line::from(vec![
                     Span::styled("CWD: ", Style::default().fg(Color::DarkGray)),
-                    Span::styled(cwd, Style::default().fg(Color::White)),
+                    Span::styled(
+                        fit("CWD: ", &cwd),
+                        if session.cwd.is_some() {
+                            Style::default().fg(Color::White).add_modifier(clickable)
+                        } else {
+                            Style::default().fg(Color::White)
+                        },
+                    ),
+                ]),
+                Line::from(vec![
+                    Span::styled("Git: ", Style::default().fg(Color::DarkGray)),
+                    {
+                        let (text, color) = git_status_text(app, session);
+                        let style = if session.worktree.is_some() {
+                            Style::default().fg(color).add_modifier(clickable)
+                        } else {
+                            Style::default().fg(color)
+                        };
+                        Span::styled(fit("Git: ", &text), style)
+                    },
                 ]),
                 Line::from(vec![
                     Span::styled("Tokens: ", Style::default().fg(Color::DarkGray)),
@@ -176,13 +2434,69 @@ fn draw_session_info(f: &mut Frame, app: &App, area: Rect) {
                         Style::default().fg(Color::Cyan),
                     ),
                 ]),
+                Line::from(vec![
+                    Span::styled("Context: ", Style::default().fg(Color::DarkGray)),
+                    match app.config.context.usage(session) {
+                        Some(context) => Span::styled(
+                            format!(
+                                "{} ~{}% of {}",
+                                gauge(context.percent(), 10),
+                                context.percent(),
+                                format_tokens(context.window)
+                            ),
+                            app.config
+                                .context
+                                .level(&context)
+                                .map_or(Style::default().fg(Color::Cyan), context_style),
+                        ),
+                        None => Span::styled("unknown", Style::default().fg(Color::DarkGray)),
+                    },
+                    Span::styled(
+                        match session.compactions.len() {
+                            0 => String::new(),
+                            n => format!(" · compacted {}×", n),
+                        },
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]),
+                Line::from(vec![
+                    Span::styled("Cost: ", Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        format!(
+                            "~{}{}",
+                            app.config.currency.format(cost),
+                            if unpriced > 0 {
+                                " (partly unpriced)"
+                            } else {
+                                ""
+                            }
+                        ),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                ]),
                 Line::from(vec![
                     Span::styled("Messages: ", Style::default().fg(Color::DarkGray)),
                     Span::styled(
-                        session.messages.len().to_string(),
+                        session.message_count().to_string(),
                         Style::default().fg(Color::White),
                     ),
                 ]),
+                Line::from(vec![
+                    Span::styled("Turns: ", Style::default().fg(Color::DarkGray)),
+                    match latency_stats {
+                        Some(stats) => Span::styled(
+                            format!(
+                                "{} · median {} · p90 {} · max {}",
+                                stats.turns,
+                                latency::format_latency(stats.median),
+                                latency::format_latency(stats.p90),
+                                latency::format_latency(stats.max)
+                            ),
+                            Style::default().fg(Color::White),
+                        ),
+                        None => Span::styled("none answered", Style::default().fg(Color::DarkGray)),
+                    },
+                ]),
                 Line::from(vec![
                     Span::styled("Status: ", Style::default().fg(Color::DarkGray)),
                     if session.is_active() {
@@ -197,12 +2511,92 @@ fn draw_session_info(f: &mut Frame, app: &App, area: Rect) {
                     },
                 ]),
             ]);
+            let health = health::health(session, &app.config);
+            let text = match health.state {
+                HealthState::Ok => health.state.label().to_string(),
+                state => format!("{} ({})", state.label(), health.reason),
+            };
+            info_lines.push(Line::from(vec![
+                Span::styled("Health: ", Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    format!("{} ", health.state.glyph()),
+                    Style::default().fg(health_color(health.state)),
+                ),
+                Span::styled(
+                    fit("Health: ✗ ", &text),
+                    Style::default().fg(health_color(health.state)),
+                ),
+            ]));
+            if !session.api_errors.is_empty() || !session.stop_reasons.is_empty() {
+                let mut errors: BTreeMap<&str, usize> = BTreeMap::new();
+                for error in &session.api_errors {
+                    *errors.entry(error.label.as_str()).or_default() += 1;
+                }
+                let stops: usize = session.stop_reasons.values().sum();
+                let details: Vec<String> = errors
+                    .into_iter()
+                    .chain(session.stop_reasons.iter().map(|(r, n)| (r.as_str(), *n)))
+                    .map(|(label, n)| format!("{} ×{}", label, n))
+                    .collect();
+                let last = session.api_errors.last().map_or(String::new(), |e| {
+                    format!(" (last {})", app.clock(e.timestamp))
+                });
+                let text = format!(
+                    "{} errors{}, {} stops: {}",
+                    session.api_errors.len(),
+                    last,
+                    stops,
+                    details.join(", ")
+                );
+                info_lines.push(Line::from(vec![
+                    Span::styled("Failures: ", Style::default().fg(Color::DarkGray)),
+                    Span::styled(fit("Failures: ", &text), Style::default().fg(Color::Red)),
+                ]));
+            }
+            if session.parse_diagnostics.count > 0 {
+                info_lines.push(Line::from(vec![
+                    Span::styled("Parse errors: ", Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        format!(
+                            "{} lines skipped (E to view)",
+                            session.parse_diagnostics.count
+                        ),
+                        Style::default().fg(Color::Red),
+                    ),
+                ]));
+            }
             if let Some(ref summary) = session.summary {
                 info_lines.push(Line::from(vec![
                     Span::styled("Summary: ", Style::default().fg(Color::DarkGray)),
-                    Span::styled(summary.as_str(), Style::default().fg(Color::White)),
+                    Span::styled(
+                        fit("Summary: ", &app.private(summary)),
+                        Style::default().fg(Color::White),
+                    ),
                 ]));
             }
+            if !session.tags.is_empty() {
+                let mut spans = vec![Span::styled("Tags: ", Style::default().fg(Color::DarkGray))];
+                for tag in &session.tags {
+                    spans.push(Span::styled(
+                        format!("#{}", tag),
+                        Style::default().fg(Color::Black).bg(tag_color(tag)),
+                    ));
+                    spans.push(Span::raw(" "));
+                }
+                info_lines.push(Line::from(spans));
+            }
+            if let Some(note) = app.state.notes.get(&session.id) {
+                info_lines.push(Line::from(Span::styled(
+                    "Note:",
+                    Style::default().fg(Color::DarkGray),
+                )));
+                for note_line in note.lines() {
+                    info_lines.push(Line::from(Span::styled(
+                        format!("  {}", fit("  ", note_line)),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                }
+            }
             info_lines
         } else {
             vec![Line::from("No session selected")]
@@ -219,67 +2613,508 @@ fn draw_session_info(f: &mut Frame, app: &App, area: Rect) {
     );
 
     f.render_widget(info, area);
+
+    // Lines are unwrapped, so line i sits on row i inside the border
+    let inner_height = area.height.saturating_sub(2) as usize;
+    app.info_regions = actions
+        .into_iter()
+        .filter(|(line, _)| *line < inner_height)
+        .map(|(line, action)| {
+            let rect = Rect::new(
+                area.x + 1,
+                area.y + 1 + line as u16,
+                area.width.saturating_sub(2),
+                1,
+            );
+            (rect, action)
+        })
+        .collect();
+}
+
+/// Label and style used for a message role in the chat and popups
+fn role_style(msg_type: &MessageType) -> (&'static str, Style) {
+    match msg_type {
+        MessageType::User => (
+            "User",
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+        MessageType::Assistant => (
+            "Assistant",
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ),
+        MessageType::ToolUse => ("Tool", Style::default().fg(Color::Magenta)),
+        MessageType::Progress => ("...", Style::default().fg(Color::DarkGray)),
+        MessageType::Other => ("Other", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+/// Single-column stand-in for a role in the one-line chat style
+fn role_glyph(msg: &SessionMessage) -> &'static str {
+    match msg.msg_type {
+        MessageType::User if msg.is_prompt() => "›",
+        MessageType::User => "↳",
+        MessageType::Assistant => "●",
+        MessageType::ToolUse => "⚙",
+        MessageType::Progress | MessageType::Other => "·",
+    }
+}
+
+/// Split `text` into spans, highlighting case-insensitive occurrences of
+/// `needle` as the chat search finds them. Returns alternating
+/// plain/highlighted spans, so a line with n matches yields 2n + 1 spans.
+fn highlight_matches(text: &str, needle: &str, base: Style) -> Vec<Span<'static>> {
+    let highlight = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = message::find_ignore_case(text, needle, pos) {
+        spans.push(Span::styled(text[pos..found.start].to_string(), base));
+        spans.push(Span::styled(text[found.clone()].to_string(), highlight));
+        pos = found.end;
+    }
+    spans.push(Span::styled(text[pos..].to_string(), base));
+    spans
+}
+
+/// Shorten `text` to at most `max` terminal columns, marking the cut with `…`
+pub fn truncate_width(text: &str, max: usize) -> String {
+    if text.width() <= max {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        // Reserve one column for the ellipsis
+        if used + w + 1 > max {
+            break;
+        }
+        used += w;
+        out.push(c);
+    }
+    if max > 0 {
+        out.push('…');
+    }
+    out
+}
+
+/// Number of rows `lines` occupy when wrapped to `width`
+fn wrapped_height(lines: &[Line], width: u16) -> usize {
+    Paragraph::new(lines.to_vec())
+        .wrap(Wrap { trim: false })
+        .line_count(width)
+}
+
+/// Which side a message's bubble goes on and its background; tool calls
+/// and results stay in the stream
+fn bubble_side(msg: &SessionMessage) -> Option<(bool, Color)> {
+    match msg.msg_type {
+        MessageType::User if msg.is_prompt() => Some((true, Color::Indexed(22))),
+        MessageType::Assistant => Some((false, Color::Indexed(236))),
+        _ => None,
+    }
+}
+
+/// `lines` in a rounded box at most four fifths of `width` wide, against
+/// the right edge or the left, on a `background` fill
+fn bubble(lines: &[Line], width: usize, right: bool, background: Color) -> Vec<Line<'static>> {
+    let max_inner = (width * 4 / 5).saturating_sub(4).max(8);
+    let rows: Vec<Line<'static>> = lines
+        .iter()
+        .flat_map(|line| wrap_line(line, max_inner))
+        .collect();
+    let inner = rows.iter().map(Line::width).max().unwrap_or(0);
+    let indent = if right {
+        " ".repeat(width.saturating_sub(inner + 4))
+    } else {
+        String::new()
+    };
+    let border = Style::default().fg(Color::DarkGray);
+    let fill = Style::default().bg(background);
+
+    let mut out = vec![Line::from(vec![
+        Span::raw(indent.clone()),
+        Span::styled(format!("╭{}╮", "─".repeat(inner + 2)), border),
+    ])];
+    for row in rows {
+        let pad = inner - row.width();
+        let mut spans = vec![
+            Span::raw(indent.clone()),
+            Span::styled("│", border),
+            Span::styled(" ", fill),
+        ];
+        spans.extend(row.spans.into_iter().map(|span| {
+            let style = fill.patch(span.style);
+            Span::styled(span.content, style)
+        }));
+        spans.push(Span::styled(" ".repeat(pad + 1), fill));
+        spans.push(Span::styled("│", border));
+        out.push(Line::from(spans));
+    }
+    out.push(Line::from(vec![
+        Span::raw(indent),
+        Span::styled(format!("╰{}╯", "─".repeat(inner + 2)), border),
+    ]));
+    out
+}
+
+/// `line` broken into rows at most `width` wide, between words where it
+/// can be
+fn wrap_line(line: &Line, width: usize) -> Vec<Line<'static>> {
+    let cells: Vec<(char, Style)> = line
+        .spans
+        .iter()
+        .flat_map(|span| {
+            let style = line.style.patch(span.style);
+            span.content.chars().map(move |c| (c, style))
+        })
+        .collect();
+    let cell_width = |cells: &[(char, Style)]| -> usize {
+        cells.iter().map(|(c, _)| c.width().unwrap_or(0)).sum()
+    };
+    let mut rows: Vec<Vec<(char, Style)>> = Vec::new();
+    let mut row: Vec<(char, Style)> = Vec::new();
+    let mut row_width = 0;
+    // Words keep the spaces after them
+    for word in cells.split_inclusive(|(c, _)| *c == ' ') {
+        let word_width = cell_width(word);
+        if row_width + word_width > width && row_width > 0 {
+            rows.push(std::mem::take(&mut row));
+            row_width = 0;
+        }
+        if word_width <= width {
+            row.extend_from_slice(word);
+            row_width += word_width;
+            continue;
+        }
+        // A word longer than a row is split wherever it runs out
+        for &cell in word {
+            let w = cell.0.width().unwrap_or(0);
+            if row_width + w > width {
+                rows.push(std::mem::take(&mut row));
+                row_width = 0;
+            }
+            row.push(cell);
+            row_width += w;
+        }
+    }
+    rows.push(row);
+    rows.into_iter()
+        .map(|row| {
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            let mut text = String::new();
+            let mut current = None;
+            for (c, style) in row {
+                if let Some(previous) = current.filter(|&s| s != style) {
+                    spans.push(Span::styled(std::mem::take(&mut text), previous));
+                }
+                current = Some(style);
+                text.push(c);
+            }
+            if let Some(style) = current {
+                spans.push(Span::styled(text, style));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn draw_replay_gauge(f: &mut Frame, app: &App, replay: &Replay, area: Rect) {
+    let len = app
+        .sessions
+        .get(&replay.session_id)
+        .map_or(0, |s| s.messages.len());
+    let state = if replay.playing { "▶" } else { "⏸" };
+    let label = format!(
+        " {} {} {}/{} ",
+        state,
+        replay.speed.label(),
+        replay.position,
+        len
+    );
+    let gauge = LineGauge::default()
+        .ratio(replay.progress(len).clamp(0.0, 1.0))
+        .label(Span::styled(label, Style::default().fg(Color::Yellow)))
+        .filled_style(Style::default().fg(Color::Yellow))
+        .unfilled_style(Style::default().fg(Color::DarkGray));
+    f.render_widget(gauge, area);
+}
+
+/// A message's content as shown: secrets masked, and prompts blurred in
+/// privacy mode
+fn displayed_content<'a>(app: &App, msg: &'a SessionMessage) -> Cow<'a, str> {
+    if app.privacy && msg.is_prompt() {
+        return Cow::Owned(redact::blur(&msg.content));
+    }
+    app.redactor.redact(&msg.content)
 }
 
 fn draw_chat_stream(f: &mut Frame, app: &mut App, area: Rect) {
-    let messages = if let Some(ref id) = app.selected_session {
-        app.sessions.get(id).map(|s| &s.messages[..]).unwrap_or(&[])
+    // A replay of this session gets its progress bar under the chat
+    let area = match app.replay {
+        Some(ref replay) if app.selected_session.as_ref() == Some(&replay.session_id) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(1)])
+                .split(area);
+            draw_replay_gauge(f, app, replay, chunks[1]);
+            chunks[0]
+        }
+        _ => area,
+    };
+    let messages = app
+        .selected()
+        .map(|s| &s.messages[..app.visible_message_count(s)])
+        .unwrap_or(&[]);
+    let session_id = app.selected_session.as_deref().unwrap_or("");
+    let map_width = if app.show_minimap && area.width >= MINIMAP_MIN_WIDTH {
+        1
     } else {
-        &[]
+        0
+    };
+    let available = area.width.saturating_sub(2 + map_width);
+    // Text is wrapped to the capped width and placed within what's available
+    let inner_width = match app.config.max_content_width {
+        0 => available,
+        max => available.min(max),
     };
+    // Highlight the chat search, or failing that the list filter's text
+    let filter = app.filter_text.as_deref().map(SessionFilter::parse);
+    let needle = app
+        .chat_search
+        .as_deref()
+        .or_else(|| filter.as_ref().and_then(|f| f.text()));
+    let mut match_count = 0;
+    // Turn latency goes on the header of each turn's last reply
+    let last_complete = app.selected().is_some_and(|s| {
+        messages.len() == s.messages.len() && (!s.is_active() || s.awaiting_user())
+    });
+    let latencies: HashMap<usize, TimeDelta> = latency::turn_latencies(messages, last_complete)
+        .into_iter()
+        .map(|t| (t.last_reply, t.elapsed))
+        .collect();
 
     let mut lines: Vec<Line> = Vec::new();
+    let mut message_lines = Vec::new();
+    let mut visual_lines = 0;
+    let line_limit = app.message_line_limit();
+    let density = app.config.density;
 
-    for msg in messages.iter() {
-        let time = msg
-            .timestamp
-            .with_timezone(&Local)
-            .format("%H:%M")
-            .to_string();
+    for (idx, msg) in messages.iter().enumerate() {
+        // Skip progress messages in the chat view (too noisy)
+        if msg.msg_type == MessageType::Progress {
+            continue;
+        }
 
-        let (prefix, style) = match msg.msg_type {
-            MessageType::User => (
-                "User",
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            MessageType::Assistant => (
-                "Assistant",
-                Style::default()
-                    .fg(Color::Blue)
-                    .add_modifier(Modifier::BOLD),
+        let time = app.clock(msg.timestamp);
+        let class = classify(&app.config.message_classes, msg);
+        if class.is_some_and(|c| app.hidden_classes.contains(&c.name)) {
+            continue;
+        }
+        let (prefix, mut style) = match class {
+            Some(c) => (
+                c.name.as_str(),
+                Style::default().fg(c.color).add_modifier(Modifier::BOLD),
             ),
-            MessageType::ToolUse => ("Tool", Style::default().fg(Color::Magenta)),
-            MessageType::Progress => ("...", Style::default().fg(Color::DarkGray)),
-            MessageType::Other => ("Other", Style::default().fg(Color::DarkGray)),
+            None => role_style(&msg.msg_type),
         };
+        if app.selected_message == Some(idx) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
 
-        // Skip progress messages in the chat view (too noisy)
-        if msg.msg_type == MessageType::Progress {
+        if app.chat_style == ChatStyle::Oneline {
+            let mut spans = vec![
+                Span::styled(format!("{} ", time), Style::default().fg(Color::DarkGray)),
+                Span::styled(role_glyph(msg), style),
+                Span::raw(" "),
+            ];
+            if app.state.is_starred(session_id, &msg.key()) {
+                spans.push(Span::styled("★ ", Style::default().fg(Color::Yellow)));
+            }
+            let content = displayed_content(app, msg);
+            let first = match msg.error {
+                Some(ref error) => format!("error: {}", error),
+                None => content
+                    .lines()
+                    .map(str::trim)
+                    .find(|l| !l.is_empty())
+                    .unwrap_or("")
+                    .to_string(),
+            };
+            let used: usize = spans.iter().map(Span::width).sum();
+            let text = truncate_width(&first, (inner_width as usize).saturating_sub(used));
+            let base = match msg.error {
+                Some(_) => Style::default().fg(Color::Red),
+                None => Style::default().fg(Color::White),
+            };
+            match needle {
+                Some(needle) => {
+                    let matched = highlight_matches(&text, needle, base);
+                    match_count += matched.len() / 2;
+                    spans.extend(matched);
+                }
+                None => spans.push(Span::styled(text, base)),
+            }
+            message_lines.push((idx, visual_lines));
+            visual_lines += 1;
+            lines.push(Line::from(spans));
             continue;
         }
 
-        lines.push(Line::from(vec![
-            Span::styled(format!("[{}] ", time), Style::default().fg(Color::DarkGray)),
-            Span::styled(format!("{}: ", prefix), style),
-        ]));
+        let mut header = Vec::new();
+        if density == Density::Comfortable {
+            header.push(Span::styled(
+                format!("[{}] ", time),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        header.push(Span::styled(format!("{}: ", prefix), style));
+        if app.state.is_starred(session_id, &msg.key()) {
+            header.push(Span::styled("★", Style::default().fg(Color::Yellow)));
+        }
+        if let Some(ref error) = msg.error {
+            header.push(Span::styled(
+                format!("error: {} ", error),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+        if let Some(ref reason) = msg.stop_reason {
+            header.push(Span::styled(
+                format!("stopped: {} ", reason),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if let Some(&elapsed) = latencies.get(&idx) {
+            header.push(Span::styled(
+                format!(
+                    "{} {}",
+                    if app.config.screen_reader {
+                        "took"
+                    } else {
+                        "⏱"
+                    },
+                    latency::format_latency(elapsed)
+                ),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        let mut msg_lines = vec![Line::from(header)];
+        // A bubble's box already sets the body apart
+        let bubbled = app.chat_style == ChatStyle::Bubbles && bubble_side(msg).is_some();
+        // A screen reader hears each line on its own, so each says whose
+        // it is
+        let indent = if app.config.screen_reader {
+            format!("{}: ", prefix)
+        } else if bubbled {
+            String::new()
+        } else {
+            density.indent().to_string()
+        };
 
-        // Truncate long content for display (char-boundary safe)
-        for content_line in msg.content.lines() {
-            lines.push(Line::from(Span::styled(
-                format!("  {}", content_line),
-                Style::default().fg(Color::White),
+        // A matching renderer replaces the plain body; folding and the
+        // line limit then apply to its lines
+        let content = displayed_content(app, msg);
+        let rendered = app.renderers.find(msg).map(|r| match content {
+            Cow::Borrowed(_) => r.render(msg, inner_width as usize),
+            Cow::Owned(ref redacted) => {
+                let msg = SessionMessage {
+                    content: redacted.clone(),
+                    ..msg.clone()
+                };
+                r.render(&msg, inner_width as usize)
+            }
+        });
+        let total = rendered
+            .as_ref()
+            .map_or_else(|| content.lines().count(), Vec::len);
+        let folded = app.is_folded(idx, msg);
+        let shown = if folded {
+            1
+        } else {
+            line_limit.map_or(total, |max| total.min(max))
+        };
+        if let Some(rendered) = rendered {
+            msg_lines.extend(rendered.into_iter().take(shown));
+        } else {
+            for content_line in content.lines().take(shown) {
+                if let Some(hash) = message::commit_line(content_line)
+                    .filter(|_| message::created_commit_at(messages, idx).is_some())
+                {
+                    msg_lines.push(Line::from(vec![
+                        Span::raw(indent.clone()),
+                        Span::styled(
+                            format!(" commit {} ", hash),
+                            Style::default()
+                                .fg(Color::Black)
+                                .bg(Color::LightYellow)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(" i to show", Style::default().fg(Color::DarkGray)),
+                    ]));
+                    continue;
+                }
+                let text = format!("{}{}", indent, content_line);
+                let base = Style::default().fg(Color::White);
+                match needle {
+                    Some(needle) => {
+                        let spans = highlight_matches(&text, needle, base);
+                        match_count += spans.len() / 2;
+                        msg_lines.push(Line::from(spans));
+                    }
+                    None => msg_lines.push(Line::from(Span::styled(text, base))),
+                }
+            }
+        }
+        if folded {
+            msg_lines.push(Line::from(Span::styled(
+                format!("{}▸ {} more lines (o to expand)", indent, total - shown),
+                Style::default().fg(Color::Cyan),
+            )));
+        } else if shown < total {
+            msg_lines.push(Line::from(Span::styled(
+                format!("{}… {} more lines (T to show all)", indent, total - shown),
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
             )));
         }
-        lines.push(Line::from("")); // blank separator
+        if bubbled && let Some((right, background)) = bubble_side(msg) {
+            msg_lines = bubble(&msg_lines, inner_width as usize, right, background);
+        }
+        if density == Density::Comfortable {
+            msg_lines.push(Line::from("")); // blank separator
+        }
+
+        message_lines.push((idx, visual_lines));
+        visual_lines += wrapped_height(&msg_lines, inner_width);
+        lines.extend(msg_lines);
     }
 
     let inner_height = area.height.saturating_sub(2) as usize;
 
     let title = if let Some(ref id) = app.selected_session {
         if let Some(session) = app.sessions.get(id) {
-            format!(" Chat - {} ", session.display_name())
+            let mut suffix = String::new();
+            if needle.is_some() {
+                suffix.push_str(&format!("({} matches) ", match_count));
+            }
+            let held_back = session.messages.len() - app.visible_message_count(session);
+            if app.paused.as_ref().is_some_and(|(id, _)| *id == session.id) {
+                suffix.push_str(&format!("PAUSED (+{} new) ", held_back));
+            }
+            if app.privacy {
+                suffix.push_str("PRIVATE ");
+            }
+            // Keep the status suffix visible by shortening the name first
+            let budget = (available as usize).saturating_sub(" Chat -  ".len() + suffix.width());
+            format!(
+                " Chat - {} {}",
+                truncate_width(&app.private(&session.display_name()), budget),
+                suffix
+            )
         } else {
             " Chat ".to_string()
         }
@@ -297,51 +3132,485 @@ fn draw_chat_stream(f: &mut Frame, app: &mut App, area: Rect) {
         .title(title)
         .border_style(Style::default().fg(chat_border));
 
-    // Use ratatui's built-in line_count for exact wrapped line calculation
-    let paragraph = Paragraph::new(lines)
-        .block(block)
-        .wrap(Wrap { trim: false });
-    let inner_width = area.width.saturating_sub(2);
-    let visual_lines = paragraph.line_count(inner_width);
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
     let max_scroll = visual_lines.saturating_sub(inner_height);
-    let scroll_offset: u16 = if app.chat_scroll_locked_to_bottom {
-        max_scroll as u16
+
+    // Keep the view steady when earlier messages were just prepended
+    if let Some((target, offset)) = app.chat_anchor.take()
+        && let Some(&(_, line)) = message_lines.iter().find(|(idx, _)| *idx == target)
+    {
+        app.chat_scroll = line + offset;
+    }
+
+    // Bring a requested message into view (cursor moves, starred jumps)
+    if let Some(target) = app.chat_jump_to.take()
+        && let Some(&(_, line)) = message_lines.iter().find(|(idx, _)| *idx == target)
+    {
+        let top = if app.chat_scroll_locked_to_bottom {
+            max_scroll
+        } else {
+            app.chat_scroll
+        };
+        if line < top || line >= top + inner_height {
+            app.chat_scroll = line;
+            app.chat_scroll_locked_to_bottom = false;
+        }
+    }
+
+    // Scrolling back down to the end follows new messages again
+    if app.new_messages_below && app.chat_scroll >= max_scroll {
+        app.chat_scroll_locked_to_bottom = true;
+    }
+    if app.chat_scroll_locked_to_bottom {
+        app.chat_scroll = max_scroll;
+        app.new_messages_below = false;
     } else {
         app.chat_scroll = app.chat_scroll.min(max_scroll);
-        app.chat_scroll as u16
-    };
+    }
     app.chat_total_lines = visual_lines;
+    app.chat_message_lines = message_lines;
+    if app
+        .selected_message
+        .is_some_and(|m| !app.chat_message_lines.iter().any(|(idx, _)| *idx == m))
+    {
+        app.selected_message = None;
+    }
+
+    // Where the view is, once there is more than fits
+    let block = if visual_lines > inner_height {
+        let bottom = (app.chat_scroll + inner_height).min(visual_lines);
+        block.title_bottom(
+            Line::from(Span::styled(
+                format!(
+                    " {}% ({}/{} lines) ",
+                    bottom * 100 / visual_lines,
+                    format_count(bottom),
+                    format_count(visual_lines)
+                ),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .right_aligned(),
+        )
+    } else {
+        block
+    };
+    let block = if app.new_messages_below {
+        block.title_bottom(Line::from(Span::styled(
+            " ↓ new messages (G) ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )))
+    } else {
+        block
+    };
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(map_width)])
+        .split(inner);
+    let margin = chunks[0].width.saturating_sub(inner_width);
+    let text_area = Rect {
+        x: match app.config.content_align {
+            ContentAlign::Left => chunks[0].x,
+            ContentAlign::Center => chunks[0].x + margin / 2,
+        },
+        width: inner_width,
+        ..chunks[0]
+    };
+    let chat = paragraph.scroll((app.chat_scroll as u16, 0));
+    f.render_widget(chat, text_area);
+    if app.config.truecolor && app.chat_style == ChatStyle::Stream {
+        tint_messages(f.buffer_mut(), app, text_area);
+    }
+    if map_width > 0 {
+        draw_minimap(f, app, chunks[1]);
+    }
+}
+
+/// Give each message's rows in view its role's tint, across the whole
+/// width, leaving out the blank line after it
+fn tint_messages(buffer: &mut Buffer, app: &App, area: Rect) {
+    let Some(session) = app.selected() else {
+        return;
+    };
+    let starts = &app.chat_message_lines;
+    let separator = usize::from(app.config.density == Density::Comfortable);
+    for (i, &(idx, start)) in starts.iter().enumerate() {
+        let end = starts
+            .get(i + 1)
+            .map_or(app.chat_total_lines, |&(_, next)| next)
+            .saturating_sub(separator);
+        let Some(tint) = session
+            .messages
+            .get(idx)
+            .and_then(|msg| role_tint(msg, app.config.background))
+        else {
+            continue;
+        };
+        let rows = start.max(app.chat_scroll)..end.min(app.chat_scroll + area.height as usize);
+        for row in rows {
+            let y = area.y + (row - app.chat_scroll) as u16;
+            for x in area.left()..area.right() {
+                if let Some(cell) = buffer.cell_mut((x, y))
+                    && cell.bg == Color::Reset
+                {
+                    cell.set_bg(tint);
+                }
+            }
+        }
+    }
+}
+
+/// Chat panes narrower than this leave the minimap out
+const MINIMAP_MIN_WIDTH: u16 = 40;
+
+/// How a message shows on the minimap; the most telling one in a row wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MapMark {
+    /// Tool results and anything else
+    Other,
+    Reply,
+    Tool,
+    Prompt,
+    Error,
+}
+
+impl MapMark {
+    fn of(msg: &SessionMessage) -> Self {
+        if msg.error.is_some() {
+            return MapMark::Error;
+        }
+        match msg.msg_type {
+            MessageType::User if msg.is_prompt() => MapMark::Prompt,
+            MessageType::Assistant => MapMark::Reply,
+            MessageType::ToolUse => MapMark::Tool,
+            _ => MapMark::Other,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            MapMark::Other => Color::DarkGray,
+            MapMark::Reply => Color::Blue,
+            MapMark::Tool => Color::Magenta,
+            MapMark::Prompt => Color::Green,
+            MapMark::Error => Color::Red,
+        }
+    }
+
+    /// Stand-in for the color when drawing without any
+    fn symbol(self) -> &'static str {
+        match self {
+            MapMark::Other => ".",
+            MapMark::Reply => "|",
+            MapMark::Tool => "+",
+            MapMark::Prompt => ">",
+            MapMark::Error => "!",
+        }
+    }
+}
 
-    let chat = paragraph.scroll((scroll_offset, 0));
-    f.render_widget(chat, area);
+/// The whole transcript squeezed into one column, each row standing for
+/// an equal share of its lines, with the rows in view on a gray background
+fn draw_minimap(f: &mut Frame, app: &App, area: Rect) {
+    let Some(session) = app.selected() else {
+        return;
+    };
+    let rows = area.height as usize;
+    let total = app.chat_total_lines;
+    if rows == 0 || total == 0 {
+        return;
+    }
+    // Lines per row, at least one so a short transcript maps one to one
+    let scale = total.div_ceil(rows).max(1);
+    let mut marks: Vec<Option<MapMark>> = vec![None; rows];
+    let starts = &app.chat_message_lines;
+    for (i, &(idx, start)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(total, |&(_, next)| next);
+        let Some(msg) = session.messages.get(idx) else {
+            continue;
+        };
+        let mark = MapMark::of(msg);
+        for row in marks
+            .iter_mut()
+            .take(end.saturating_sub(1) / scale + 1)
+            .skip(start / scale)
+        {
+            *row = (*row).max(Some(mark));
+        }
+    }
+    let view = app.chat_scroll / scale..=(app.chat_scroll + rows).saturating_sub(1) / scale;
+    let lines: Vec<Line> = marks
+        .iter()
+        .enumerate()
+        .map(|(row, mark)| {
+            let mut style = Style::default().fg(mark.map_or(Color::Reset, MapMark::color));
+            if view.contains(&row) {
+                style = style.bg(Color::DarkGray);
+            }
+            let symbol = match mark {
+                Some(mark) if app.config.no_color => mark.symbol(),
+                Some(_) => "▐",
+                None => " ",
+            };
+            Line::from(Span::styled(symbol, style))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), area);
 }
 
 fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
     let mode_text = if app.filter_mode {
-        format!("FILTER: /{}", app.filter_text.as_deref().unwrap_or(""))
+        format!(
+            "FILTER: /{}  (Up/Down: history)",
+            app.filter_text.as_deref().unwrap_or("")
+        )
+    } else if let Some(ref prompt) = app.prompt {
+        format!("{}: {}", prompt.kind.label(), prompt.text)
+    } else if let Some(count) = app.pending_count {
+        format!("{}", count)
+    } else if app.pending_g.is_some() {
+        "g  g:jump".to_string()
+    } else if let Some((bracket, _)) = app.pending_bracket {
+        let direction = if bracket == ']' { "next" } else { "previous" };
+        format!("{}  u:{} prompt", bracket, direction)
+    } else if let Some(msg) = app.status_message() {
+        msg.to_string()
+    } else if app.view != View::Monitor {
+        "q:quit  Esc:monitor  F1-F5/1-5:views  F5:keys".to_string()
     } else {
         {
             let focus_label = match app.focus {
                 FocusPanel::Sessions => "sessions",
                 FocusPanel::Chat => "chat",
+                FocusPanel::Side => "panel",
             };
             format!(
-                "q:quit  Tab:focus({})  j/k:nav  Enter:select  r:refresh  /:filter  a:active({})",
+                "q:quit  F1-F5:views  Tab:focus({})  j/k:nav  gg/G:top/end  Enter:select  r:refresh  /:filter  F:saved  ^W:workspace  C:classes  l:columns  h:heatmap  a:active({})  I:reverse sort  R:rename  N:note  t:tags  J/K:msg  V:chat style  M:minimap  Z:12/24h  ]u/[u:next/prev prompt  s:star  S:starred  f:find  y:copy  p:pause  P:privacy  v:replay  D:compare  L:latency  H:commits  i:show commit  e:file refs  w:urls  W:references  O:outline  x/X:snapshot  m:mouse  U:largest  B:usage",
                 focus_label,
                 if app.show_active_only { "on" } else { "off" }
             )
         }
     };
 
+    // How updates arrive, so a stale view is never a silent one
+    let (watch_label, watch_color) = match app.watcher.as_ref().map(|w| w.poll_interval) {
+        Some(None) => ("● live".to_string(), Color::Green),
+        Some(Some(interval)) if app.watch_limit_reached => (
+            format!("◌ polling {}s (watch limit)", interval.as_secs()),
+            Color::Yellow,
+        ),
+        Some(Some(interval)) => (format!("◌ polling {}s", interval.as_secs()), Color::Yellow),
+        None => ("○ manual refresh only".to_string(), Color::Red),
+    };
+    let counters = counters_line(app);
+    let block = usage_label(app);
+    let block_width = block
+        .as_ref()
+        .map_or(0, |(label, _)| label.width() as u16 + 2);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(counters.width() as u16),
+            Constraint::Length(block_width),
+            Constraint::Length(watch_label.width() as u16 + 2),
+        ])
+        .split(area);
+
     let bar = Paragraph::new(Line::from(vec![
         Span::styled(" ", Style::default()),
         Span::styled(mode_text, Style::default().fg(Color::DarkGray)),
     ]));
+    f.render_widget(bar, chunks[0]);
+    f.render_widget(Paragraph::new(counters), chunks[1]);
+    if let Some((label, color)) = block {
+        f.render_widget(
+            Paragraph::new(Span::styled(
+                format!(" {} ", label),
+                Style::default().fg(color),
+            )),
+            chunks[2],
+        );
+    }
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            format!(" {} ", watch_label),
+            Style::default().fg(watch_color),
+        )),
+        chunks[3],
+    );
+}
+
+/// Active sessions, today's tokens and cost, and a blinking flag when a
+/// session is waiting for the user
+fn counters_line(app: &App) -> Line<'static> {
+    let counters = app.counters;
+    let mut spans = Vec::new();
+    if counters.awaiting_user > 0 {
+        spans.push(Span::styled(
+            format!(" ⚑ {} waiting ", counters.awaiting_user),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(if app.config.screen_reader {
+                    Modifier::BOLD
+                } else {
+                    Modifier::BOLD | Modifier::SLOW_BLINK
+                }),
+        ));
+    }
+    if counters.active > 0 {
+        spans.push(Span::styled(
+            format!(" ● {} active", counters.active),
+            Style::default().fg(Color::Green),
+        ));
+    }
+    spans.push(Span::styled(
+        format!(
+            " today {} · {} ",
+            format_tokens(app.usage.tokens_today),
+            app.config.currency.format(app.usage.cost_today)
+        ),
+        Style::default().fg(Color::Cyan),
+    ));
+    Line::from(spans)
+}
+
+/// Current usage block and daily budget, with projections at the current
+/// burn rate; `None` when there is nothing to report
+fn usage_label(app: &App) -> Option<(String, Color)> {
+    let usage = &app.usage;
+    let mut parts = Vec::new();
+    let mut color = Color::Cyan;
+    if let Some(ref block) = usage.block {
+        let remaining = block.remaining(Utc::now());
+        let limit = app.config.block_token_limit;
+        // A limit of 0 means none: show plain usage without a gauge
+        if let Some(percent) = (block.tokens * 100).checked_div(limit) {
+            color = if percent >= 100 {
+                Color::Red
+            } else if percent >= app.config.block_warn_percent {
+                Color::Yellow
+            } else {
+                Color::Green
+            };
+            parts.push(format!("5h {} {}%", gauge(percent, 10), percent));
+            // Only worth saying if the limit comes before the reset
+            if let Some(eta) = usage.time_to_use(limit.saturating_sub(block.tokens))
+                && eta < remaining
+            {
+                parts.push(format!("limit in ~{}", format_duration(eta)));
+                if color == Color::Green {
+                    color = Color::Yellow;
+                }
+            }
+        } else {
+            parts.push(format!("5h {}", format_tokens(block.tokens)));
+        }
+        parts.push(format!("{} left", format_duration(remaining)));
+    }
+    let budget = app.config.daily_token_budget;
+    if let Some(percent) = (usage.tokens_today * 100).checked_div(budget) {
+        parts.push(format!("today {}%", percent));
+        let now = Utc::now();
+        let midnight = app.zone.day(now).succ_opt().map(|d| app.zone.day_start(d));
+        if let Some(eta) = usage.time_to_use(budget.saturating_sub(usage.tokens_today))
+            && midnight.is_some_and(|m| now + eta < m)
+        {
+            parts.push(format!("budget in ~{}", format_duration(eta)));
+        }
+        if percent >= 100 {
+            color = Color::Red;
+        }
+    }
+    (!parts.is_empty()).then(|| (parts.join(" · "), color))
+}
+
+/// Text progress bar `width` cells wide, full at 100 percent
+fn gauge(percent: u64, width: usize) -> String {
+    let filled = (percent.min(100) as usize * width).div_ceil(100);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+/// Branch, uncommitted files and upstream distance of the session's
+/// working directory as last queried
+fn git_status_text(app: &App, session: &Session) -> (String, Color) {
+    let Some(cwd) = session.cwd.as_deref() else {
+        return ("n/a".to_string(), Color::DarkGray);
+    };
+    let status = match app.git.status(cwd) {
+        None => return ("…".to_string(), Color::DarkGray),
+        Some(None) => return ("not a repository".to_string(), Color::DarkGray),
+        Some(Some(status)) => status,
+    };
+    let mut parts = Vec::new();
+    if let Some(ref worktree) = session.worktree {
+        parts.push(format!(
+            "worktree {} of {}",
+            app.private(&worktree.name),
+            app.private(&worktree.repo)
+        ));
+    }
+    parts.push(status.branch.clone().unwrap_or("detached".to_string()));
+    parts.push(match status.dirty {
+        0 => "clean".to_string(),
+        n => format!("{} uncommitted", n),
+    });
+    match status.ahead_behind {
+        Some((0, 0)) => parts.push("up to date".to_string()),
+        Some((ahead, behind)) => parts.push(format!("↑{} ↓{}", ahead, behind)),
+        None => parts.push("no upstream".to_string()),
+    }
+    let color = if status.dirty > 0 || status.ahead_behind.is_some_and(|(a, _)| a > 0) {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+    (parts.join(" · "), color)
+}
+
+fn health_color(state: HealthState) -> Color {
+    match state {
+        HealthState::Erroring => Color::Red,
+        HealthState::Waiting => Color::Magenta,
+        HealthState::NearLimit => Color::LightRed,
+        HealthState::Slow => Color::Yellow,
+        HealthState::Ok => Color::DarkGray,
+    }
+}
+
+/// Badge style of a session nearing compaction
+fn context_style(level: ContextLevel) -> Style {
+    match level {
+        ContextLevel::Warn => Style::default().fg(Color::Black).bg(Color::Yellow),
+        ContextLevel::Critical => Style::default()
+            .fg(Color::White)
+            .bg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+    }
+}
 
-    f.render_widget(bar, area);
+/// Stable color for a tag chip, derived from the tag name
+fn tag_color(tag: &str) -> Color {
+    const PALETTE: [Color; 6] = [
+        Color::Cyan,
+        Color::Magenta,
+        Color::LightBlue,
+        Color::LightGreen,
+        Color::LightRed,
+        Color::LightYellow,
+    ];
+    let hash = tag.to_lowercase().bytes().fold(0usize, |acc, b| {
+        acc.wrapping_mul(31).wrapping_add(b as usize)
+    });
+    PALETTE[hash % PALETTE.len()]
 }
 
-fn format_tokens(count: u64) -> String {
+pub fn format_tokens(count: u64) -> String {
     if count >= 1_000_000 {
         format!("{:.1}M", count as f64 / 1_000_000.0)
     } else if count >= 1_000 {
@@ -350,3 +3619,111 @@ fn format_tokens(count: u64) -> String {
         count.to_string()
     }
 }
+
+/// A count with thousands separators, such as `12,345`
+pub fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Compact duration such as `2h13m` or `45m`
+pub fn format_duration(d: TimeDelta) -> String {
+    let minutes = d.num_minutes().max(0);
+    if minutes >= 60 {
+        format!("{}h{:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1 << 30 {
+        format!("{:.1}G", bytes as f64 / (1u64 << 30) as f64)
+    } else if bytes >= 1 << 20 {
+        format!("{:.1}M", bytes as f64 / (1u64 << 20) as f64)
+    } else if bytes >= 1 << 10 {
+        format!("{:.1}K", bytes as f64 / (1u64 << 10) as f64)
+    } else {
+        format!("{}B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::*;
+
+    /// Every key the monitor's handler matches, as the help spells it
+    fn monitor_handler_keys() -> Vec<String> {
+        let source = include_str!("app.rs");
+        let start = source.find("pub fn handle_key_event").unwrap();
+        let end = start + source[start..].find("\n    }\n").unwrap();
+        // Keys before this go to the side panel, replay and counts
+        let body = &source[start..end];
+        let body = &body[body.find("match key.code {").unwrap()..];
+        let arm = Regex::new(
+            r"KeyCode::(Char\('(.)'\)( if key\.modifiers\.contains\(KeyModifiers::CONTROL\))?|Char\(c @ \(([^)]*)\)\)|Home|End|PageUp|PageDown|Tab|Enter)",
+        )
+        .unwrap();
+        let mut keys = Vec::new();
+        for caps in arm.captures_iter(body) {
+            if let Some(c) = caps.get(2) {
+                keys.push(match (c.as_str(), caps.get(3)) {
+                    (c, Some(_)) => format!("^{}", c.to_uppercase()),
+                    (" ", None) => "Space".to_string(),
+                    (c, None) => c.to_string(),
+                });
+            } else if let Some(chars) = caps.get(4) {
+                keys.extend(
+                    chars
+                        .as_str()
+                        .split(" | ")
+                        .map(|c| c.trim_matches('\'').to_string()),
+                );
+            } else {
+                keys.push(match &caps[1] {
+                    "PageUp" => "PgUp".to_string(),
+                    "PageDown" => "PgDn".to_string(),
+                    other => other.to_string(),
+                });
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn help_lists_every_monitor_key() {
+        let (_, monitor) = VIEW_KEYS[0];
+        let tokens: Vec<&str> = monitor
+            .iter()
+            .flat_map(|(keys, _)| keys.split([' ', ',']))
+            .filter(|t| !t.is_empty())
+            // `j/k` is two keys, a lone `/` is one
+            .flat_map(|t| {
+                if t == "/" {
+                    vec![t]
+                } else {
+                    t.split('/').collect()
+                }
+            })
+            .filter(|t| !t.is_empty())
+            .collect();
+        let keys = monitor_handler_keys();
+        assert!(keys.len() > 40, "handler not found: {:?}", keys);
+        for key in keys {
+            // `gg` and the `]u`/`[u` motions start with their key
+            let listed = tokens
+                .iter()
+                .any(|t| *t == key || *t == format!("{0}{0}", key) || *t == format!("{}u", key));
+            assert!(listed, "{} is handled but missing from the help", key);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+
+use crate::pricing::PriceTable;
+use crate::session::{Session, UsagePoint};
+use crate::timezone::DisplayZone;
+
+/// Length of a rate-limit block on Claude Pro/Max plans
+pub const BLOCK_HOURS: i64 = 5;
+
+/// How far back to look for the start of the current block; a chain of
+/// back-to-back blocks longer than this may shift its start slightly
+const LOOKBACK_HOURS: i64 = 24;
+
+/// Recent usage averaged into the burn rate
+const BURN_WINDOW_MINUTES: i64 = 15;
+
+/// A rolling usage block: it starts at the hour of the first reply after
+/// the previous block ended and lasts `BLOCK_HOURS`
+#[derive(Debug, Clone)]
+pub struct UsageBlock {
+    pub end: DateTime<Utc>,
+    pub tokens: u64,
+}
+
+impl UsageBlock {
+    /// Time left until the block resets
+    pub fn remaining(&self, now: DateTime<Utc>) -> TimeDelta {
+        (self.end - now).max(TimeDelta::zero())
+    }
+}
+
+/// Usage aggregated across all sessions at one point in time
+#[derive(Debug, Clone, Default)]
+pub struct UsageSnapshot {
+    /// The block containing now, if there has been any usage in it
+    pub block: Option<UsageBlock>,
+    /// Tokens used since midnight in the display zone
+    pub tokens_today: u64,
+    /// Estimated USD cost of today's priced tokens
+    pub cost_today: f64,
+    /// Tokens per minute over the last `BURN_WINDOW_MINUTES`
+    pub burn_rate: f64,
+}
+
+impl UsageSnapshot {
+    pub fn compute(
+        sessions: &HashMap<String, Session>,
+        prices: &PriceTable,
+        zone: &DisplayZone,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let points = points_since(sessions, now - TimeDelta::hours(LOOKBACK_HOURS));
+        let midnight = zone.day_start(zone.day(now));
+        let burn_since = now - TimeDelta::minutes(BURN_WINDOW_MINUTES);
+        let tokens_since = |since: DateTime<Utc>| -> u64 {
+            points
+                .iter()
+                .filter(|p| p.timestamp >= since)
+                .map(|p| p.tokens())
+                .sum()
+        };
+        Self {
+            block: current_block(&points, now),
+            tokens_today: tokens_since(midnight),
+            cost_today: prices
+                .total_cost(points.iter().copied().filter(|p| p.timestamp >= midnight))
+                .0,
+            burn_rate: tokens_since(burn_since) as f64 / BURN_WINDOW_MINUTES as f64,
+        }
+    }
+
+    /// How long `tokens` more will last at the current burn rate
+    pub fn time_to_use(&self, tokens: u64) -> Option<TimeDelta> {
+        if self.burn_rate <= 0.0 {
+            return None;
+        }
+        let minutes = tokens as f64 / self.burn_rate;
+        TimeDelta::try_minutes(minutes.ceil() as i64)
+    }
+}
+
+/// Usage points of every session since `since`, oldest first
+pub fn points_since(sessions: &HashMap<String, Session>, since: DateTime<Utc>) -> Vec<&UsagePoint> {
+    let mut points: Vec<&UsagePoint> = sessions
+        .values()
+        .flat_map(|s| {
+            // Points are in file order, so the recent ones are at the end
+            s.usage
+                .iter()
+                .rev()
+                .take_while(move |p| p.timestamp >= since)
+        })
+        .collect();
+    points.sort_by_key(|p| p.timestamp);
+    points
+}
+
+/// Replay `points` (oldest first) into blocks and return the one holding `now`
+fn current_block(points: &[&UsagePoint], now: DateTime<Utc>) -> Option<UsageBlock> {
+    let mut block: Option<UsageBlock> = None;
+    for point in points {
+        match block {
+            Some(ref mut b) if point.timestamp < b.end => b.tokens += point.tokens(),
+            _ => {
+                let start = point
+                    .timestamp
+                    .duration_trunc(TimeDelta::hours(1))
+                    .unwrap_or(point.timestamp);
+                block = Some(UsageBlock {
+                    end: start + TimeDelta::hours(BLOCK_HOURS),
+                    tokens: point.tokens(),
+                });
+            }
+        }
+    }
+    block.filter(|b| now < b.end)
+}
@@ -1,64 +1,261 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
+use std::time::Duration;
 
 use anyhow::Result;
-use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use clap::ValueEnum;
+use notify::event::ModifyKind;
+use notify::{Config, Event, EventKind, PollWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+
+use crate::session::IgnoreList;
+
+/// Suggested fix shown when the inotify watch limit is exhausted
+pub const WATCH_LIMIT_FIX: &str = "sudo sysctl fs.inotify.max_user_watches=524288";
+
+/// How session file changes are detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum WatcherBackend {
+    /// Native filesystem events, falling back to polling if they fail
+    #[default]
+    Native,
+    /// Periodic scans, for network mounts that never deliver events
+    Poll,
+    /// No watching; refresh manually with `r`
+    Off,
+}
 
 pub enum WatchEvent {
     FileModified(PathBuf),
     FileCreated(PathBuf),
+    /// A transcript or a whole (project) directory was deleted
+    FileRemoved(PathBuf),
+    /// claudy's own config file was written
+    ConfigChanged,
+    /// The OS ran out of watches (inotify `max_user_watches`), so some
+    /// directories are no longer watched
+    WatchLimitReached,
 }
 
 pub struct SessionWatcher {
-    _watcher: RecommendedWatcher,
+    watcher: Box<dyn Watcher + Send>,
+    /// Directories whose project subdirectories get watched
+    watch_paths: Vec<PathBuf>,
     pub rx: mpsc::Receiver<WatchEvent>,
+    /// Scan interval when using the polling backend instead of native
+    /// filesystem events (which network mounts often never deliver)
+    pub poll_interval: Option<Duration>,
 }
 
 impl SessionWatcher {
-    pub fn new(watch_path: PathBuf) -> Result<Self> {
+    /// Watch each of `watch_paths` for new project directories and each
+    /// project directory in them recursively, leaving out the `ignore`d
+    /// projects, plus the directory holding `config_path` (if
+    /// it exists) for config edits. With `poll_interval`, files are scanned
+    /// for changes instead.
+    pub fn new(
+        watch_paths: &[PathBuf],
+        ignore: &IgnoreList,
+        config_path: Option<PathBuf>,
+        poll_interval: Option<Duration>,
+    ) -> Result<Self> {
         let (tx, rx) = mpsc::channel();
+        let handler = event_handler(
+            tx,
+            config_path.as_deref().map(spellings).unwrap_or_default(),
+            watch_paths.to_vec(),
+            ignore.clone(),
+        );
 
-        let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
-            if let Ok(event) = res {
-                for path in &event.paths {
-                    let ext = path.extension().and_then(|e| e.to_str());
-                    let fname = path.file_name().and_then(|n| n.to_str());
-
-                    // Only care about .jsonl files and sessions-index.json
-                    let dominated = ext == Some("jsonl") || fname == Some("sessions-index.json");
-                    if !dominated {
-                        continue;
-                    }
-
-                    let watch_event = match event.kind {
-                        EventKind::Modify(_) => Some(WatchEvent::FileModified(path.clone())),
-                        EventKind::Create(_) => Some(WatchEvent::FileCreated(path.clone())),
-                        _ => None,
-                    };
-                    if let Some(evt) = watch_event {
-                        let _ = tx.send(evt);
-                    }
+        let mut watcher: Box<dyn Watcher + Send> = match poll_interval {
+            Some(interval) => Box::new(PollWatcher::new(
+                handler,
+                Config::default().with_poll_interval(interval),
+            )?),
+            None => {
+                let mut watcher = notify::recommended_watcher(handler)?;
+                // Reduce poll interval for lower latency on macOS FSEvents
+                watcher.configure(Config::default().with_poll_interval(Duration::from_secs(1)))?;
+                Box::new(watcher)
+            }
+        };
+        for watch_path in watch_paths {
+            watcher.watch(watch_path, RecursiveMode::NonRecursive)?;
+            for entry in std::fs::read_dir(watch_path)?.flatten() {
+                let project = entry.path();
+                if project.is_dir() && !ignore.ignores(&entry.file_name().to_string_lossy()) {
+                    watcher.watch(&project, RecursiveMode::Recursive)?;
                 }
             }
-        })?;
-
-        // Reduce poll interval for lower latency on macOS FSEvents
-        watcher
-            .configure(Config::default().with_poll_interval(std::time::Duration::from_secs(1)))?;
-        watcher.watch(&watch_path, RecursiveMode::Recursive)?;
+            tracing::info!(path = %watch_path.display(), ?poll_interval, "watching sessions");
+        }
+        if let Some(config_dir) = config_path.as_deref().and_then(Path::parent)
+            && config_dir.is_dir()
+        {
+            // Watch the directory, not the file: editors often save by
+            // writing a temp file and renaming it over the original
+            watcher.watch(config_dir, RecursiveMode::NonRecursive)?;
+        }
 
         Ok(Self {
-            _watcher: watcher,
+            watcher,
+            watch_paths: watch_paths.to_vec(),
             rx,
+            poll_interval,
         })
     }
 
-    /// Non-blocking poll for watch events
-    pub fn poll(&self) -> Vec<WatchEvent> {
+    /// Non-blocking poll for watch events, watching new project
+    /// directories as they appear
+    pub fn poll(&mut self) -> Vec<WatchEvent> {
         let mut events = Vec::new();
         while let Ok(evt) = self.rx.try_recv() {
+            if let WatchEvent::FileCreated(path) = &evt
+                && is_project_dir(path, &self.watch_paths)
+                && let Err(e) = self.watcher.watch(path, RecursiveMode::Recursive)
+            {
+                tracing::warn!(error = %e, path = %path.display(), "watching project failed");
+                if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) {
+                    events.push(WatchEvent::WatchLimitReached);
+                }
+            }
             events.push(evt);
         }
         events
     }
 }
+
+/// Whether starting a watcher failed because the OS watch limit is exhausted
+pub fn is_watch_limit(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<notify::Error>()
+        .is_some_and(|e| matches!(e.kind, notify::ErrorKind::MaxFilesWatch))
+}
+
+/// Whether `path` is a directory directly inside one of `watch_paths`
+fn is_project_dir(path: &Path, watch_paths: &[PathBuf]) -> bool {
+    path.is_dir()
+        && path
+            .parent()
+            .is_some_and(|parent| watch_paths.iter().any(|p| p == parent))
+}
+
+/// Translate raw notify events into `WatchEvent`s on `tx`, dropping those
+/// in ignored projects under `watch_paths`
+fn event_handler(
+    tx: mpsc::Sender<WatchEvent>,
+    config_files: Vec<PathBuf>,
+    watch_paths: Vec<PathBuf>,
+    ignore: IgnoreList,
+) -> impl Fn(Result<Event, notify::Error>) + Send + 'static {
+    move |res: Result<Event, notify::Error>| {
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!(error = %e, "watcher error");
+                if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) {
+                    let _ = tx.send(WatchEvent::WatchLimitReached);
+                }
+                return;
+            }
+        };
+        for path in &event.paths {
+            let ext = path.extension().and_then(|e| e.to_str());
+            let fname = path.file_name().and_then(|n| n.to_str());
+
+            if config_files.contains(path) {
+                if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                    tracing::debug!(kind = ?event.kind, "config file event");
+                    let _ = tx.send(WatchEvent::ConfigChanged);
+                }
+                continue;
+            }
+            if ignore.ignores_path(path, &watch_paths) {
+                continue;
+            }
+
+            // Removing a directory takes its sessions with it
+            let transcript_or_dir = ext == Some("jsonl") || ext.is_none();
+            if let EventKind::Remove(_) = event.kind
+                && transcript_or_dir
+            {
+                tracing::debug!(path = %path.display(), "remove event");
+                let _ = tx.send(WatchEvent::FileRemoved(path.clone()));
+                continue;
+            }
+
+            // Renames (including temp-file-and-rename writes) report the old
+            // and/or new path depending on the platform; whichever side still
+            // exists is new content, the other side is gone
+            if let EventKind::Modify(ModifyKind::Name(mode)) = event.kind {
+                if transcript_or_dir {
+                    tracing::debug!(?mode, path = %path.display(), "rename event");
+                    let _ = tx.send(if path.exists() {
+                        WatchEvent::FileCreated(path.clone())
+                    } else {
+                        WatchEvent::FileRemoved(path.clone())
+                    });
+                }
+                continue;
+            }
+
+            // A new project directory; the already written sessions in it
+            // come with it, and it needs watching itself
+            if let EventKind::Create(_) = event.kind
+                && is_project_dir(path, &watch_paths)
+            {
+                tracing::debug!(path = %path.display(), "directory created");
+                let _ = tx.send(WatchEvent::FileCreated(path.clone()));
+                continue;
+            }
+
+            // Only care about .jsonl files and sessions-index.json
+            let dominated = ext == Some("jsonl") || fname == Some("sessions-index.json");
+            if !dominated {
+                continue;
+            }
+
+            let watch_event = match event.kind {
+                EventKind::Modify(_) => Some(WatchEvent::FileModified(path.clone())),
+                EventKind::Create(_) => Some(WatchEvent::FileCreated(path.clone())),
+                _ => None,
+            };
+            if let Some(evt) = watch_event {
+                tracing::debug!(kind = ?event.kind, path = %path.display(), "watch event");
+                let _ = tx.send(evt);
+            }
+        }
+    }
+}
+
+/// `file` as given and with its directory canonicalized: notify may
+/// report either (e.g. /private/var for /var on macOS)
+fn spellings(file: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![file.to_path_buf()];
+    if let (Some(dir), Some(name)) = (file.parent(), file.file_name())
+        && let Ok(dir) = dir.canonicalize()
+        && dir.join(name) != file
+    {
+        paths.push(dir.join(name));
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_events_match_the_whole_path() {
+        let base = tempfile::tempdir().unwrap();
+        let base = base.path();
+        std::fs::create_dir_all(base.join("real/claudy")).unwrap();
+        std::os::unix::fs::symlink(base.join("real"), base.join("link")).unwrap();
+        let paths = spellings(&base.join("link/claudy/config.toml"));
+        let real = base.canonicalize().unwrap().join("real/claudy/config.toml");
+        assert!(paths.contains(&base.join("link/claudy/config.toml")));
+        assert!(paths.contains(&real));
+        // A project file that merely ends the same way isn't the config
+        assert!(!paths.contains(&base.join("other/claudy/config.toml")));
+    }
+}